@@ -0,0 +1,230 @@
+use crate::settings::MenuColors;
+use crate::storage;
+use crate::strings::Strings;
+use crate::{draw_centered_text, SCREEN_SIZE, TARGET_LENGTH};
+use ggez::graphics::{self, Color, Rect, Text};
+use ggez::mint::Point2;
+use ggez::{Context, GameResult};
+use std::collections::{BTreeMap, VecDeque};
+use std::time::Duration;
+
+const ACHIEVEMENTS_FILE: &str = "/achievements.txt";
+
+const TOAST_LENGTH: Duration = Duration::from_millis(2500);
+// How long the toast takes to slide in, and again to slide back out
+const TOAST_SLIDE: Duration = Duration::from_millis(300);
+const TOAST_SIZE: (f32, f32) = (360.0, 64.0);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Achievement {
+    FirstBite,
+    HalfWay,
+    Untouchable,
+}
+
+impl Achievement {
+    pub const ALL: [Achievement; 3] = [
+        Achievement::FirstBite,
+        Achievement::HalfWay,
+        Achievement::Untouchable,
+    ];
+
+    fn name(self, strings: &Strings) -> String {
+        strings.get(&format!("achievement.{}", self.key()))
+    }
+
+    fn description(self, strings: &Strings) -> String {
+        strings.get(&format!("achievement.{}.description", self.key()))
+    }
+
+    // Used in the save file and for looking up the name and description
+    fn key(self) -> &'static str {
+        match self {
+            Achievement::FirstBite => "first_bite",
+            Achievement::HalfWay => "half_way",
+            Achievement::Untouchable => "untouchable",
+        }
+    }
+
+    fn earned(self, run: &RunProgress) -> bool {
+        match self {
+            Achievement::FirstBite => run.food_eaten >= 1,
+            Achievement::HalfWay => run.length >= TARGET_LENGTH / 2,
+            Achievement::Untouchable => run.time >= Duration::from_secs(120),
+        }
+    }
+}
+
+// Whatever the achievements need to know about the run in progress
+pub struct RunProgress {
+    pub food_eaten: u32,
+    pub length: u32,
+    pub time: Duration,
+}
+
+#[derive(Debug, Default)]
+pub struct Achievements {
+    unlocked: Vec<Achievement>,
+    // Unlocked but not shown yet, in the order they were unlocked
+    toasts: VecDeque<Achievement>,
+    toast_started: Option<Duration>,
+}
+
+impl Achievements {
+    pub fn load(ctx: &Context) -> Self {
+        let entries = storage::load(ctx, ACHIEVEMENTS_FILE);
+        Achievements {
+            unlocked: Achievement::ALL
+                .into_iter()
+                .filter(|achievement| entries.contains_key(achievement.key()))
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    fn save(&self, ctx: &Context) {
+        let entries: BTreeMap<String, String> = self
+            .unlocked
+            .iter()
+            .map(|achievement| (achievement.key().to_string(), "unlocked".to_string()))
+            .collect();
+        storage::save(ctx, ACHIEVEMENTS_FILE, &entries);
+    }
+
+    pub fn is_unlocked(&self, achievement: Achievement) -> bool {
+        self.unlocked.contains(&achievement)
+    }
+
+    // Called every tick. Anything already unlocked (this run or any earlier one) is skipped,
+    // so nothing toasts twice
+    pub fn check(&mut self, ctx: &Context, run: &RunProgress) {
+        let mut changed = false;
+        for achievement in Achievement::ALL {
+            if !self.is_unlocked(achievement) && achievement.earned(run) {
+                self.unlocked.push(achievement);
+                self.toasts.push_back(achievement);
+                changed = true;
+            }
+        }
+        if changed {
+            self.save(ctx);
+        }
+    }
+
+    // Moves on to the next toast once the current one is done, so they queue up
+    // rather than piling on top of each other
+    pub fn update_toasts(&mut self, now: Duration) {
+        match self.toast_started {
+            Some(started) if now - started >= TOAST_LENGTH => {
+                self.toasts.pop_front();
+                self.toast_started = None;
+            }
+            None if !self.toasts.is_empty() => self.toast_started = Some(now),
+            _ => (),
+        }
+    }
+
+    pub fn draw_toast(
+        &self,
+        canvas: &mut graphics::Canvas,
+        ctx: &mut Context,
+        strings: &Strings,
+        colors: &MenuColors,
+        reduce_motion: bool,
+    ) -> GameResult {
+        let (Some(achievement), Some(started)) = (self.toasts.front(), self.toast_started) else {
+            return Ok(());
+        };
+
+        // Slides down from above the screen, sits for a bit, then slides back up.
+        // With reduced motion it just appears in place for the whole time
+        let elapsed = ctx.time.time_since_start() - started;
+        let remaining = TOAST_LENGTH.saturating_sub(elapsed);
+        let shown = if reduce_motion {
+            1.0
+        } else {
+            (elapsed.min(remaining).as_secs_f32() / TOAST_SLIDE.as_secs_f32()).min(1.0)
+        };
+        let y = -TOAST_SIZE.1 + shown * (TOAST_SIZE.1 + 8.0);
+        let x = SCREEN_SIZE.0 / 2.0 - TOAST_SIZE.0 / 2.0;
+
+        canvas.draw(
+            &graphics::Quad,
+            graphics::DrawParam::new()
+                .dest_rect(Rect::new(x, y, TOAST_SIZE.0, TOAST_SIZE.1))
+                .color(Color {
+                    a: 0.85,
+                    ..colors.shade
+                }),
+        );
+
+        let mut heading = Text::new(strings.get("achievements.toast"));
+        heading.set_scale(16.0);
+        heading.fragments_mut()[0].color = Some(colors.highlight);
+        draw_centered_text(canvas, ctx, &heading, y + 8.0, colors.text)?;
+
+        let mut name = Text::new(achievement.name(strings));
+        name.set_scale(26.0);
+        draw_centered_text(canvas, ctx, &name, y + 28.0, colors.text)
+    }
+
+    // The full list, with anything still locked greyed out
+    pub fn draw_list(
+        &self,
+        canvas: &mut graphics::Canvas,
+        ctx: &mut Context,
+        strings: &Strings,
+        colors: &MenuColors,
+    ) -> GameResult {
+        let mut title = Text::new(strings.get("achievements.heading"));
+        title.set_scale(40.0);
+        draw_centered_text(canvas, ctx, &title, 60.0, colors.text)?;
+
+        for (i, achievement) in Achievement::ALL.into_iter().enumerate() {
+            let y = 150.0 + i as f32 * 80.0;
+            let color = if self.is_unlocked(achievement) {
+                colors.highlight
+            } else {
+                colors.dim_text
+            };
+
+            let mut name = Text::new(achievement.name(strings));
+            name.set_scale(28.0);
+            name.fragments_mut()[0].color = Some(color);
+            canvas.draw(&name, Point2 { x: 160.0, y });
+
+            let status = if self.is_unlocked(achievement) {
+                "achievements.unlocked"
+            } else {
+                "achievements.locked"
+            };
+            let mut status = Text::new(strings.get(status));
+            status.set_scale(20.0);
+            status.fragments_mut()[0].color = Some(color);
+            let width = status.measure(ctx)?.x;
+            canvas.draw(
+                &status,
+                Point2 {
+                    x: SCREEN_SIZE.0 - 160.0 - width,
+                    y: y + 6.0,
+                },
+            );
+
+            let mut description = Text::new(achievement.description(strings));
+            description.set_scale(18.0);
+            canvas.draw(
+                &description,
+                graphics::DrawParam::new()
+                    .dest(Point2 {
+                        x: 160.0,
+                        y: y + 34.0,
+                    })
+                    .color(colors.text),
+            );
+        }
+
+        let mut hint = Text::new(strings.get("back_hint"));
+        hint.set_scale(16.0);
+        draw_centered_text(canvas, ctx, &hint, SCREEN_SIZE.1 - 24.0, colors.text)
+    }
+}
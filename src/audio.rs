@@ -0,0 +1,1367 @@
+use crate::settings::Settings;
+use crate::{GridPosition, GRID_SIZE};
+use cpal::traits::{DeviceTrait, HostTrait};
+use ggez::audio::{AudioContext, SoundData, SoundSource, Source, SpatialSource};
+use ggez::{Context, GameResult};
+use oorandom::Rand32;
+use std::path::{Path, PathBuf};
+
+// The gameplay track plays quieter than everything else at the same music volume
+const GAME_MUSIC_VOLUME: f32 = 0.25;
+// How long one track takes to fade out while the next fades in
+const CROSSFADE_LENGTH: std::time::Duration = std::time::Duration::from_secs(1);
+// The gameplay track gets out of the way a bit quicker than that when a run ends
+const RUN_END_FADE_LENGTH: std::time::Duration = std::time::Duration::from_millis(700);
+// How often to look for a sound device again after losing the last one
+const AUDIO_RETRY_INTERVAL: std::time::Duration = std::time::Duration::from_secs(3);
+// How often to check the sounds are still going to the system's default output. A device
+// going away never shows up as an error anywhere the game can see it, so it's looked for
+const DEVICE_CHECK_INTERVAL: std::time::Duration = std::time::Duration::from_secs(2);
+// How much of its volume the music keeps while the death sting plays over it
+const DUCKED_MUSIC_VOLUME: f32 = 0.2;
+// Every sound is looked for under each of these in turn, so a differently encoded file can
+// just be dropped into resources in place of the old one
+const AUDIO_EXTENSIONS: [&str; 3] = ["ogg", "wav", "mp3"];
+// With the embed-audio feature the stock sounds are built into the executable, so it can
+// be handed out on its own without a resources folder. That puts several megabytes of
+// music into the binary and slows every build down, so it's off unless a packager asks
+// for it. A file on disk still comes first, so the music can be swapped out either way
+#[cfg(feature = "embed-audio")]
+const EMBEDDED: [(&str, &[u8]); 5] = [
+    ("/snake_jazz", include_bytes!("../resources/snake_jazz.mp3")),
+    (
+        "/megalovania",
+        include_bytes!("../resources/megalovania.mp3"),
+    ),
+    (
+        "/congratulations",
+        include_bytes!("../resources/congratulations.mp3"),
+    ),
+    ("/sad_violin", include_bytes!("../resources/sad_violin.mp3")),
+    ("/snake", include_bytes!("../resources/snake.mp3")),
+];
+#[cfg(not(feature = "embed-audio"))]
+const EMBEDDED: [(&str, &[u8]); 0] = [];
+// Anything in here is played during games in place of the usual gameplay track
+const PLAYLIST_DIR: &str = "/music/gameplay";
+const PLAYLIST_EXTENSIONS: [&str; 4] = ["mp3", "ogg", "wav", "flac"];
+// There's no telling what tempo someone's own songs are, so rhythm mode assumes this
+const PLAYLIST_BPM: f32 = 120.0;
+// Each step of a combo plays the eat sound a semitone higher, up to this many
+const EAT_PITCH_MAX_STEPS: u32 = 12;
+// The tick click goes off several times a second, so it sits well under the other effects
+const TICK_SOUND_VOLUME: f32 = 0.3;
+
+// The short sounds played over everything else
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Sfx {
+    // The death sting on its own, without ducking the music like the one on the loss
+    // screen does
+    Death,
+    // A menu button going off
+    Click,
+    // The keyboard moving between buttons or rows
+    Select,
+    // The snake taking a step
+    Tick,
+}
+
+// What Sound needs from whatever actually makes the noise. In the game that's a ggez
+// Source, which wants the Context to play. The tests put in a stand-in that only keeps
+// track of what was done to it, so the fading and sting logic can run without a device
+pub trait AudioSource {
+    type Context;
+
+    fn play(&mut self, ctx: &Self::Context) -> GameResult;
+    fn pause(&self);
+    fn resume(&self);
+    fn playing(&self) -> bool;
+    fn set_volume(&mut self, volume: f32);
+    fn set_pitch(&mut self, pitch: f32);
+    // Where the next play starts from
+    fn set_start(&mut self, start: std::time::Duration);
+    // How long it's been heard for since it was last played
+    fn elapsed(&self) -> std::time::Duration;
+}
+
+impl AudioSource for Source {
+    type Context = Context;
+
+    fn play(&mut self, ctx: &Context) -> GameResult {
+        SoundSource::play(self, ctx)
+    }
+
+    fn pause(&self) {
+        SoundSource::pause(self);
+    }
+
+    fn resume(&self) {
+        SoundSource::resume(self);
+    }
+
+    fn playing(&self) -> bool {
+        SoundSource::playing(self)
+    }
+
+    fn set_volume(&mut self, volume: f32) {
+        SoundSource::set_volume(self, volume);
+    }
+
+    fn set_pitch(&mut self, pitch: f32) {
+        SoundSource::set_pitch(self, pitch);
+    }
+
+    fn set_start(&mut self, start: std::time::Duration) {
+        SoundSource::set_start(self, start);
+    }
+
+    fn elapsed(&self) -> std::time::Duration {
+        SoundSource::elapsed(self)
+    }
+}
+
+// A sound that might not have loaded. A missing file shouldn't stop the game starting, so
+// it's reported once and from then on everything done with it just does nothing
+pub struct Sound<S = Source>(Option<S>);
+
+impl<S: AudioSource> Sound<S> {
+    // Takes a name without an extension, like "/eat", and plays whatever find_sound
+    // turns up for it
+    fn find(loader: &impl SoundLoader<Source = S>, name: &str, repeat: bool) -> Self {
+        Sound(find_sound(loader, name, |file| loader.load(file, repeat)))
+    }
+
+    // Starts over from the beginning, even if it's already going. One that won't play is
+    // a file that can't be decoded, so it goes silent for good rather than erroring every
+    // time it comes up
+    pub fn play(&mut self, ctx: &S::Context) {
+        if let Some(source) = &mut self.0 {
+            if let Err(e) = source.play(ctx) {
+                eprintln!("Could not play a sound, so it won't be heard: {}", e);
+                self.0 = None;
+            }
+        }
+    }
+
+    pub fn playing(&self) -> bool {
+        self.0.as_ref().is_some_and(|source| source.playing())
+    }
+
+    fn loaded(&self) -> bool {
+        self.0.is_some()
+    }
+
+    pub fn pause(&self) {
+        if let Some(source) = &self.0 {
+            source.pause();
+        }
+    }
+
+    fn resume(&self) {
+        if let Some(source) = &self.0 {
+            source.resume();
+        }
+    }
+
+    fn elapsed(&self) -> std::time::Duration {
+        self.0
+            .as_ref()
+            .map_or(std::time::Duration::ZERO, |source| source.elapsed())
+    }
+
+    fn set_start(&mut self, start: std::time::Duration) {
+        if let Some(source) = &mut self.0 {
+            source.set_start(start);
+        }
+    }
+
+    fn set_pitch(&mut self, pitch: f32) {
+        if let Some(source) = &mut self.0 {
+            source.set_pitch(pitch);
+        }
+    }
+
+    fn set_volume(&mut self, volume: f32) {
+        if let Some(source) = &mut self.0 {
+            source.set_volume(volume);
+        }
+    }
+}
+
+// Where across the stereo field a spot on the board is heard, from -1 for the left
+// column to 1 for the right
+fn pan(pos: GridPosition) -> f32 {
+    pos.x as f32 / (GRID_SIZE.0 - 1) as f32 * 2.0 - 1.0
+}
+
+// Somewhere a sound can be read from
+#[derive(Debug, PartialEq, Eq)]
+pub enum SoundFile {
+    Disk(PathBuf),
+    Embedded(&'static [u8]),
+}
+
+impl std::fmt::Display for SoundFile {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            SoundFile::Disk(path) => write!(f, "{}", path.display()),
+            SoundFile::Embedded(_) => write!(f, "the built in copy"),
+        }
+    }
+}
+
+// Where load_step gets the sounds from. In the game that's resources, read through the
+// Context. The tests put in a folder holding whatever files they say it does
+pub trait SoundLoader {
+    type Source;
+
+    fn exists(&self, path: &Path) -> bool;
+    // Everything in the folder, or nothing if there's no such folder
+    fn read_dir(&self, dir: &str) -> Vec<PathBuf>;
+    // None if it won't load, which is logged here
+    fn load(&self, file: &SoundFile, repeat: bool) -> Option<Self::Source>;
+    fn load_eat(&self, file: &SoundFile) -> Option<EatSound>;
+}
+
+impl SoundLoader for Context {
+    type Source = Source;
+
+    fn exists(&self, path: &Path) -> bool {
+        self.fs.exists(path)
+    }
+
+    fn read_dir(&self, dir: &str) -> Vec<PathBuf> {
+        self.fs
+            .read_dir(dir)
+            .map(|entries| entries.collect())
+            .unwrap_or_default()
+    }
+
+    fn load(&self, file: &SoundFile, repeat: bool) -> Option<Source> {
+        match sound_data(self, file).and_then(|data| Source::from_data(self, data)) {
+            Ok(mut source) => {
+                source.set_repeat(repeat);
+                Some(source)
+            }
+            Err(e) => {
+                eprintln!("Could not load {}: {}", file, e);
+                None
+            }
+        }
+    }
+
+    // If it can't be set up to come from anywhere it just plays down the middle
+    fn load_eat(&self, file: &SoundFile) -> Option<EatSound> {
+        let data = sound_data(self, file)
+            .map_err(|e| eprintln!("Could not load {}: {}", file, e))
+            .ok()?;
+        match SpatialSource::from_data(self, data.clone()) {
+            Ok(source) => Some(EatSound::Spatial(source)),
+            Err(_) => Source::from_data(self, data)
+                .map_err(|e| eprintln!("Could not load {}: {}", file, e))
+                .ok()
+                .map(|source| EatSound::Mono(Sound(Some(source)))),
+        }
+    }
+}
+
+fn sound_data(ctx: &Context, file: &SoundFile) -> GameResult<SoundData> {
+    match file {
+        SoundFile::Disk(path) => SoundData::new(ctx, path),
+        SoundFile::Embedded(bytes) => Ok(SoundData::from_bytes(bytes)),
+    }
+}
+
+// Everywhere the name could come from, best first: each of its AUDIO_EXTENSIONS that's
+// there, then the built in copy if there is one. Nothing at all means it stays silent
+fn sound_files(
+    name: &str,
+    exists: impl Fn(&Path) -> bool,
+    embedded: &[(&str, &'static [u8])],
+) -> Vec<SoundFile> {
+    AUDIO_EXTENSIONS
+        .iter()
+        .map(|ext| PathBuf::from(format!("{}.{}", name, ext)))
+        .filter(|path| exists(path))
+        .map(SoundFile::Disk)
+        .chain(
+            embedded
+                .iter()
+                .filter(|(embedded, _)| *embedded == name)
+                .map(|(_, bytes)| SoundFile::Embedded(bytes)),
+        )
+        .collect()
+}
+
+// The first of sound_files that loads. Whichever it is gets logged so it's clear what's
+// actually being played
+fn find_sound<T>(
+    loader: &impl SoundLoader,
+    name: &str,
+    load: impl Fn(&SoundFile) -> Option<T>,
+) -> Option<T> {
+    for file in sound_files(name, |path| loader.exists(path), &EMBEDDED) {
+        if let Some(loaded) = load(&file) {
+            eprintln!("Playing {} for {}", file, name);
+            return Some(loaded);
+        }
+    }
+    eprintln!(
+        "Could not find {} as any of {:?}, so it won't be heard",
+        name, AUDIO_EXTENSIONS
+    );
+    None
+}
+
+// The name of the system's default output, or None if there isn't one
+fn default_device() -> Option<String> {
+    cpal::default_host()
+        .default_output_device()
+        .and_then(|device| device.name().ok())
+}
+
+// The eat sound, which climbs in pitch as a combo goes on and drops back when it breaks,
+// and comes from whichever side of the board the food was on
+pub enum EatSound {
+    Spatial(SpatialSource),
+    Mono(Sound),
+}
+
+impl EatSound {
+    const NAME: &'static str = "/eat";
+
+    fn load(loader: &impl SoundLoader) -> Self {
+        find_sound(loader, Self::NAME, |file| loader.load_eat(file))
+            .unwrap_or(EatSound::Mono(Sound(None)))
+    }
+
+    fn set_volume(&mut self, volume: f32) {
+        match self {
+            EatSound::Spatial(source) => source.set_volume(volume),
+            EatSound::Mono(sound) => sound.set_volume(volume),
+        }
+    }
+
+    // A combo level of 0 is the sound as recorded. Like any Sound, one that won't play
+    // goes silent
+    pub fn play(&mut self, ctx: &Context, combo_level: u32, pos: GridPosition) {
+        let steps = combo_level.min(EAT_PITCH_MAX_STEPS);
+        let pitch = 2f32.powf(steps as f32 / 12.0);
+        match self {
+            EatSound::Spatial(source) => {
+                // The ears sit at -1 and 1 along x, so this puts it right by one of them
+                // at the board's edges
+                source.set_position([pan(pos), 0.0, 0.0]);
+                source.set_pitch(pitch);
+                if let Err(e) = source.play(ctx) {
+                    eprintln!("Could not play {}, so it won't be heard: {}", Self::NAME, e);
+                    *self = EatSound::Mono(Sound(None));
+                }
+            }
+            EatSound::Mono(sound) => {
+                sound.set_pitch(pitch);
+                sound.play(ctx);
+            }
+        }
+    }
+}
+
+// The music that goes with each part of the game. Only one is ever wanted at a time
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Track {
+    Title,
+    Game,
+    Win,
+    Loss,
+}
+
+impl Track {
+    const ALL: [Track; 4] = [Track::Title, Track::Game, Track::Win, Track::Loss];
+
+    // Without the extension, which is whatever find_sound turns up from sound_files
+    fn name(self) -> &'static str {
+        match self {
+            Track::Title => "/snake_jazz",
+            Track::Game => "/megalovania",
+            Track::Win => "/congratulations",
+            Track::Loss => "/sad_violin",
+        }
+    }
+
+    // The tempo rhythm mode moves the snake to. Only the gameplay track is ever used
+    // for it, but every track has one
+    fn bpm(self) -> f32 {
+        match self {
+            Track::Title => 100.0,
+            Track::Game => 120.0,
+            Track::Win => 112.0,
+            Track::Loss => 70.0,
+        }
+    }
+
+    // How loud it plays at full music volume
+    fn volume(self) -> f32 {
+        match self {
+            Track::Game => GAME_MUSIC_VOLUME,
+            _ => 1.0,
+        }
+    }
+}
+
+struct Channel<S> {
+    // Just the one for most tracks, which loops. A playlist plays each song once through
+    songs: Vec<Sound<S>>,
+    song: usize,
+    bpm: f32,
+    // How far faded in it is, from 0 to 1
+    level: f32,
+    pitch: f32,
+    // Where in the song the current play started, and where it'll pick up from next time.
+    // Kept here rather than trusting the Source, which forgets its place whenever it has to
+    // be played over again for a new pitch
+    started_at: std::time::Duration,
+    position: std::time::Duration,
+    // Set when the song can't just be resumed, because it's never been played or its pitch
+    // or position has changed since, so it has to be played again from position
+    stale: bool,
+    // Set when the fade paused it, which is how a song that's stopped because it ran out
+    // is told apart from one that's only waiting to come back
+    paused: bool,
+}
+
+impl<S: AudioSource> Channel<S> {
+    fn new(songs: Vec<Sound<S>>, bpm: f32) -> Self {
+        Channel {
+            songs,
+            song: 0,
+            bpm,
+            level: 0.0,
+            pitch: 1.0,
+            started_at: std::time::Duration::ZERO,
+            position: std::time::Duration::ZERO,
+            stale: true,
+            paused: false,
+        }
+    }
+
+    fn sound(&self) -> &Sound<S> {
+        &self.songs[self.song]
+    }
+
+    // The Source only counts time as it's heard, so a raised pitch gets through more song
+    fn position_now(&self) -> std::time::Duration {
+        self.started_at + self.sound().elapsed().mul_f32(self.pitch)
+    }
+
+    fn play_from_position(&mut self, ctx: &S::Context) {
+        let (pitch, position) = (self.pitch, self.position);
+        let sound = &mut self.songs[self.song];
+        sound.set_pitch(pitch);
+        sound.set_start(position);
+        self.started_at = position;
+        self.stale = false;
+        self.paused = false;
+        sound.play(ctx);
+    }
+
+    // Wraps back round to the first song after the last
+    fn next_song(&mut self) {
+        self.sound().pause();
+        self.song = (self.song + 1) % self.songs.len();
+        self.position = std::time::Duration::ZERO;
+        self.stale = true;
+    }
+}
+
+// Every file in the playlist folder, shuffled. Empty if there's no folder or nothing in it
+fn load_playlist<S>(loader: &impl SoundLoader<Source = S>, rng: &mut Rand32) -> Vec<Sound<S>> {
+    let mut paths: Vec<PathBuf> = loader
+        .read_dir(PLAYLIST_DIR)
+        .into_iter()
+        .filter(|path| {
+            path.extension()
+                .and_then(|ext| ext.to_str())
+                .is_some_and(|ext| PLAYLIST_EXTENSIONS.contains(&ext.to_lowercase().as_str()))
+        })
+        .collect();
+    // Sorted first so the same seed always gives the same order
+    paths.sort();
+    for i in (1..paths.len()).rev() {
+        let j = rng.rand_range(0..(i as u32 + 1)) as usize;
+        paths.swap(i, j);
+    }
+    paths
+        .into_iter()
+        .map(|path| Sound(loader.load(&SoundFile::Disk(path), false)))
+        .collect()
+}
+
+// Owns the tracks and crossfades between them. The screens just say which one they want
+// and this takes care of fading, starting and pausing, so asking for the track that's
+// already on changes nothing.
+// Every track picks up where it was paused when it comes back in. The one exception is a
+// new game, which starts the menu, win and loss music over from the top but leaves the
+// gameplay track where it was, so a quick retry carries the same song on
+pub struct Music<S = Source> {
+    channels: Vec<Channel<S>>,
+    wanted: Option<Track>,
+    // From the settings, before each track's own level and the fade
+    volume: f32,
+    // Turned down under the death sting
+    ducked: bool,
+    // Set when a run ends, until the gameplay track is wanted again
+    run_ended: bool,
+}
+
+impl<S: AudioSource> Music<S> {
+    // Every track silent until it's loaded
+    fn new() -> Self {
+        Music {
+            channels: Track::ALL
+                .into_iter()
+                .map(|track| Channel::new(vec![Sound(None)], track.bpm()))
+                .collect(),
+            wanted: None,
+            volume: 1.0,
+            ducked: false,
+            run_ended: false,
+        }
+    }
+
+    // None fades everything out
+    fn want(&mut self, track: Option<Track>) {
+        if track == Some(Track::Game) {
+            self.run_ended = false;
+        }
+        self.wanted = track;
+    }
+
+    // The gameplay track fades out over RUN_END_FADE_LENGTH instead of the usual crossfade
+    pub fn end_run(&mut self) {
+        self.fade_out(Track::Game);
+        self.run_ended = true;
+    }
+
+    // Whether the track has faded all the way out, or was never on
+    pub fn faded_out(&self, track: Track) -> bool {
+        self.channels[track as usize].level == 0.0
+    }
+
+    // Fades the track out if it's the one on, and leaves anything else alone
+    pub fn fade_out(&mut self, track: Track) {
+        if self.wanted == Some(track) {
+            self.wanted = None;
+        }
+    }
+
+    // Anything the finale sped up goes back to normal too
+    pub fn new_game(&mut self) {
+        self.run_ended = false;
+        for track in Track::ALL {
+            let channel = &mut self.channels[track as usize];
+            if track != Track::Game {
+                channel.position = std::time::Duration::ZERO;
+            } else if channel.sound().playing() {
+                // Still fading out, so its place is wherever it's got to
+                channel.position = channel.position_now();
+            }
+            channel.pitch = 1.0;
+            channel.stale = true;
+        }
+    }
+
+    // Takes effect straight away if the track is on, carrying on from the same spot
+    fn set_pitch(&mut self, ctx: &S::Context, track: Track, pitch: f32) {
+        let channel = &mut self.channels[track as usize];
+        if channel.sound().playing() {
+            channel.position = channel.position_now();
+            channel.pitch = pitch;
+            channel.play_from_position(ctx);
+        } else {
+            channel.pitch = pitch;
+            channel.stale = true;
+        }
+    }
+
+    // How far into its song the track is, or None when it isn't playing
+    pub fn position(&self, track: Track) -> Option<std::time::Duration> {
+        let channel = &self.channels[track as usize];
+        channel.sound().playing().then(|| channel.position_now())
+    }
+
+    pub fn bpm(&self, track: Track) -> f32 {
+        self.channels[track as usize].bpm
+    }
+
+    // Skips to the next song in the playlist, whether or not it's on right now
+    pub fn next_song(&mut self, track: Track) {
+        self.channels[track as usize].next_song();
+    }
+
+    // Called every frame. The wanted track fades up while everything else fades down,
+    // and a track is only paused once it's faded all the way out
+    fn update(&mut self, ctx: &S::Context, delta: std::time::Duration) {
+        for track in Track::ALL {
+            let wanted = self.wanted == Some(track);
+            let fade_length = if track == Track::Game && self.run_ended {
+                RUN_END_FADE_LENGTH
+            } else {
+                CROSSFADE_LENGTH
+            };
+            let step = delta.as_secs_f32() / fade_length.as_secs_f32();
+            let channel = &mut self.channels[track as usize];
+            if wanted {
+                channel.level = f32::min(channel.level + step, 1.0);
+                if channel.stale {
+                    channel.play_from_position(ctx);
+                } else if channel.paused {
+                    channel.sound().resume();
+                    channel.paused = false;
+                } else if !channel.sound().playing() {
+                    // Nothing paused it, so the song ran out
+                    channel.next_song();
+                    channel.play_from_position(ctx);
+                }
+            } else {
+                channel.level = f32::max(channel.level - step, 0.0);
+                if channel.level == 0.0 && channel.sound().playing() {
+                    // Unless new_game has already put it back to the top while it was
+                    // still fading, in which case that's where it starts next time
+                    if !channel.stale {
+                        channel.position = channel.position_now();
+                    }
+                    channel.sound().pause();
+                    channel.paused = true;
+                }
+            }
+            let duck = if self.ducked {
+                DUCKED_MUSIC_VOLUME
+            } else {
+                1.0
+            };
+            let volume = self.volume * track.volume() * channel.level * duck;
+            channel.songs[channel.song].set_volume(volume);
+        }
+    }
+}
+
+impl<S: AudioSource> Music<S> {
+    // The gameplay track is the playlist folder when there's anything in it
+    fn load(&mut self, loader: &impl SoundLoader<Source = S>, rng: &mut Rand32, track: Track) {
+        let playlist = if track == Track::Game {
+            load_playlist(loader, rng)
+        } else {
+            Vec::new()
+        };
+        self.channels[track as usize] = if playlist.is_empty() {
+            Channel::new(vec![Sound::find(loader, track.name(), true)], track.bpm())
+        } else {
+            Channel::new(playlist, PLAYLIST_BPM)
+        };
+    }
+}
+
+// The one-off sounds the end screens open with before their music starts
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Sting {
+    // A fanfare for filling the board
+    Win,
+    // The death sound
+    Loss,
+}
+
+// Where the end screen's sting is up to for the current run
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum StingState {
+    NotPlayed,
+    Playing(Sting),
+    Finished,
+}
+
+// Every sound the game makes, kept as music or sound effects so each side follows its
+// own volume
+pub struct AudioManager<S = Source> {
+    pub music: Music<S>,
+    death_sound: Sound<S>,
+    fanfare: Sound<S>,
+    sting: StingState,
+    // Always a ggez sound, since it can come from one side or the other. Silent in tests
+    eat_sound: EatSound,
+    click_sound: Sound<S>,
+    select_sound: Sound<S>,
+    tick_sound: Sound<S>,
+    // Kept for setting the effects up again after the device comes back
+    sfx_volume: f32,
+    // Only for shuffling the playlist, so loading never uses up the game's numbers
+    rng: Rand32,
+    // Set while there's no sound device. Counts down to the next try at finding one
+    retry_in: Option<std::time::Duration>,
+    // The default output device the sounds were made on, and how long until it's next
+    // checked for having gone or changed
+    device: Option<String>,
+    device_check_in: std::time::Duration,
+    // Off with --no-audio, in which case nothing is ever loaded and every sound stays silent
+    enabled: bool,
+}
+
+impl AudioManager {
+    // How many load_step calls it takes to load everything: the four tracks, then the
+    // six effects
+    pub const LOAD_STEPS: usize = 10;
+
+    // Any that are missing are left silent, so with none at all the game still runs.
+    // The volumes need applying again once everything's in
+    pub fn load_step(&mut self, ctx: &Context, step: usize) {
+        if step == 0 && self.enabled {
+            self.device = default_device();
+        }
+        self.load_from(ctx, step);
+    }
+
+    // Every Source belongs to the device it was made on, so with a new one everything has
+    // to be loaded again. The music starts its songs over, but fades back in as usual. With
+    // no device at all, the game carries on in silence and update keeps looking for one
+    fn reconnect(&mut self, ctx: &mut Context) {
+        match AudioContext::new(&ctx.fs) {
+            Ok(audio) => {
+                ctx.audio = audio;
+                for step in 0..Self::LOAD_STEPS {
+                    self.load_step(ctx, step);
+                }
+                self.set_sfx_volumes();
+                if self.retry_in.take().is_some() {
+                    eprintln!("Found a sound device again");
+                }
+            }
+            Err(e) => {
+                if self.available() {
+                    eprintln!("Lost the sound device, carrying on without sound: {}", e);
+                }
+                self.retry_in = Some(AUDIO_RETRY_INTERVAL);
+            }
+        }
+    }
+
+    // Called every frame. While there's no device it only keeps looking for one
+    pub fn update(&mut self, ctx: &mut Context, delta: std::time::Duration) {
+        if !self.enabled {
+            return;
+        }
+        if let Some(retry_in) = self.retry_in {
+            match retry_in.checked_sub(delta) {
+                Some(left) if !left.is_zero() => self.retry_in = Some(left),
+                _ => self.reconnect(ctx),
+            }
+            return;
+        }
+        match self.device_check_in.checked_sub(delta) {
+            Some(left) if !left.is_zero() => self.device_check_in = left,
+            _ => {
+                self.device_check_in = DEVICE_CHECK_INTERVAL;
+                if default_device() != self.device {
+                    eprintln!("The sound device changed, moving the sounds over to the new one");
+                    self.reconnect(ctx);
+                    return;
+                }
+            }
+        }
+        self.update_sounds(ctx, delta);
+    }
+
+    pub fn play_eat(&mut self, ctx: &Context, combo_level: u32, pos: GridPosition) {
+        if self.available() {
+            self.eat_sound.play(ctx, combo_level, pos);
+        }
+    }
+}
+
+impl<S: AudioSource> AudioManager<S> {
+    // Starts out with everything silent. The files are read in by load_step, a piece at
+    // a time, so there's a loading screen to look at while it happens
+    pub fn new(seed: u64, enabled: bool) -> Self {
+        AudioManager {
+            music: Music::new(),
+            death_sound: Sound(None),
+            fanfare: Sound(None),
+            sting: StingState::NotPlayed,
+            eat_sound: EatSound::Mono(Sound(None)),
+            click_sound: Sound(None),
+            select_sound: Sound(None),
+            tick_sound: Sound(None),
+            sfx_volume: 1.0,
+            rng: Rand32::new(seed),
+            retry_in: None,
+            device: None,
+            device_check_in: DEVICE_CHECK_INTERVAL,
+            enabled,
+        }
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.enabled
+    }
+
+    fn load_from(&mut self, loader: &impl SoundLoader<Source = S>, step: usize) {
+        if !self.enabled {
+            return;
+        }
+        match step {
+            0..=3 => self.music.load(loader, &mut self.rng, Track::ALL[step]),
+            4 => self.death_sound = Sound::find(loader, "/snake", false),
+            5 => self.eat_sound = EatSound::load(loader),
+            6 => self.click_sound = Sound::find(loader, "/click", false),
+            7 => self.select_sound = Sound::find(loader, "/select", false),
+            8 => self.tick_sound = Sound::find(loader, "/tick", false),
+            9 => self.fanfare = Sound::find(loader, "/fanfare", false),
+            _ => (),
+        }
+    }
+
+    // Everything load_step had to leave silent, by the name it was looked for under. A
+    // playlist counts as there as long as one of its songs is
+    pub fn missing(&self) -> Vec<&'static str> {
+        if !self.enabled {
+            return Vec::new();
+        }
+        let eat_loaded = match &self.eat_sound {
+            EatSound::Spatial(_) => true,
+            EatSound::Mono(sound) => sound.loaded(),
+        };
+        let effects = [
+            ("/snake", self.death_sound.loaded()),
+            (EatSound::NAME, eat_loaded),
+            ("/click", self.click_sound.loaded()),
+            ("/select", self.select_sound.loaded()),
+            ("/tick", self.tick_sound.loaded()),
+            ("/fanfare", self.fanfare.loaded()),
+        ];
+        Track::ALL
+            .into_iter()
+            .filter(|track| {
+                !self.music.channels[*track as usize]
+                    .songs
+                    .iter()
+                    .any(Sound::loaded)
+            })
+            .map(Track::name)
+            .chain(
+                effects
+                    .into_iter()
+                    .filter(|(_, loaded)| !loaded)
+                    .map(|(name, _)| name),
+            )
+            .collect()
+    }
+
+    // False while the sound device is gone
+    pub fn available(&self) -> bool {
+        self.retry_in.is_none()
+    }
+
+    // The screens just say which track goes with them and the music takes care of the
+    // rest. Asking for the one that's already on changes nothing
+    pub fn request_music(&mut self, track: Option<Track>) {
+        self.music.want(track);
+    }
+
+    pub fn set_music_pitch(&mut self, ctx: &S::Context, track: Track, pitch: f32) {
+        self.music.set_pitch(ctx, track, pitch);
+    }
+
+    fn sting_sound(&self, sting: Sting) -> &Sound<S> {
+        match sting {
+            Sting::Win => &self.fanfare,
+            Sting::Loss => &self.death_sound,
+        }
+    }
+
+    // The music comes back up as soon as the sting is over, whatever screen the player has
+    // gone on to by then
+    fn update_sounds(&mut self, ctx: &S::Context, delta: std::time::Duration) {
+        if let StingState::Playing(sting) = self.sting {
+            if !self.sting_sound(sting).playing() {
+                self.sting = StingState::Finished;
+                self.music.ducked = false;
+            }
+        }
+        self.music.update(ctx, delta);
+    }
+
+    // What the win and loss screens ask for every frame. Once the gameplay track has
+    // faded out the sting plays over whatever music is left, ducked, and then the track
+    // loops from when it's done. The sting only goes off once a run, and one that's
+    // missing just finishes straight away
+    pub fn sting_then_loop(&mut self, ctx: &S::Context, sting: Sting, track: Track) {
+        if !self.music.faded_out(Track::Game) {
+            return;
+        }
+        self.start_sting(ctx, sting);
+        if self.sting == StingState::Finished {
+            self.music.want(Some(track));
+        }
+    }
+
+    // Plays the sting right away, over the gameplay track as it fades, for when something
+    // comes before the end screen. Does nothing if this run's sting has already gone off
+    pub fn start_sting(&mut self, ctx: &S::Context, sting: Sting) {
+        if self.sting != StingState::NotPlayed {
+            return;
+        }
+        self.sting = StingState::Playing(sting);
+        self.music.ducked = true;
+        match sting {
+            Sting::Win => self.fanfare.play(ctx),
+            Sting::Loss => self.death_sound.play(ctx),
+        }
+    }
+
+    // Cuts the sting off and readies it for the end of the next run
+    pub fn stop_sting(&mut self) {
+        if let StingState::Playing(sting) = self.sting {
+            self.sting_sound(sting).pause();
+        }
+        self.sting = StingState::NotPlayed;
+        self.music.ducked = false;
+    }
+
+    // Pushes the volume settings out. A Source keeps its volume when it's started again,
+    // so the effects only need it when a setting changes, and the music picks it up on its
+    // next frame. Muting everything wins over whatever the music on its own is set to
+    pub fn apply_volumes(&mut self, settings: &Settings) {
+        let (music, sfx) = if settings.muted {
+            (0.0, 0.0)
+        } else if settings.music_muted {
+            (0.0, settings.sfx_volume as f32 / 100.0)
+        } else {
+            (
+                settings.music_volume as f32 / 100.0,
+                settings.sfx_volume as f32 / 100.0,
+            )
+        };
+
+        self.music.volume = music;
+        self.sfx_volume = sfx;
+        self.set_sfx_volumes();
+    }
+
+    fn set_sfx_volumes(&mut self) {
+        let sfx = self.sfx_volume;
+        self.death_sound.set_volume(sfx);
+        self.fanfare.set_volume(sfx);
+        self.eat_sound.set_volume(sfx);
+        self.click_sound.set_volume(sfx);
+        self.select_sound.set_volume(sfx);
+        self.tick_sound.set_volume(sfx * TICK_SOUND_VOLUME);
+    }
+
+    // Playing an effect that's already going starts it over, so quick repeats each get heard
+    pub fn play_sfx(&mut self, ctx: &S::Context, sfx: Sfx) {
+        if !self.available() {
+            return;
+        }
+        match sfx {
+            Sfx::Death => self.death_sound.play(ctx),
+            Sfx::Click => self.click_sound.play(ctx),
+            Sfx::Select => self.select_sound.play(ctx),
+            Sfx::Tick => self.tick_sound.play(ctx),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+    use std::time::Duration;
+
+    const FRAME: Duration = Duration::from_millis(50);
+
+    // Stands in for a Source. Nothing's heard and nothing ever runs out on its own, so a
+    // test ends a sound by setting playing itself
+    #[derive(Default)]
+    struct FakeSource {
+        playing: Cell<bool>,
+        // How many times it's been started, as opposed to resumed, and where from last time
+        plays: u32,
+        start: Duration,
+        // How much has been heard since it was started, moved on by the test
+        elapsed: Duration,
+        volume: f32,
+        // Won't play, like a file that turns out not to decode
+        broken: bool,
+    }
+
+    impl AudioSource for FakeSource {
+        type Context = ();
+
+        fn play(&mut self, _: &()) -> GameResult {
+            if self.broken {
+                return Err(ggez::GameError::AudioError("can't decode".to_string()));
+            }
+            self.playing.set(true);
+            self.plays += 1;
+            self.elapsed = Duration::ZERO;
+            Ok(())
+        }
+
+        fn pause(&self) {
+            self.playing.set(false);
+        }
+
+        fn resume(&self) {
+            self.playing.set(true);
+        }
+
+        fn playing(&self) -> bool {
+            self.playing.get()
+        }
+
+        fn set_volume(&mut self, volume: f32) {
+            self.volume = volume;
+        }
+
+        fn set_pitch(&mut self, _: f32) {}
+
+        fn set_start(&mut self, start: Duration) {
+            self.start = start;
+        }
+
+        fn elapsed(&self) -> Duration {
+            self.elapsed
+        }
+    }
+
+    fn fake() -> Sound<FakeSource> {
+        Sound(Some(FakeSource::default()))
+    }
+
+    // Every track and effect there, and nothing playing yet
+    fn audio() -> AudioManager<FakeSource> {
+        let mut audio = AudioManager::new(0, true);
+        for channel in &mut audio.music.channels {
+            channel.songs = vec![fake()];
+        }
+        audio.death_sound = fake();
+        audio.fanfare = fake();
+        audio
+    }
+
+    fn track(audio: &AudioManager<FakeSource>, track: Track) -> &FakeSource {
+        audio.music.channels[track as usize].songs[0]
+            .0
+            .as_ref()
+            .unwrap()
+    }
+
+    fn track_mut(audio: &mut AudioManager<FakeSource>, track: Track) -> &mut FakeSource {
+        audio.music.channels[track as usize].songs[0]
+            .0
+            .as_mut()
+            .unwrap()
+    }
+
+    fn level(audio: &AudioManager<FakeSource>, track: Track) -> f32 {
+        audio.music.channels[track as usize].level
+    }
+
+    // Frames for that long, with whatever the screen on top asks for each frame
+    fn run(
+        audio: &mut AudioManager<FakeSource>,
+        length: Duration,
+        mut each_frame: impl FnMut(&mut AudioManager<FakeSource>),
+    ) {
+        let mut elapsed = Duration::ZERO;
+        while elapsed < length {
+            each_frame(audio);
+            audio.update_sounds(&(), FRAME);
+            elapsed += FRAME;
+        }
+    }
+
+    #[test]
+    fn asking_for_the_track_already_on_changes_nothing() {
+        let mut audio = audio();
+        audio.request_music(Some(Track::Title));
+        run(&mut audio, FRAME, |_| ());
+        assert!(track(&audio, Track::Title).playing());
+        assert!(level(&audio, Track::Title) < 1.0);
+
+        run(&mut audio, CROSSFADE_LENGTH, |audio| {
+            audio.request_music(Some(Track::Title))
+        });
+        assert_eq!(level(&audio, Track::Title), 1.0);
+        assert_eq!(track(&audio, Track::Title).volume, 1.0);
+        assert_eq!(track(&audio, Track::Title).plays, 1);
+    }
+
+    #[test]
+    fn crossfades_and_picks_up_where_it_left_off() {
+        let mut audio = audio();
+        audio.request_music(Some(Track::Title));
+        run(&mut audio, CROSSFADE_LENGTH * 2, |_| ());
+
+        audio.request_music(Some(Track::Game));
+        run(&mut audio, CROSSFADE_LENGTH / 2, |_| ());
+        // Halfway, both are going
+        assert!((level(&audio, Track::Title) - 0.5).abs() < 0.01);
+        assert!((level(&audio, Track::Game) - 0.5).abs() < 0.01);
+        assert!(track(&audio, Track::Title).playing());
+        assert!(track(&audio, Track::Game).playing());
+
+        run(&mut audio, CROSSFADE_LENGTH, |_| ());
+        assert!(!track(&audio, Track::Title).playing());
+        assert_eq!(level(&audio, Track::Game), 1.0);
+        assert_eq!(track(&audio, Track::Game).volume, GAME_MUSIC_VOLUME);
+
+        // Back in from where it was paused, not over from the top
+        audio.request_music(Some(Track::Title));
+        run(&mut audio, FRAME, |_| ());
+        assert!(track(&audio, Track::Title).playing());
+        assert_eq!(track(&audio, Track::Title).plays, 1);
+    }
+
+    #[test]
+    fn the_sting_waits_for_the_game_track_and_the_music_waits_for_the_sting() {
+        let mut audio = audio();
+        audio.request_music(Some(Track::Game));
+        run(&mut audio, CROSSFADE_LENGTH * 2, |_| ());
+
+        audio.music.end_run();
+        let loss_screen = |audio: &mut AudioManager<FakeSource>| {
+            audio.sting_then_loop(&(), Sting::Loss, Track::Loss)
+        };
+        run(&mut audio, RUN_END_FADE_LENGTH / 2, loss_screen);
+        assert_eq!(audio.death_sound.0.as_ref().unwrap().plays, 0);
+
+        run(&mut audio, RUN_END_FADE_LENGTH, loss_screen);
+        assert!(audio.death_sound.playing());
+        assert!(audio.music.ducked);
+        assert!(!track(&audio, Track::Loss).playing());
+
+        // Over the sting, the loss music comes in
+        audio.death_sound.pause();
+        run(&mut audio, FRAME * 2, loss_screen);
+        assert!(!audio.music.ducked);
+        assert!(track(&audio, Track::Loss).playing());
+
+        // Only the once a run
+        run(&mut audio, CROSSFADE_LENGTH, loss_screen);
+        assert_eq!(audio.death_sound.0.as_ref().unwrap().plays, 1);
+        audio.stop_sting();
+        assert_eq!(audio.sting, StingState::NotPlayed);
+    }
+
+    #[test]
+    fn a_missing_sting_finishes_straight_away() {
+        let mut audio = audio();
+        audio.death_sound = Sound(None);
+        audio.music.end_run();
+        run(&mut audio, FRAME * 2, |audio| {
+            audio.sting_then_loop(&(), Sting::Loss, Track::Loss)
+        });
+        assert!(track(&audio, Track::Loss).playing());
+    }
+
+    #[test]
+    fn a_retry_carries_the_game_track_on_but_starts_the_menus_over() {
+        let mut audio = audio();
+        audio.request_music(Some(Track::Title));
+        run(&mut audio, FRAME, |_| ());
+        track_mut(&mut audio, Track::Title).elapsed = Duration::from_secs(10);
+
+        // Into a game, and out to the loss screen
+        audio.request_music(Some(Track::Game));
+        run(&mut audio, CROSSFADE_LENGTH * 2, |_| ());
+        track_mut(&mut audio, Track::Game).elapsed = Duration::from_secs(30);
+        audio.music.end_run();
+        audio.request_music(Some(Track::Loss));
+        run(&mut audio, CROSSFADE_LENGTH * 2, |_| ());
+        assert!(!track(&audio, Track::Game).playing());
+        track_mut(&mut audio, Track::Loss).elapsed = Duration::from_secs(5);
+
+        // Try again
+        audio.music.new_game();
+        audio.request_music(Some(Track::Game));
+        run(&mut audio, CROSSFADE_LENGTH * 2, |_| ());
+        assert_eq!(track(&audio, Track::Game).start, Duration::from_secs(30));
+
+        // Dying again hears the loss music from the top, and so does the title after it
+        audio.music.end_run();
+        audio.request_music(Some(Track::Loss));
+        run(&mut audio, FRAME, |_| ());
+        assert_eq!(track(&audio, Track::Loss).start, Duration::ZERO);
+        assert_eq!(track(&audio, Track::Loss).plays, 2);
+        audio.request_music(Some(Track::Title));
+        run(&mut audio, FRAME, |_| ());
+        assert_eq!(track(&audio, Track::Title).start, Duration::ZERO);
+        assert_eq!(track(&audio, Track::Title).plays, 2);
+    }
+
+    #[test]
+    fn pausing_and_unpausing_picks_the_game_track_back_up() {
+        let mut audio = audio();
+        audio.request_music(Some(Track::Title));
+        run(&mut audio, CROSSFADE_LENGTH * 2, |_| ());
+        audio.request_music(Some(Track::Game));
+        run(&mut audio, CROSSFADE_LENGTH * 2, |_| ());
+        track_mut(&mut audio, Track::Game).elapsed = Duration::from_secs(20);
+
+        // The pause menu wants nothing, and the game wants its track back
+        run(&mut audio, CROSSFADE_LENGTH * 2, |audio| {
+            audio.request_music(None)
+        });
+        assert!(!track(&audio, Track::Game).playing());
+        run(&mut audio, CROSSFADE_LENGTH * 2, |audio| {
+            audio.request_music(Some(Track::Game))
+        });
+        assert!(track(&audio, Track::Game).playing());
+        assert_eq!(track(&audio, Track::Game).plays, 1);
+        assert_eq!(level(&audio, Track::Game), 1.0);
+        // Nothing in between brought the title music back
+        assert!(!track(&audio, Track::Title).playing());
+        assert_eq!(level(&audio, Track::Title), 0.0);
+    }
+
+    #[test]
+    fn a_sound_that_wont_play_only_silences_itself() {
+        let mut audio = audio();
+        audio.fanfare.0.as_mut().unwrap().broken = true;
+        audio.click_sound = fake();
+        audio.start_sting(&(), Sting::Win);
+        assert!(!audio.fanfare.loaded());
+        // The device is still there, so everything else carries on
+        assert!(audio.available());
+        audio.play_sfx(&(), Sfx::Click);
+        assert!(audio.click_sound.playing());
+
+        track_mut(&mut audio, Track::Title).broken = true;
+        audio.request_music(Some(Track::Title));
+        run(&mut audio, FRAME * 2, |_| ());
+        assert!(!audio.music.channels[Track::Title as usize].songs[0].loaded());
+        assert!(audio.available());
+        audio.request_music(Some(Track::Game));
+        run(&mut audio, FRAME, |_| ());
+        assert!(track(&audio, Track::Game).playing());
+    }
+
+    #[test]
+    fn the_eat_sound_pans_across_the_board() {
+        assert_eq!(pan(GridPosition::new(0, 5)), -1.0);
+        assert_eq!(pan(GridPosition::new(GRID_SIZE.0 - 1, 5)), 1.0);
+        // The middle of an even width falls between two cells, which pan out evenly
+        let middle = (GRID_SIZE.0 - 1) as f32 / 2.0;
+        let (left, right) = (
+            pan(GridPosition::new(middle.floor() as i16, 5)),
+            pan(GridPosition::new(middle.ceil() as i16, 5)),
+        );
+        assert!((left + right).abs() < 1e-6);
+        assert!(left <= 0.0 && right >= 0.0);
+    }
+
+    #[test]
+    fn sound_files_go_ogg_then_wav_then_mp3_then_built_in() {
+        const BUILT_IN: &[u8] = b"built in";
+        let embedded = [("/eat", BUILT_IN)];
+        let all = sound_files("/eat", |_| true, &embedded);
+        assert_eq!(
+            all,
+            [
+                SoundFile::Disk(PathBuf::from("/eat.ogg")),
+                SoundFile::Disk(PathBuf::from("/eat.wav")),
+                SoundFile::Disk(PathBuf::from("/eat.mp3")),
+                SoundFile::Embedded(BUILT_IN),
+            ]
+        );
+
+        let only_mp3 = sound_files("/eat", |path| path.ends_with("eat.mp3"), &embedded);
+        assert_eq!(only_mp3[0], SoundFile::Disk(PathBuf::from("/eat.mp3")));
+
+        let none_on_disk = sound_files("/eat", |_| false, &embedded);
+        assert_eq!(none_on_disk, [SoundFile::Embedded(BUILT_IN)]);
+    }
+
+    #[test]
+    fn a_sound_with_no_file_anywhere_is_silent() {
+        const BUILT_IN: &[u8] = b"built in";
+        assert!(sound_files("/click", |_| false, &[("/eat", BUILT_IN)]).is_empty());
+    }
+
+    // A resources folder holding just the files given
+    struct Folder(&'static [&'static str]);
+
+    impl SoundLoader for Folder {
+        type Source = FakeSource;
+
+        fn exists(&self, path: &Path) -> bool {
+            self.0.iter().any(|file| Path::new(file) == path)
+        }
+
+        fn read_dir(&self, dir: &str) -> Vec<PathBuf> {
+            self.0
+                .iter()
+                .filter(|file| file.starts_with(dir))
+                .map(PathBuf::from)
+                .collect()
+        }
+
+        fn load(&self, _: &SoundFile, _: bool) -> Option<FakeSource> {
+            Some(FakeSource::default())
+        }
+
+        fn load_eat(&self, _: &SoundFile) -> Option<EatSound> {
+            None
+        }
+    }
+
+    fn load_all(audio: &mut AudioManager<FakeSource>, folder: &Folder) {
+        for step in 0..AudioManager::LOAD_STEPS {
+            audio.load_from(folder, step);
+        }
+    }
+
+    #[test]
+    fn an_empty_resources_folder_loads_all_silent() {
+        let mut audio = AudioManager::new(0, true);
+        load_all(&mut audio, &Folder(&[]));
+        for track in Track::ALL {
+            assert!(!audio.music.channels[track as usize].sound().loaded());
+        }
+        assert!(!audio.death_sound.loaded() && !audio.fanfare.loaded());
+        assert!(!audio.click_sound.loaded() && !audio.select_sound.loaded());
+        assert!(!audio.tick_sound.loaded());
+
+        // One sound for every step, and none of them there
+        let missing = audio.missing();
+        assert_eq!(missing.len(), AudioManager::LOAD_STEPS);
+        for track in Track::ALL {
+            assert!(missing.contains(&track.name()));
+        }
+        assert!(missing.contains(&EatSound::NAME));
+
+        // And asking any of it to play just does nothing
+        audio.play_sfx(&(), Sfx::Click);
+        audio.request_music(Some(Track::Title));
+        run(&mut audio, FRAME, |_| ());
+    }
+
+    #[test]
+    fn only_what_couldnt_be_found_is_missing() {
+        let mut audio = AudioManager::new(0, true);
+        load_all(
+            &mut audio,
+            &Folder(&["/snake_jazz.ogg", "/click.wav", "/music/gameplay/song.mp3"]),
+        );
+        let missing = audio.missing();
+        assert!(!missing.contains(&Track::Title.name()));
+        // The playlist stands in for the gameplay track
+        assert!(!missing.contains(&Track::Game.name()));
+        assert!(!missing.contains(&"/click"));
+        assert_eq!(missing.len(), AudioManager::LOAD_STEPS - 3);
+
+        // Turned off on purpose, nothing's loaded or missing
+        let mut off = AudioManager::new(0, false);
+        load_all(&mut off, &Folder(&["/click.wav"]));
+        assert!(!off.click_sound.loaded());
+        assert!(off.missing().is_empty());
+    }
+}
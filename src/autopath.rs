@@ -0,0 +1,53 @@
+use crate::{Direction, GridPosition, GRID_SIZE};
+use std::collections::VecDeque;
+
+const DIRECTIONS: [Direction; 4] = [
+    Direction::Up,
+    Direction::Down,
+    Direction::Left,
+    Direction::Right,
+];
+
+// The shortest way from the head to target that doesn't go through anything blocked, as the
+// direction to take on each tick. With walls down the path is allowed to wrap around the edges
+pub fn find_path(
+    head: GridPosition,
+    target: GridPosition,
+    walls: bool,
+    blocked: impl Fn(GridPosition) -> bool,
+) -> Option<VecDeque<Direction>> {
+    let index = |pos: GridPosition| pos.x as usize * GRID_SIZE.1 as usize + pos.y as usize;
+    // How each cell was first reached, so the path can be walked back from the target
+    let mut came_from: Vec<Option<(GridPosition, Direction)>> =
+        vec![None; GRID_SIZE.0 as usize * GRID_SIZE.1 as usize];
+    let mut visited = vec![false; came_from.len()];
+    let mut frontier = VecDeque::from([head]);
+    visited[index(head)] = true;
+
+    while let Some(pos) = frontier.pop_front() {
+        if pos == target {
+            let mut path = VecDeque::new();
+            let mut at = pos;
+            while let Some((from, dir)) = came_from[index(at)] {
+                path.push_front(dir);
+                at = from;
+            }
+            return Some(path);
+        }
+
+        for dir in DIRECTIONS {
+            if walls && GridPosition::move_wraps(pos, dir) {
+                continue;
+            }
+            let next = GridPosition::new_from_move(pos, dir);
+            if visited[index(next)] || blocked(next) {
+                continue;
+            }
+            visited[index(next)] = true;
+            came_from[index(next)] = Some((pos, dir));
+            frontier.push_back(next);
+        }
+    }
+
+    None
+}
@@ -0,0 +1,109 @@
+use crate::scoring::ScoringPreset;
+use crate::settings::{MenuColors, Settings, Speed};
+use crate::strings::Strings;
+use ggez::graphics::{self, Color, Rect, Text};
+use ggez::mint::Point2;
+use ggez::{Context, GameResult};
+
+const TEXT_SCALE: f32 = 14.0;
+// Space inside each badge around its label, and between badges
+const PADDING: f32 = 4.0;
+const GAP: f32 = 6.0;
+// Past this the rest get folded into a "+N" badge instead of running off the edge
+const MAX_ROW_WIDTH: f32 = 320.0;
+
+// The string keys of every rule the run is being played under that isn't the default,
+// always in this order so the same run always gets the same row
+pub fn active(settings: &Settings, ghost: bool) -> Vec<&'static str> {
+    let mut badges = Vec::new();
+    if settings.walls {
+        badges.push("badge.walls");
+    }
+    match settings.speed {
+        Speed::Slow => badges.push("badge.slow"),
+        Speed::Fast => badges.push("badge.fast"),
+        Speed::Rhythm => badges.push("badge.rhythm"),
+        Speed::Normal => (),
+    }
+    if settings.scoring == ScoringPreset::Arcade {
+        badges.push("badge.arcade");
+    }
+    if ghost {
+        badges.push("badge.ghost");
+    }
+    badges
+}
+
+fn label(text: String) -> Text {
+    let mut text = Text::new(text);
+    text.set_scale(TEXT_SCALE);
+    text
+}
+
+// Works out which labels fit in the row, swapping the tail for "+N" if they don't all fit
+fn fit(ctx: &Context, strings: &Strings, keys: &[&str]) -> GameResult<Vec<(Text, f32)>> {
+    let mut labels = Vec::new();
+    let mut width = 0.0;
+    for (i, key) in keys.iter().enumerate() {
+        let text = label(strings.get(key));
+        let badge_width = text.measure(ctx)?.x + 2.0 * PADDING;
+        // Whatever's left has to fit in a "+N" too, unless this is the last one
+        let rest = keys.len() - i - 1;
+        let reserve = if rest > 0 {
+            label(format!("+{rest}")).measure(ctx)?.x + 2.0 * PADDING + GAP
+        } else {
+            0.0
+        };
+        if width + badge_width + reserve > MAX_ROW_WIDTH {
+            let more = label(format!("+{}", keys.len() - i));
+            let more_width = more.measure(ctx)?.x + 2.0 * PADDING;
+            labels.push((more, more_width));
+            break;
+        }
+        width += badge_width + GAP;
+        labels.push((text, badge_width));
+    }
+    Ok(labels)
+}
+
+pub fn row_width(ctx: &Context, strings: &Strings, keys: &[&str]) -> GameResult<f32> {
+    let labels = fit(ctx, strings, keys)?;
+    let gaps = labels.len().saturating_sub(1) as f32 * GAP;
+    Ok(labels.iter().map(|(_, width)| width).sum::<f32>() + gaps)
+}
+
+// Draws the row with its top left corner at x, y
+pub fn draw(
+    canvas: &mut graphics::Canvas,
+    ctx: &Context,
+    strings: &Strings,
+    colors: &MenuColors,
+    keys: &[&str],
+    x: f32,
+    y: f32,
+) -> GameResult {
+    let mut x = x;
+    for (text, width) in fit(ctx, strings, keys)? {
+        let height = text.measure(ctx)?.y + 2.0 * PADDING;
+        canvas.draw(
+            &graphics::Quad,
+            graphics::DrawParam::new()
+                .dest_rect(Rect::new(x, y, width, height))
+                .color(Color {
+                    a: 0.6,
+                    ..colors.shade
+                }),
+        );
+        canvas.draw(
+            &text,
+            graphics::DrawParam::new()
+                .dest(Point2 {
+                    x: x + PADDING,
+                    y: y + PADDING,
+                })
+                .color(colors.highlight),
+        );
+        x += width + GAP;
+    }
+    Ok(())
+}
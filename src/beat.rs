@@ -0,0 +1,94 @@
+// Moves per beat, so at the gameplay track's 120 BPM the snake moves 8 times a second,
+// a little under normal speed
+pub const STEPS_PER_BEAT: u32 = 4;
+// Beats in a bar, which is how many lights the metronome has
+pub const BEATS_PER_BAR: u32 = 4;
+// A frame that lands more steps than this past the last one doesn't try to catch up.
+// The music jumping around (starting, coming back from pause, the next song) shouldn't
+// fire a burst of moves, so the grid just picks up again from wherever it is now
+const MAX_CATCH_UP: i64 = 2;
+
+// The tick source for rhythm mode. Rather than so many ticks a second, the snake moves on
+// a grid of steps laid over the music, worked out from the track's BPM and how far into
+// it the music has got. When there's no music playing it keeps time by itself
+#[derive(Debug, Clone)]
+pub struct BeatClock {
+    bpm: f32,
+    // Seconds into the song, with the calibration offset added on
+    time: f32,
+    last_step: Option<i64>,
+}
+
+impl BeatClock {
+    pub fn new() -> Self {
+        BeatClock {
+            bpm: 120.0,
+            time: 0.0,
+            last_step: None,
+        }
+    }
+
+    // How long each move takes
+    pub fn step_length(&self) -> std::time::Duration {
+        std::time::Duration::from_secs_f32(60.0 / self.bpm / STEPS_PER_BEAT as f32)
+    }
+
+    // Called every frame of the game. A positive offset moves the snake that many
+    // milliseconds ahead of the music, for setups where the sound comes out late
+    pub fn advance(
+        &mut self,
+        delta: std::time::Duration,
+        music: Option<std::time::Duration>,
+        bpm: f32,
+        offset_ms: i16,
+    ) {
+        self.bpm = bpm;
+        self.time = match music {
+            Some(position) => position.as_secs_f32() + offset_ms as f32 / 1000.0,
+            None => self.time + delta.as_secs_f32(),
+        };
+    }
+
+    fn current_step(&self) -> i64 {
+        (self.time * self.bpm / 60.0 * STEPS_PER_BEAT as f32).floor() as i64
+    }
+
+    // Whether the snake should move again. Called until it says no, like
+    // check_update_time, so a slow frame still gets every move it's owed
+    pub fn next_step(&mut self) -> bool {
+        let current = self.current_step();
+        match self.last_step {
+            Some(last) if current > last && current - last <= MAX_CATCH_UP => {
+                self.last_step = Some(last + 1);
+                true
+            }
+            _ => {
+                self.last_step = Some(current);
+                false
+            }
+        }
+    }
+
+    // How far the music is from one step to the next, for drawing the snake in between
+    pub fn step_progress(&self) -> f32 {
+        (self.time * self.bpm / 60.0 * STEPS_PER_BEAT as f32).rem_euclid(1.0)
+    }
+
+    // Whether the move just made landed right on a beat rather than between two
+    pub fn on_beat(&self) -> bool {
+        self.last_step
+            .is_some_and(|step| step.rem_euclid(STEPS_PER_BEAT as i64) == 0)
+    }
+
+    // How far through the current beat the music is, from 0 right on it up to nearly 1
+    pub fn beat_phase(&self) -> f32 {
+        (self.time * self.bpm / 60.0).rem_euclid(1.0)
+    }
+
+    // Which beat of the bar the music is on, counting from 0
+    pub fn beat_in_bar(&self) -> u32 {
+        (self.time * self.bpm / 60.0)
+            .floor()
+            .rem_euclid(BEATS_PER_BAR as f32) as u32
+    }
+}
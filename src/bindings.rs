@@ -0,0 +1,315 @@
+use crate::Direction;
+use ggez::input::keyboard::KeyCode;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    Up,
+    Down,
+    Left,
+    Right,
+    Pause,
+    Confirm,
+}
+
+impl Action {
+    pub const ALL: [Action; 6] = [
+        Action::Up,
+        Action::Down,
+        Action::Left,
+        Action::Right,
+        Action::Pause,
+        Action::Confirm,
+    ];
+
+    pub fn key(self) -> &'static str {
+        match self {
+            Action::Up => "up",
+            Action::Down => "down",
+            Action::Left => "left",
+            Action::Right => "right",
+            Action::Pause => "pause",
+            Action::Confirm => "confirm",
+        }
+    }
+
+    pub fn direction(self) -> Option<Direction> {
+        match self {
+            Action::Up => Some(Direction::Up),
+            Action::Down => Some(Direction::Down),
+            Action::Left => Some(Direction::Left),
+            Action::Right => Some(Direction::Right),
+            Action::Pause | Action::Confirm => None,
+        }
+    }
+}
+
+// Escape always backs out (and cancels picking a key), and F2 is the high contrast hotkey
+// that has to work from anywhere, so neither can be given to anything else
+const RESERVED: [KeyCode; 2] = [KeyCode::Escape, KeyCode::F2];
+
+// Every key that can be bound, along with the name it's shown as. These are also what
+// the settings file stores, so a name can never change once it's here
+const KEY_NAMES: [(KeyCode, &str); 64] = [
+    (KeyCode::Up, "Up"),
+    (KeyCode::Down, "Down"),
+    (KeyCode::Left, "Left"),
+    (KeyCode::Right, "Right"),
+    (KeyCode::A, "A"),
+    (KeyCode::B, "B"),
+    (KeyCode::C, "C"),
+    (KeyCode::D, "D"),
+    (KeyCode::E, "E"),
+    (KeyCode::F, "F"),
+    (KeyCode::G, "G"),
+    (KeyCode::H, "H"),
+    (KeyCode::I, "I"),
+    (KeyCode::J, "J"),
+    (KeyCode::K, "K"),
+    (KeyCode::L, "L"),
+    (KeyCode::M, "M"),
+    (KeyCode::N, "N"),
+    (KeyCode::O, "O"),
+    (KeyCode::P, "P"),
+    (KeyCode::Q, "Q"),
+    (KeyCode::R, "R"),
+    (KeyCode::S, "S"),
+    (KeyCode::T, "T"),
+    (KeyCode::U, "U"),
+    (KeyCode::V, "V"),
+    (KeyCode::W, "W"),
+    (KeyCode::X, "X"),
+    (KeyCode::Y, "Y"),
+    (KeyCode::Z, "Z"),
+    (KeyCode::Key0, "0"),
+    (KeyCode::Key1, "1"),
+    (KeyCode::Key2, "2"),
+    (KeyCode::Key3, "3"),
+    (KeyCode::Key4, "4"),
+    (KeyCode::Key5, "5"),
+    (KeyCode::Key6, "6"),
+    (KeyCode::Key7, "7"),
+    (KeyCode::Key8, "8"),
+    (KeyCode::Key9, "9"),
+    (KeyCode::Numpad0, "Numpad0"),
+    (KeyCode::Numpad1, "Numpad1"),
+    (KeyCode::Numpad2, "Numpad2"),
+    (KeyCode::Numpad3, "Numpad3"),
+    (KeyCode::Numpad4, "Numpad4"),
+    (KeyCode::Numpad5, "Numpad5"),
+    (KeyCode::Numpad6, "Numpad6"),
+    (KeyCode::Numpad7, "Numpad7"),
+    (KeyCode::Numpad8, "Numpad8"),
+    (KeyCode::Numpad9, "Numpad9"),
+    (KeyCode::Space, "Space"),
+    (KeyCode::Return, "Enter"),
+    (KeyCode::Tab, "Tab"),
+    (KeyCode::Back, "Backspace"),
+    (KeyCode::LShift, "LShift"),
+    (KeyCode::RShift, "RShift"),
+    (KeyCode::LControl, "LCtrl"),
+    (KeyCode::RControl, "RCtrl"),
+    (KeyCode::Comma, "Comma"),
+    (KeyCode::Period, "Period"),
+    (KeyCode::Slash, "Slash"),
+    (KeyCode::Semicolon, "Semicolon"),
+    (KeyCode::Apostrophe, "Apostrophe"),
+    (KeyCode::Minus, "Minus"),
+];
+
+pub fn key_name(key: KeyCode) -> Option<&'static str> {
+    KEY_NAMES
+        .iter()
+        .find(|(code, _)| *code == key)
+        .map(|(_, name)| *name)
+}
+
+pub fn key_from_name(name: &str) -> Option<KeyCode> {
+    KEY_NAMES
+        .iter()
+        .find(|(_, key_name)| *key_name == name)
+        .map(|(code, _)| *code)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BindError {
+    Reserved,
+    // Not in KEY_NAMES, so there'd be no way to show it
+    Unsupported,
+    // Already bound to this other action
+    Taken(Action),
+}
+
+// One key per action, in the same order as Action::ALL
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Bindings {
+    keys: [KeyCode; 6],
+}
+
+impl Default for Bindings {
+    fn default() -> Self {
+        Bindings {
+            keys: [
+                KeyCode::Up,
+                KeyCode::Down,
+                KeyCode::Left,
+                KeyCode::Right,
+                KeyCode::P,
+                KeyCode::Return,
+            ],
+        }
+    }
+}
+
+impl Bindings {
+    pub fn get(&self, action: Action) -> KeyCode {
+        self.keys[action as usize]
+    }
+
+    pub fn action(&self, key: KeyCode) -> Option<Action> {
+        Action::ALL
+            .into_iter()
+            .find(|&action| self.get(action) == key)
+    }
+
+    // A bound key always does what it's bound to. Anything unbound falls back on the
+    // built in arrows and WASD, and HJKL if they're turned on, so those keep working
+    // unless they've been given away
+    pub fn direction(&self, key: KeyCode, vim_keys: bool) -> Option<Direction> {
+        match self.action(key) {
+            Some(action) => action.direction(),
+            None => Direction::from_keycode(key)
+                .or_else(|| Direction::from_vim_key(key).filter(|_| vim_keys)),
+        }
+    }
+
+    // Takes each action's key from the saved names. One that's missing, unknown, reserved
+    // or already taken by an earlier action gets its default back, or if even that's taken,
+    // the first key nothing else is using
+    pub fn from_names<'a>(name: impl Fn(Action) -> Option<&'a str>) -> Self {
+        let mut loaded: [Option<KeyCode>; 6] = [None; 6];
+        for action in Action::ALL {
+            loaded[action as usize] = name(action)
+                .and_then(key_from_name)
+                .filter(|key| !RESERVED.contains(key) && !loaded.contains(&Some(*key)));
+        }
+
+        let defaults = Bindings::default();
+        for action in Action::ALL {
+            if loaded[action as usize].is_some() {
+                continue;
+            }
+            let default = defaults.get(action);
+            loaded[action as usize] = if loaded.contains(&Some(default)) {
+                KEY_NAMES
+                    .iter()
+                    .map(|(key, _)| *key)
+                    .find(|key| !loaded.contains(&Some(*key)))
+            } else {
+                Some(default)
+            };
+        }
+
+        // There are far more keys than actions, so every slot got filled
+        Bindings {
+            keys: loaded.map(|key| key.unwrap_or(KeyCode::Escape)),
+        }
+    }
+
+    pub fn names(&self) -> impl Iterator<Item = (Action, &'static str)> + '_ {
+        Action::ALL
+            .into_iter()
+            .filter_map(|action| key_name(self.get(action)).map(|name| (action, name)))
+    }
+
+    pub fn bind(&mut self, action: Action, key: KeyCode) -> Result<(), BindError> {
+        if RESERVED.contains(&key) {
+            return Err(BindError::Reserved);
+        }
+        if key_name(key).is_none() {
+            return Err(BindError::Unsupported);
+        }
+        match self.action(key) {
+            Some(other) if other != action => Err(BindError::Taken(other)),
+            _ => {
+                self.keys[action as usize] = key;
+                Ok(())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_key_name_round_trips() {
+        for (key, name) in KEY_NAMES {
+            assert_eq!(key_name(key), Some(name));
+            assert_eq!(key_from_name(name), Some(key));
+        }
+    }
+
+    #[test]
+    fn no_key_or_name_is_listed_twice() {
+        for (i, (key, name)) in KEY_NAMES.iter().enumerate() {
+            for (other_key, other_name) in &KEY_NAMES[i + 1..] {
+                assert_ne!(key, other_key);
+                assert_ne!(name, other_name);
+            }
+        }
+    }
+
+    #[test]
+    fn saved_bindings_load_back_the_same() {
+        let mut bindings = Bindings::default();
+        bindings.bind(Action::Up, KeyCode::I).unwrap();
+        bindings.bind(Action::Pause, KeyCode::Space).unwrap();
+        let names: Vec<(Action, &str)> = bindings.names().collect();
+        let loaded = Bindings::from_names(|action| {
+            names
+                .iter()
+                .find(|(saved, _)| *saved == action)
+                .map(|(_, name)| *name)
+        });
+        assert_eq!(loaded, bindings);
+    }
+
+    #[test]
+    fn a_bad_entry_only_loses_its_own_key() {
+        let loaded = Bindings::from_names(|action| match action {
+            Action::Up => Some("I"),
+            Action::Down => Some("NotAKey"),
+            // Reserved
+            Action::Left => Some("Escape"),
+            // Already Up's
+            Action::Right => Some("I"),
+            Action::Pause => None,
+            Action::Confirm => Some("Space"),
+        });
+        let defaults = Bindings::default();
+        assert_eq!(loaded.get(Action::Up), KeyCode::I);
+        assert_eq!(loaded.get(Action::Down), defaults.get(Action::Down));
+        assert_eq!(loaded.get(Action::Left), defaults.get(Action::Left));
+        assert_eq!(loaded.get(Action::Right), defaults.get(Action::Right));
+        assert_eq!(loaded.get(Action::Pause), defaults.get(Action::Pause));
+        assert_eq!(loaded.get(Action::Confirm), KeyCode::Space);
+    }
+
+    #[test]
+    fn a_default_thats_been_taken_falls_back_on_a_free_key() {
+        // Confirm's default went to Up, so it gets the first key in the list nobody has,
+        // which is the Up arrow Up gave up for it
+        let loaded = Bindings::from_names(|action| match action {
+            Action::Up => Some("Enter"),
+            Action::Confirm => None,
+            _ => key_name(Bindings::default().get(action)),
+        });
+        assert_eq!(loaded.get(Action::Up), KeyCode::Return);
+        assert_eq!(loaded.get(Action::Confirm), KeyCode::Up);
+        let keys: Vec<KeyCode> = Action::ALL.map(|action| loaded.get(action)).to_vec();
+        for (i, key) in keys.iter().enumerate() {
+            assert!(!keys[i + 1..].contains(key));
+        }
+    }
+}
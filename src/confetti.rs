@@ -0,0 +1,100 @@
+use crate::SCREEN_SIZE;
+use ggez::graphics::{self, Color, Rect};
+use oorandom::Rand32;
+use std::time::Duration;
+
+const BURST_SIZE: usize = 300;
+const MAX_PARTICLES: usize = 600;
+// Another burst goes off this often for as long as the win screen is up
+const REBURST_INTERVAL: Duration = Duration::from_secs(6);
+// Pixels per second per second
+const GRAVITY: f32 = 500.0;
+
+struct Particle {
+    x: f32,
+    y: f32,
+    velocity_x: f32,
+    velocity_y: f32,
+    size: f32,
+    color: Color,
+}
+
+// Has its own RNG so throwing confetti around never changes where the food goes
+pub struct Confetti {
+    rng: Rand32,
+    particles: Vec<Particle>,
+    last_burst: Option<Duration>,
+}
+
+impl Confetti {
+    pub fn new(seed: u64) -> Self {
+        Confetti {
+            rng: Rand32::new(seed),
+            particles: Vec::new(),
+            last_burst: None,
+        }
+    }
+
+    // Everything shoots up and out from the bottom middle of the screen, in the theme's
+    // confetti colors
+    fn burst(&mut self, colors: &[Color]) {
+        let room = MAX_PARTICLES - self.particles.len();
+        for _ in 0..BURST_SIZE.min(room) {
+            let color = colors[self.rng.rand_range(0..colors.len() as u32) as usize];
+            self.particles.push(Particle {
+                x: SCREEN_SIZE.0 / 2.0,
+                y: SCREEN_SIZE.1,
+                velocity_x: (self.rng.rand_float() - 0.5) * 800.0,
+                velocity_y: -400.0 - self.rng.rand_float() * 500.0,
+                size: 4.0 + self.rng.rand_float() * 6.0,
+                color,
+            });
+        }
+    }
+
+    // Called every frame while the win screen is up. The first call sets off a burst
+    pub fn update(&mut self, now: Duration, delta: Duration, colors: &[Color]) {
+        if self
+            .last_burst
+            .is_none_or(|last| now - last >= REBURST_INTERVAL)
+        {
+            self.burst(colors);
+            self.last_burst = Some(now);
+        }
+
+        let seconds = delta.as_secs_f32();
+        for particle in &mut self.particles {
+            particle.velocity_y += GRAVITY * seconds;
+            particle.x += particle.velocity_x * seconds;
+            particle.y += particle.velocity_y * seconds;
+        }
+        // Anything that's fallen off the bottom or the sides is gone for good. Going off
+        // the top is fine, since gravity brings it back
+        self.particles.retain(|particle| {
+            particle.y < SCREEN_SIZE.1 + particle.size
+                && (-particle.size..SCREEN_SIZE.0).contains(&particle.x)
+        });
+    }
+
+    // So the next time the win screen comes up it starts with a fresh burst
+    pub fn clear(&mut self) {
+        self.particles.clear();
+        self.last_burst = None;
+    }
+
+    pub fn draw(&self, canvas: &mut graphics::Canvas) {
+        for particle in &self.particles {
+            canvas.draw(
+                &graphics::Quad,
+                graphics::DrawParam::new()
+                    .dest_rect(Rect::new(
+                        particle.x,
+                        particle.y,
+                        particle.size,
+                        particle.size,
+                    ))
+                    .color(particle.color),
+            );
+        }
+    }
+}
@@ -0,0 +1,129 @@
+use crate::settings::MenuColors;
+use crate::strings::Strings;
+use crate::{draw_centered_text, SCREEN_SIZE};
+use ggez::graphics::{self, Text};
+use ggez::{Context, GameResult};
+
+// Pixels per second, kept slow enough to actually read
+const SCROLL_SPEED: f32 = 40.0;
+const LINE_HEIGHT: f32 = 36.0;
+
+// The line that sets off the death sound as it scrolls past the middle of the screen
+const SNAKE_CREDIT: Line = Line::Name("snake.mp3");
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Line {
+    // Headings and Text are string keys. Names (people, files, crates) are shown as they are
+    Heading(&'static str),
+    Text(&'static str),
+    Name(&'static str),
+    Gap,
+}
+
+const LINES: &[Line] = &[
+    Line::Heading("credits.title"),
+    Line::Gap,
+    Line::Heading("credits.made_by"),
+    Line::Name("Andy Olshanky"),
+    Line::Text("credits.based_on"),
+    Line::Gap,
+    Line::Heading("credits.music"),
+    Line::Name("snake_jazz.mp3"),
+    Line::Name("megalovania.mp3"),
+    Line::Name("congratulations.mp3"),
+    Line::Name("sad_violin.mp3"),
+    SNAKE_CREDIT,
+    Line::Gap,
+    Line::Heading("credits.built_with"),
+    Line::Name("ggez"),
+    Line::Name("oorandom"),
+    Line::Name("getrandom"),
+    Line::Gap,
+    Line::Text("credits.thanks"),
+];
+
+pub struct Credits {
+    // How far the text has moved up since it started at the bottom of the screen
+    scroll: f32,
+}
+
+impl Credits {
+    pub fn new() -> Self {
+        Credits { scroll: 0.0 }
+    }
+
+    pub fn reset(&mut self) {
+        self.scroll = 0.0;
+    }
+
+    // Everything has scrolled off the top once the scroll covers the screen plus all the lines
+    fn loop_length() -> f32 {
+        SCREEN_SIZE.1 + LINES.len() as f32 * LINE_HEIGHT
+    }
+
+    fn line_y(&self, index: usize) -> f32 {
+        SCREEN_SIZE.1 + index as f32 * LINE_HEIGHT - self.scroll
+    }
+
+    // Called every frame rather than every tick so the scroll is smooth.
+    // Returns true when the snake.mp3 credit just crossed the middle of the screen
+    pub fn update(&mut self, seconds: f32) -> bool {
+        let snake_line = LINES
+            .iter()
+            .position(|&line| line == SNAKE_CREDIT)
+            .unwrap_or(0);
+        let middle = SCREEN_SIZE.1 / 2.0;
+        let was_below = self.line_y(snake_line) > middle;
+
+        self.scroll += SCROLL_SPEED * seconds;
+        if self.scroll >= Self::loop_length() {
+            self.scroll -= Self::loop_length();
+            return false;
+        }
+
+        was_below && self.line_y(snake_line) <= middle
+    }
+
+    pub fn draw(
+        &self,
+        canvas: &mut graphics::Canvas,
+        ctx: &mut Context,
+        strings: &Strings,
+        colors: &MenuColors,
+    ) -> GameResult {
+        for (i, &line) in LINES.iter().enumerate() {
+            let y = self.line_y(i);
+            // No point laying out text nobody can see
+            if !(-LINE_HEIGHT..SCREEN_SIZE.1).contains(&y) {
+                continue;
+            }
+
+            let text = match line {
+                Line::Heading(key) => {
+                    let mut text = Text::new(strings.get(key));
+                    text.set_scale(30.0);
+                    text.fragments_mut()[0].color = Some(colors.highlight);
+                    text
+                }
+                Line::Text(key) => {
+                    let mut text = Text::new(strings.get(key));
+                    text.set_scale(22.0);
+                    text
+                }
+                Line::Name(name) => {
+                    let mut text = Text::new(name);
+                    text.set_scale(22.0);
+                    text
+                }
+                Line::Gap => continue,
+            };
+            draw_centered_text(canvas, ctx, &text, y, colors.text)?;
+        }
+
+        let mut hint = Text::new(strings.get("back_hint"));
+        hint.set_scale(16.0);
+        draw_centered_text(canvas, ctx, &hint, SCREEN_SIZE.1 - 24.0, colors.text)?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,75 @@
+use crate::settings::{DpadCorner, MenuColors};
+use crate::{arrow_points, grow_rect, Direction, SCREEN_SIZE};
+use ggez::graphics::{self, Color, Rect};
+use ggez::{Context, GameResult};
+
+// Big enough to hit with a thumb without looking
+const BUTTON_SIZE: f32 = 72.0;
+// Kept clear between the pad and the edges of the window
+const MARGIN: f32 = 16.0;
+
+// A cross of four buttons, one button's width from the corner's two edges
+fn buttons(corner: DpadCorner) -> Option<[(Direction, Rect); 4]> {
+    let center_x = match corner {
+        DpadCorner::Hidden => return None,
+        DpadCorner::BottomLeft => MARGIN + 1.5 * BUTTON_SIZE,
+        DpadCorner::BottomRight => SCREEN_SIZE.0 - MARGIN - 1.5 * BUTTON_SIZE,
+    };
+    let center_y = SCREEN_SIZE.1 - MARGIN - 1.5 * BUTTON_SIZE;
+    let button = |x: f32, y: f32| Rect::new(x, y, BUTTON_SIZE, BUTTON_SIZE);
+    let half = BUTTON_SIZE / 2.0;
+
+    Some([
+        (
+            Direction::Up,
+            button(center_x - half, center_y - 3.0 * half),
+        ),
+        (Direction::Down, button(center_x - half, center_y + half)),
+        (
+            Direction::Left,
+            button(center_x - 3.0 * half, center_y - half),
+        ),
+        (Direction::Right, button(center_x + half, center_y - half)),
+    ])
+}
+
+pub fn hit_test(corner: DpadCorner, x: f32, y: f32) -> Option<Direction> {
+    buttons(corner)?
+        .into_iter()
+        .find(|(_, rect)| rect.contains([x, y]))
+        .map(|(dir, _)| dir)
+}
+
+// See-through so the board underneath stays readable
+pub fn draw(
+    canvas: &mut graphics::Canvas,
+    ctx: &Context,
+    corner: DpadCorner,
+    colors: &MenuColors,
+) -> GameResult {
+    let Some(buttons) = buttons(corner) else {
+        return Ok(());
+    };
+
+    for (dir, rect) in buttons {
+        canvas.draw(
+            &graphics::Quad,
+            graphics::DrawParam::new().dest_rect(rect).color(Color {
+                a: 0.25,
+                ..colors.text
+            }),
+        );
+        let arrow = graphics::Mesh::new_polygon(
+            ctx,
+            graphics::DrawMode::fill(),
+            &arrow_points(grow_rect(rect, -BUTTON_SIZE / 4.0), dir),
+            Color {
+                a: 0.6,
+                ..colors.text
+            },
+        )?;
+        canvas.draw(&arrow, graphics::DrawParam::new());
+    }
+
+    Ok(())
+}
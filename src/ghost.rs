@@ -0,0 +1,115 @@
+use crate::scoring::ScoringPreset;
+use crate::storage;
+use crate::{Direction, GridPosition};
+use ggez::graphics::{self, Color};
+use ggez::Context;
+use std::collections::BTreeMap;
+
+// Where the head started plus the direction it moved on every tick is all we need
+// to play a run back, since the head movement doesn't depend on anything else
+#[derive(Debug, Clone)]
+pub struct GhostRecording {
+    start: GridPosition,
+    moves: Vec<Direction>,
+}
+
+impl GhostRecording {
+    pub fn new(start: GridPosition) -> Self {
+        GhostRecording {
+            start,
+            moves: Vec::new(),
+        }
+    }
+
+    pub fn record(&mut self, dir: Direction) {
+        self.moves.push(dir);
+    }
+
+    // One recording per scoring preset so the ghost is always the run you're chasing
+    fn file_name(preset: ScoringPreset) -> String {
+        format!("/ghost_{}.txt", preset.key())
+    }
+
+    pub fn load(ctx: &Context, preset: ScoringPreset) -> Option<Self> {
+        let entries = storage::load(ctx, &Self::file_name(preset));
+
+        let (x, y) = entries.get("start")?.split_once(',')?;
+        let start = GridPosition::new(x.parse().ok()?, y.parse().ok()?);
+        let moves = entries
+            .get("moves")?
+            .chars()
+            .map(|c| match c {
+                'U' => Some(Direction::Up),
+                'D' => Some(Direction::Down),
+                'L' => Some(Direction::Left),
+                'R' => Some(Direction::Right),
+                _ => None,
+            })
+            .collect::<Option<Vec<Direction>>>()?;
+
+        Some(GhostRecording { start, moves })
+    }
+
+    pub fn save(&self, ctx: &Context, preset: ScoringPreset) {
+        let moves: String = self
+            .moves
+            .iter()
+            .map(|dir| match dir {
+                Direction::Up => 'U',
+                Direction::Down => 'D',
+                Direction::Left => 'L',
+                Direction::Right => 'R',
+            })
+            .collect();
+
+        let mut entries = BTreeMap::new();
+        entries.insert(
+            "start".to_string(),
+            format!("{},{}", self.start.x, self.start.y),
+        );
+        entries.insert("moves".to_string(), moves);
+        storage::save(ctx, &Self::file_name(preset), &entries);
+    }
+}
+
+// Plays a recording back one move per game tick
+pub struct Ghost {
+    recording: GhostRecording,
+    pos: GridPosition,
+    tick: usize,
+}
+
+impl Ghost {
+    pub fn new(recording: GhostRecording) -> Self {
+        Ghost {
+            pos: recording.start,
+            recording,
+            tick: 0,
+        }
+    }
+
+    pub fn finished(&self) -> bool {
+        self.tick >= self.recording.moves.len()
+    }
+
+    pub fn update(&mut self) {
+        if let Some(dir) = self.recording.moves.get(self.tick) {
+            self.pos = GridPosition::new_from_move(self.pos, *dir);
+            self.tick += 1;
+        }
+    }
+
+    // Low alpha so it never gets in the way of the real snake
+    pub fn draw(&self, canvas: &mut graphics::Canvas, color: Color) {
+        if self.finished() {
+            return;
+        }
+
+        canvas.draw(
+            &graphics::Quad,
+            graphics::DrawParam::new()
+                .dest_rect(self.pos.into())
+                .color(Color { a: 0.3, ..color }),
+        );
+    }
+}
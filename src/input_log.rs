@@ -0,0 +1,212 @@
+use crate::settings::Settings;
+use crate::storage;
+use crate::{Direction, GRID_SIZE};
+use std::path::PathBuf;
+
+// What --record and --playback ask for. Recording keeps the most recent run, overwriting
+// the file each time one ends
+pub enum InputMode {
+    Live,
+    Record { path: PathBuf, log: InputLog },
+    Playback(InputLog),
+}
+
+impl InputMode {
+    pub fn from_args() -> Result<Self, String> {
+        let mut args = std::env::args().skip(1);
+        let mut mode = InputMode::Live;
+        while let Some(arg) = args.next() {
+            let flag = arg.as_str();
+            let path = match flag {
+                // Not about input at all, main looks for it itself
+                "--no-audio" => continue,
+                "--record" | "--playback" => args
+                    .next()
+                    .map(PathBuf::from)
+                    .ok_or(format!("{} needs a file", flag))?,
+                _ => return Err(format!("Unknown argument {}", flag)),
+            };
+            mode = if flag == "--record" {
+                InputMode::Record {
+                    path,
+                    log: InputLog::default(),
+                }
+            } else {
+                InputMode::Playback(InputLog::load(&path)?)
+            };
+        }
+        Ok(mode)
+    }
+}
+
+// Everything needed to play a run back exactly: the seed its board came from, the rules
+// it was played under, and the direction the snake took on every tick that it turned
+#[derive(Default)]
+pub struct InputLog {
+    pub seed: u64,
+    grid: (i16, i16),
+    scoring: String,
+    walls: bool,
+    turns: Vec<(u32, Direction)>,
+}
+
+fn direction_key(dir: Direction) -> char {
+    match dir {
+        Direction::Up => 'U',
+        Direction::Down => 'D',
+        Direction::Left => 'L',
+        Direction::Right => 'R',
+    }
+}
+
+fn direction_from_key(key: &str) -> Option<Direction> {
+    match key {
+        "U" => Some(Direction::Up),
+        "D" => Some(Direction::Down),
+        "L" => Some(Direction::Left),
+        "R" => Some(Direction::Right),
+        _ => None,
+    }
+}
+
+impl InputLog {
+    pub fn new(seed: u64, settings: &Settings) -> Self {
+        InputLog {
+            seed,
+            grid: GRID_SIZE,
+            scoring: settings.scoring.key().to_string(),
+            walls: settings.walls,
+            turns: Vec::new(),
+        }
+    }
+
+    // Called every tick, but only keeps the ticks where the direction changed
+    pub fn record(&mut self, tick: u32, dir: Direction) {
+        if self.turns.last().map(|&(_, last)| last) != Some(dir) {
+            self.turns.push((tick, dir));
+        }
+    }
+
+    pub fn turn_at(&self, tick: u32) -> Option<Direction> {
+        self.turns
+            .binary_search_by_key(&tick, |&(t, _)| t)
+            .ok()
+            .map(|i| self.turns[i].1)
+    }
+
+    // A run only plays back the same way on the same board under the same rules
+    pub fn check(&self, settings: &Settings) -> Result<(), String> {
+        if self.grid != GRID_SIZE {
+            return Err(format!(
+                "Recorded on a {}x{} board, but this one is {}x{}",
+                self.grid.0, self.grid.1, GRID_SIZE.0, GRID_SIZE.1
+            ));
+        }
+        if self.scoring != settings.scoring.key() || self.walls != settings.walls {
+            return Err(format!(
+                "Recorded with scoring={} walls={}, but the settings have scoring={} walls={}",
+                self.scoring,
+                self.walls,
+                settings.scoring.key(),
+                settings.walls
+            ));
+        }
+        Ok(())
+    }
+
+    // Written straight to the path given on the command line, not the user dir
+    pub fn save(&self, path: &PathBuf) {
+        let turns: Vec<String> = self
+            .turns
+            .iter()
+            .map(|&(tick, dir)| format!("{}:{}", tick, direction_key(dir)))
+            .collect();
+        let contents = format!(
+            "grid={}x{}\nscoring={}\nwalls={}\nseed={}\nturns={}\n",
+            self.grid.0,
+            self.grid.1,
+            self.scoring,
+            self.walls,
+            self.seed,
+            turns.join(",")
+        );
+        if let Err(e) = std::fs::write(path, contents) {
+            eprintln!("Could not save {}: {}", path.display(), e);
+        }
+    }
+
+    fn load(path: &PathBuf) -> Result<Self, String> {
+        let contents = std::fs::read_to_string(path)
+            .map_err(|e| format!("Could not read {}: {}", path.display(), e))?;
+        let entries = storage::parse(&contents);
+        let field = |key: &str| {
+            entries
+                .get(key)
+                .ok_or(format!("{} is missing {}", path.display(), key))
+        };
+        let bad = |key: &str| format!("{} has a bad {}", path.display(), key);
+
+        let grid = field("grid")?
+            .split_once('x')
+            .and_then(|(x, y)| Some((x.parse().ok()?, y.parse().ok()?)))
+            .ok_or(bad("grid"))?;
+        let mut turns = Vec::new();
+        for turn in field("turns")?.split(',').filter(|turn| !turn.is_empty()) {
+            let turn = turn
+                .split_once(':')
+                .and_then(|(tick, dir)| Some((tick.parse().ok()?, direction_from_key(dir)?)))
+                .ok_or(bad("turns"))?;
+            turns.push(turn);
+        }
+
+        Ok(InputLog {
+            seed: field("seed")?.parse().map_err(|_| bad("seed"))?,
+            grid,
+            scoring: field("scoring")?.clone(),
+            walls: field("walls")?.parse().map_err(|_| bad("walls"))?,
+            turns,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn a_saved_log_loads_back_the_same() {
+        let mut log = InputLog::new(1234, &Settings::default());
+        log.record(1, Direction::Up);
+        log.record(2, Direction::Up);
+        log.record(9, Direction::Left);
+        let path = std::env::temp_dir().join(format!("snake-log-{}.txt", std::process::id()));
+        log.save(&path);
+        let loaded = InputLog::load(&path);
+        let _ = std::fs::remove_file(&path);
+
+        let loaded = loaded.unwrap();
+        assert_eq!(loaded.seed, 1234);
+        assert_eq!(loaded.turns, [(1, Direction::Up), (9, Direction::Left)]);
+        assert_eq!(loaded.turn_at(9), Some(Direction::Left));
+        assert_eq!(loaded.turn_at(2), None);
+        assert!(loaded.check(&Settings::default()).is_ok());
+    }
+
+    #[test]
+    fn other_rules_are_refused() {
+        let log = InputLog::new(1, &Settings::default());
+        let mut settings = Settings::default();
+        settings.walls = !settings.walls;
+        assert!(log.check(&settings).is_err());
+
+        let mut settings = Settings::default();
+        settings.scoring = settings.scoring.next();
+        assert!(log.check(&settings).is_err());
+
+        let other_board = InputLog {
+            grid: (GRID_SIZE.0 + 1, GRID_SIZE.1),
+            ..InputLog::new(1, &Settings::default())
+        };
+        assert!(other_board.check(&Settings::default()).is_err());
+    }
+}
@@ -0,0 +1,297 @@
+use crate::scoring::ScoringPreset;
+use crate::settings::MenuColors;
+use crate::storage;
+use crate::strings::Strings;
+use crate::{draw_centered_text, SCREEN_SIZE};
+use ggez::graphics::{self, Color, Text};
+use ggez::mint::Point2;
+use ggez::{Context, GameResult};
+use std::collections::BTreeMap;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const LEADERBOARD_FILE: &str = "/leaderboard.txt";
+// For each scoring preset, since their scores can't be compared
+const MAX_ENTRIES: usize = 10;
+
+// Each entry is "<rank>=score,length,mode,date,name". New columns only ever get added
+// on the end, so a newer version still loads here, just without the columns it added
+const VERSION: u32 = 1;
+
+// Where each column starts, as a fraction of the screen width
+const COLUMNS: [f32; 6] = [0.1, 0.17, 0.37, 0.5, 0.64, 0.78];
+
+#[derive(Debug, Clone)]
+pub struct LeaderboardEntry {
+    pub score: u32,
+    pub length: u32,
+    pub mode: ScoringPreset,
+    // YYYY-MM-DD, in UTC
+    pub date: String,
+    pub name: Option<String>,
+}
+
+impl LeaderboardEntry {
+    pub fn new(score: u32, length: u32, mode: ScoringPreset) -> Self {
+        LeaderboardEntry {
+            score,
+            length,
+            mode,
+            date: today(),
+            name: None,
+        }
+    }
+
+    fn parse(line: &str) -> Option<Self> {
+        let mut fields = line.split(',');
+        let score = fields.next()?.parse().ok()?;
+        let length = fields.next()?.parse().ok()?;
+        let mode = ScoringPreset::from_key(fields.next()?)?;
+        let date = fields.next()?.to_string();
+        let name = fields
+            .next()
+            .filter(|name| !name.is_empty())
+            .map(String::from);
+        Some(LeaderboardEntry {
+            score,
+            length,
+            mode,
+            date,
+            name,
+        })
+    }
+
+    fn to_line(&self) -> String {
+        format!(
+            "{},{},{},{},{}",
+            self.score,
+            self.length,
+            self.mode.key(),
+            self.date,
+            self.name.as_deref().unwrap_or("")
+        )
+    }
+}
+
+// Every preset's runs share the one list, in score order, so each preset's own top ten
+// is just its entries picked out in order
+#[derive(Debug, Default)]
+pub struct Leaderboard {
+    entries: Vec<LeaderboardEntry>,
+}
+
+impl Leaderboard {
+    // A file that's missing a version or has any entry we can't read is treated as empty,
+    // since half a leaderboard would be worse than none
+    pub fn load(ctx: &Context) -> Self {
+        let file = storage::load(ctx, LEADERBOARD_FILE);
+        if file
+            .get("version")
+            .and_then(|version| version.parse::<u32>().ok())
+            .is_none()
+        {
+            return Leaderboard::default();
+        }
+
+        let entries: Option<Vec<LeaderboardEntry>> = (0..MAX_ENTRIES * ScoringPreset::ALL.len())
+            .map_while(|rank| file.get(&rank.to_string()))
+            .map(|line| LeaderboardEntry::parse(line))
+            .collect();
+
+        Leaderboard {
+            entries: entries.unwrap_or_default(),
+        }
+    }
+
+    fn save(&self, ctx: &Context) {
+        let mut file = BTreeMap::new();
+        file.insert("version".to_string(), VERSION.to_string());
+        for (rank, entry) in self.entries.iter().enumerate() {
+            file.insert(rank.to_string(), entry.to_line());
+        }
+        storage::save(ctx, LEADERBOARD_FILE, &file);
+    }
+
+    // Adds the run if it makes the top ten for its preset and returns where it placed
+    // among them
+    pub fn insert(&mut self, ctx: &Context, entry: LeaderboardEntry) -> Option<usize> {
+        let rank = self.place(entry)?;
+        self.save(ctx);
+        Some(rank)
+    }
+
+    // Ties go below the runs that got there first
+    fn place(&mut self, entry: LeaderboardEntry) -> Option<usize> {
+        if entry.score == 0 {
+            return None;
+        }
+
+        let mode = entry.mode;
+        let rank = self
+            .ranked(mode)
+            .filter(|other| other.score >= entry.score)
+            .count();
+        if rank >= MAX_ENTRIES {
+            return None;
+        }
+
+        let index = self
+            .entries
+            .iter()
+            .position(|other| entry.score > other.score)
+            .unwrap_or(self.entries.len());
+        self.entries.insert(index, entry);
+        if let Some(dropped) = self.index_of(mode, MAX_ENTRIES) {
+            self.entries.remove(dropped);
+        }
+        Some(rank)
+    }
+
+    // The preset's runs, best first
+    fn ranked(&self, mode: ScoringPreset) -> impl Iterator<Item = &LeaderboardEntry> {
+        self.entries.iter().filter(move |entry| entry.mode == mode)
+    }
+
+    // Where the preset's run at that rank sits in the shared list
+    fn index_of(&self, mode: ScoringPreset, rank: usize) -> Option<usize> {
+        self.entries
+            .iter()
+            .enumerate()
+            .filter(|(_, entry)| entry.mode == mode)
+            .nth(rank)
+            .map(|(index, _)| index)
+    }
+
+    pub fn set_name(&mut self, ctx: &Context, mode: ScoringPreset, rank: usize, name: &str) {
+        if let Some(index) = self.index_of(mode, rank) {
+            self.entries[index].name = Some(name.to_string());
+            self.save(ctx);
+        }
+    }
+
+    // Only the one preset's board, since its scores mean nothing next to another's
+    pub fn draw(
+        &self,
+        canvas: &mut graphics::Canvas,
+        ctx: &mut Context,
+        mode: ScoringPreset,
+        highlight: Option<usize>,
+        strings: &Strings,
+        colors: &MenuColors,
+    ) -> GameResult {
+        let mode_name = strings.get(&format!("scoring.{}", mode.key()));
+        let mut title = Text::new(strings.format("leaderboard.heading", &[&mode_name]));
+        title.set_scale(40.0);
+        draw_centered_text(canvas, ctx, &title, 40.0, colors.text)?;
+
+        let header = ["rank", "name", "length", "score", "mode", "date"]
+            .map(|column| strings.get(&format!("leaderboard.{}", column)));
+        draw_row(canvas, &header, 110.0, colors.dim_text);
+
+        if self.ranked(mode).next().is_none() {
+            let mut empty = Text::new(strings.get("leaderboard.empty"));
+            empty.set_scale(24.0);
+            draw_centered_text(canvas, ctx, &empty, 160.0, colors.text)?;
+        }
+
+        for (rank, entry) in self.ranked(mode).enumerate() {
+            let row = [
+                (rank + 1).to_string(),
+                entry.name.clone().unwrap_or_else(|| "---".to_string()),
+                entry.length.to_string(),
+                entry.score.to_string(),
+                strings.get(&format!("scoring.{}", entry.mode.key())),
+                entry.date.clone(),
+            ];
+            let color = if highlight == Some(rank) {
+                colors.highlight
+            } else {
+                colors.text
+            };
+            draw_row(canvas, &row, 150.0 + rank as f32 * 40.0, color);
+        }
+
+        let mut hint = Text::new(strings.get("leaderboard.hint"));
+        hint.set_scale(16.0);
+        draw_centered_text(canvas, ctx, &hint, SCREEN_SIZE.1 - 32.0, colors.text)?;
+
+        Ok(())
+    }
+}
+
+fn draw_row(canvas: &mut graphics::Canvas, cells: &[String; 6], y: f32, color: Color) {
+    for (cell, column) in cells.iter().zip(COLUMNS) {
+        let mut text = Text::new(cell.as_str());
+        text.set_scale(24.0);
+        text.fragments_mut()[0].color = Some(color);
+        canvas.draw(
+            &text,
+            Point2 {
+                x: SCREEN_SIZE.0 * column,
+                y,
+            },
+        );
+    }
+}
+
+// Today's date from the system clock, without pulling in a date crate for it
+fn today() -> String {
+    let days = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |since| since.as_secs() / 86400) as i64;
+
+    // Howard Hinnant's days-to-civil algorithm
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let day_of_era = z.rem_euclid(146097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+    let month = if shifted_month < 10 {
+        shifted_month + 3
+    } else {
+        shifted_month - 9
+    };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(score: u32, mode: ScoringPreset) -> LeaderboardEntry {
+        LeaderboardEntry::new(score, 3, mode)
+    }
+
+    #[test]
+    fn each_preset_is_ranked_on_its_own() {
+        let mut board = Leaderboard::default();
+        assert_eq!(board.place(entry(5000, ScoringPreset::Arcade)), Some(0));
+        // Way under the arcade score, but still the best classic run
+        assert_eq!(board.place(entry(40, ScoringPreset::Classic)), Some(0));
+        assert_eq!(board.place(entry(60, ScoringPreset::Classic)), Some(0));
+        assert_eq!(board.place(entry(40, ScoringPreset::Classic)), Some(2));
+        let classic: Vec<u32> = board
+            .ranked(ScoringPreset::Classic)
+            .map(|entry| entry.score)
+            .collect();
+        assert_eq!(classic, [60, 40, 40]);
+    }
+
+    #[test]
+    fn a_full_preset_does_not_push_out_another() {
+        let mut board = Leaderboard::default();
+        board.place(entry(1, ScoringPreset::Classic));
+        for score in 0..MAX_ENTRIES as u32 {
+            board.place(entry(1000 + score, ScoringPreset::Arcade));
+        }
+        assert_eq!(board.place(entry(999, ScoringPreset::Arcade)), None);
+        assert_eq!(board.place(entry(2000, ScoringPreset::Arcade)), Some(0));
+        assert_eq!(board.ranked(ScoringPreset::Arcade).count(), MAX_ENTRIES);
+        assert_eq!(board.ranked(ScoringPreset::Classic).count(), 1);
+        assert_eq!(board.index_of(ScoringPreset::Classic, 0), Some(MAX_ENTRIES));
+    }
+}
@@ -1,13 +1,67 @@
+use achievements::{Achievements, RunProgress};
+use audio::{AudioManager, Sfx, Sting, Track};
+use beat::{BeatClock, BEATS_PER_BAR};
+use bindings::{Action, BindError, Bindings};
+use confetti::Confetti;
+use credits::Credits;
 use ggez::{
-    audio::{SoundSource, Source},
-    event::{self, EventHandler, MouseButton},
+    event::{self, winit_event::TouchPhase, EventHandler, MouseButton},
     graphics::{self, Color, Rect, Text},
-    input::keyboard::{KeyCode, KeyInput},
+    input::{
+        gamepad::{
+            gilrs::{Axis, Button as GamepadButton},
+            GamepadId,
+        },
+        keyboard::{KeyCode, KeyInput},
+    },
     mint::Point2,
     Context, GameResult,
 };
+use ghost::{Ghost, GhostRecording};
+use input_log::{InputLog, InputMode};
+use leaderboard::{Leaderboard, LeaderboardEntry};
+use name_entry::NameEntry;
 use oorandom::Rand32;
+use popups::Popups;
+use records::HighScores;
+use replay::{Replay, ReplayBuffer, ReplayFrame};
+use scoring::ScoreKeeper;
+use settings::{
+    step_volume, BoardColors, ControlScheme, MenuColors, MouseMode, Settings, Speed, Theme,
+};
+use splits::{SplitResult, SplitTracker, SPLIT_INTERVAL};
+use sprites::Sprites;
+use stats::{RunEnd, SessionStats};
 use std::collections::VecDeque;
+use strings::Strings;
+use title_snake::TitleSnake;
+use trail::HeadTrail;
+
+mod achievements;
+mod audio;
+mod autopath;
+mod badges;
+mod beat;
+mod bindings;
+mod confetti;
+mod credits;
+mod dpad;
+mod ghost;
+mod input_log;
+mod leaderboard;
+mod name_entry;
+mod popups;
+mod records;
+mod replay;
+mod scoring;
+mod settings;
+mod splits;
+mod sprites;
+mod stats;
+mod storage;
+mod strings;
+mod title_snake;
+mod trail;
 
 const GRID_SIZE: (i16, i16) = (30, 20);
 const TARGET_LENGTH: u32 = (GRID_SIZE.0 * GRID_SIZE.1) as u32;
@@ -16,12 +70,89 @@ const SCREEN_SIZE: (f32, f32) = (
     GRID_SIZE.0 as f32 * GRID_CELL_SIZE.0 as f32,
     GRID_SIZE.1 as f32 * GRID_CELL_SIZE.1 as f32,
 );
-const DESIRED_FPS: u32 = 10;
+// How far a segment sits inside its cell on the sides it isn't joined to another one
+const SEGMENT_GAP: f32 = 3.0;
+const SEGMENT_CAP_RADIUS: f32 = 8.0;
+// Dying flashes the snake white twice, each flash on and then off for this long, and then
+// takes it away a segment at a time from the tail
+const DEATH_FLASH_LENGTH: std::time::Duration = std::time::Duration::from_millis(100);
+const DEATH_FLASHES: u32 = 2;
+const DEATH_VANISH_LENGTH: std::time::Duration = std::time::Duration::from_secs(1);
+// The board shakes when the snake dies, starting this many pixels either way and dying
+// down to nothing
+const SHAKE_AMPLITUDE: f32 = 6.0;
+const SHAKE_LENGTH: std::time::Duration = std::time::Duration::from_millis(400);
+// How many ticks a cell the tail left stays in the fading trail
+const TRAIL_TICKS: u32 = 4;
+// How long new food takes to pop up to full size
+const FOOD_POP_LENGTH: std::time::Duration = std::time::Duration::from_millis(300);
+// Each of the head's eyes is a square this many pixels across
+const EYE_SIZE: f32 = 6.0;
+// Once the snake fills 90% of the board the game kicks into a faster sudden-death finale
+const FINALE_LENGTH: u32 = TARGET_LENGTH * 9 / 10;
+const FINALE_SPEEDUP: u32 = 2;
+// Any faster than this many ticks a second and the grid lines shimmer against the moving
+// snake, so they're left out until it slows down again
+const GRID_LINES_MAX_RATE: u32 = 14;
+// How long the board takes to go from day through night and back to day again
+const DAY_NIGHT_CYCLE: std::time::Duration = std::time::Duration::from_secs(600);
+const FINALE_MUSIC_PITCH: f32 = 1.1;
+// Any faster than this many ticks a second and the tick click only goes on every other
+// tick, so it stays a click instead of a buzz
+const TICK_SOUND_MAX_RATE: f32 = 12.0;
+// Thin enough (and see-through) that the top row of the board is still readable under it
+const PROGRESS_BAR_HEIGHT: f32 = 4.0;
+// Radians per second for the red flash where the snake bit itself
+const COLLISION_FLASH_SPEED: f32 = 10.0;
+// Radians per second for the "NEW HIGH SCORE" banner's pulse
+const NEW_HIGH_SCORE_PULSE_SPEED: f32 = 6.0;
+// Finishing inside each of these earns another star on the win screen
+const RATING_THRESHOLDS: [std::time::Duration; 4] = [
+    std::time::Duration::from_secs(40 * 60),
+    std::time::Duration::from_secs(30 * 60),
+    std::time::Duration::from_secs(25 * 60),
+    std::time::Duration::from_secs(20 * 60),
+];
+// How long the latest split stays on the HUD, in game time so pausing holds it there
+const SPLIT_DISPLAY_LENGTH: std::time::Duration = std::time::Duration::from_secs(3);
 
 const TITLE_SCREEN: u8 = 1;
 const GAMEPLAY: u8 = 2;
 const GAME_LOSS: u8 = 3;
 const GAME_WIN: u8 = 4;
+const PAUSE_MENU: u8 = 5;
+const RESTART_PROMPT: u8 = 6;
+const SETTINGS_SCREEN: u8 = 7;
+const QUIT_PROMPT: u8 = 8;
+const CREDITS_SCREEN: u8 = 9;
+const NAME_ENTRY: u8 = 10;
+const LEADERBOARD: u8 = 11;
+const COUNTDOWN: u8 = 12;
+const STATS_SCREEN: u8 = 13;
+const ACHIEVEMENTS_SCREEN: u8 = 14;
+const CONTROLS_SCREEN: u8 = 15;
+const GAMEPAD_SCREEN: u8 = 16;
+const LOADING_SCREEN: u8 = 17;
+const DISPLAY_SCREEN: u8 = 18;
+// The snake flashing and falling apart between dying and the loss screen
+const DYING: u8 = 19;
+// Says which sounds couldn't be loaded, over the title once loading's done
+const LOAD_ERROR: u8 = 20;
+
+// The whole fade, out and back in
+const FADE_LENGTH: std::time::Duration = std::time::Duration::from_millis(300);
+// Enough to get through a corner and back out again without dropping a press
+const MAX_QUEUED_TURNS: usize = 3;
+// How long the single switch scheme shows each way to turn before moving on to the next
+const SWITCH_ROTATE_INTERVAL: std::time::Duration = std::time::Duration::from_millis(500);
+// The longest gap between two presses of the same direction that still counts as a dash
+const DASH_WINDOW: std::time::Duration = std::time::Duration::from_millis(150);
+// 3-2-1 before every new game
+const COUNTDOWN_LENGTH: std::time::Duration = std::time::Duration::from_secs(3);
+// How long the restart prompt waits for an answer before going back to the game
+const RESTART_PROMPT_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(5);
+// How long the music volume stays up on screen after + or - changes it
+const VOLUME_INDICATOR_LENGTH: std::time::Duration = std::time::Duration::from_secs(1);
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 struct GridPosition {
@@ -54,6 +185,39 @@ impl GridPosition {
             Direction::Right => GridPosition::new((pos.x + 1).rem_euclid(GRID_SIZE.0), pos.y),
         }
     }
+
+    // True if moving in dir from pos would go off the edge of the board
+    pub fn move_wraps(pos: GridPosition, dir: Direction) -> bool {
+        match dir {
+            Direction::Up => pos.y == 0,
+            Direction::Down => pos.y == GRID_SIZE.1 - 1,
+            Direction::Left => pos.x == 0,
+            Direction::Right => pos.x == GRID_SIZE.0 - 1,
+        }
+    }
+
+    // The cell under a point on the screen, kept on the board
+    pub fn from_screen(x: f32, y: f32) -> Self {
+        GridPosition::new(
+            ((x / GRID_CELL_SIZE.0 as f32) as i16).clamp(0, GRID_SIZE.0 - 1),
+            ((y / GRID_CELL_SIZE.1 as f32) as i16).clamp(0, GRID_SIZE.1 - 1),
+        )
+    }
+
+    // Which way other is from here, if it's right next door. Cells that are only next to
+    // each other by wrapping round the edge don't count
+    pub fn direction_to(self, other: GridPosition) -> Option<Direction> {
+        [
+            Direction::Up,
+            Direction::Down,
+            Direction::Left,
+            Direction::Right,
+        ]
+        .into_iter()
+        .find(|&dir| {
+            !GridPosition::move_wraps(self, dir) && GridPosition::new_from_move(self, dir) == other
+        })
+    }
 }
 
 // Allows us to easily go from GridPosition to the graphics display
@@ -93,16 +257,83 @@ impl Direction {
         }
     }
 
+    pub fn rotate_cw(self) -> Self {
+        match self {
+            Direction::Up => Direction::Right,
+            Direction::Right => Direction::Down,
+            Direction::Down => Direction::Left,
+            Direction::Left => Direction::Up,
+        }
+    }
+
+    pub fn rotate_ccw(self) -> Self {
+        self.rotate_cw().inverse()
+    }
+
+    // Only a right angle is a turn. Carrying on the same way changes nothing, and going
+    // back the way it came would run straight into the neck
+    pub fn turns_from(self, previous: Direction) -> bool {
+        self != previous && self != previous.inverse()
+    }
+
+    // WASD and the numpad work everywhere the arrows do. With NumLock off most platforms
+    // send the numpad as the arrow keys themselves, so both ways are covered
     pub fn from_keycode(key: KeyCode) -> Option<Direction> {
         match key {
-            KeyCode::Up => Some(Direction::Up),
-            KeyCode::Down => Some(Direction::Down),
-            KeyCode::Left => Some(Direction::Left),
-            KeyCode::Right => Some(Direction::Right),
+            KeyCode::Up | KeyCode::W | KeyCode::Numpad8 => Some(Direction::Up),
+            KeyCode::Down | KeyCode::S | KeyCode::Numpad2 => Some(Direction::Down),
+            KeyCode::Left | KeyCode::A | KeyCode::Numpad4 => Some(Direction::Left),
+            KeyCode::Right | KeyCode::D | KeyCode::Numpad6 => Some(Direction::Right),
+            _ => None,
+        }
+    }
+
+    // H J K L, the way vim moves the cursor
+    pub fn from_vim_key(key: KeyCode) -> Option<Direction> {
+        match key {
+            KeyCode::H => Some(Direction::Left),
+            KeyCode::J => Some(Direction::Down),
+            KeyCode::K => Some(Direction::Up),
+            KeyCode::L => Some(Direction::Right),
+            _ => None,
+        }
+    }
+
+    pub fn from_gamepad_button(btn: GamepadButton) -> Option<Direction> {
+        match btn {
+            GamepadButton::DPadUp => Some(Direction::Up),
+            GamepadButton::DPadDown => Some(Direction::Down),
+            GamepadButton::DPadLeft => Some(Direction::Left),
+            GamepadButton::DPadRight => Some(Direction::Right),
             _ => None,
         }
     }
 
+    // Whichever axis is pushed further wins, so a diagonal always picks the same way.
+    // A dead even diagonal goes up or down. Up is positive here, the same as on a stick
+    pub fn dominant(x: f32, y: f32) -> Direction {
+        if x.abs() > y.abs() {
+            if x > 0.0 {
+                Direction::Right
+            } else {
+                Direction::Left
+            }
+        } else if y > 0.0 {
+            Direction::Up
+        } else {
+            Direction::Down
+        }
+    }
+
+    // The deadzone is how far the stick has to be pushed, out of 1, before it counts
+    pub fn from_stick(x: f32, y: f32, deadzone: f32) -> Option<Direction> {
+        if x.hypot(y) < deadzone {
+            None
+        } else {
+            Some(Direction::dominant(x, y))
+        }
+    }
+
     pub fn random_direction(rng: &mut Rand32) -> Self {
         let rand_num = rng.rand_range(0..4);
         match rand_num {
@@ -118,35 +349,80 @@ impl Direction {
 #[derive(Debug, Clone, Copy)]
 struct Segment {
     pos: GridPosition,
+    // Where it was before the last tick, for drawing it sliding between the two
+    prev: GridPosition,
 }
 
 impl Segment {
     pub fn new(pos: GridPosition) -> Self {
-        Segment { pos }
+        Segment { pos, prev: pos }
+    }
+
+    // Partway from where it was to where it is now, t being how far through the tick it
+    // is. A move that wrapped round the board snaps straight there rather than sliding
+    // across the whole screen
+    fn cell(&self, t: f32) -> Rect {
+        let (from, to): (Rect, Rect) = (self.prev.into(), self.pos.into());
+        let wrapped = (self.pos.x - self.prev.x).abs() > 2 || (self.pos.y - self.prev.y).abs() > 2;
+        if wrapped {
+            return to;
+        }
+        Rect::new(
+            from.x + (to.x - from.x) * t,
+            from.y + (to.y - from.y) * t,
+            to.w,
+            to.h,
+        )
     }
 }
 
 // Another abstraction but for food
 struct Food {
     pos: GridPosition,
+    // When it turned up, if it popped in after the last one was eaten. The first food of
+    // a board is already sitting there when the game starts
+    spawned: Option<std::time::Duration>,
 }
 
 impl Food {
     pub fn new(pos: GridPosition) -> Self {
-        Food { pos }
+        Food { pos, spawned: None }
     }
 
-    // not great for scaling, look up InstanceArray or SpriteBatch for future projects
-    fn draw(&self, canvas: &mut graphics::Canvas) {
-        // r g b opacity
-        let color = [0.0, 0.0, 1.0, 1.0];
+    fn respawn(&mut self, pos: GridPosition, now: std::time::Duration) {
+        self.pos = pos;
+        self.spawned = Some(now);
+    }
 
-        canvas.draw(
-            &graphics::Quad,
-            graphics::DrawParam::new()
-                .dest_rect(self.pos.into())
-                .color(color),
-        );
+    // not great for scaling, look up InstanceArray or SpriteBatch for future projects
+    fn draw(
+        &self,
+        canvas: &mut graphics::Canvas,
+        ctx: &Context,
+        colors: &BoardColors,
+        sprites: &Sprites,
+        reduce_motion: bool,
+    ) -> GameResult {
+        let mut cell: Rect = self.pos.into();
+        // Grows out of the middle of its cell, going a little too big before settling
+        if let Some(spawned) = self.spawned.filter(|_| !reduce_motion) {
+            let t = (ctx.time.time_since_start() - spawned).as_secs_f32()
+                / FOOD_POP_LENGTH.as_secs_f32();
+            if t < 1.0 {
+                let overshoot = 1.7;
+                let scale =
+                    (1.0 + (overshoot + 1.0) * (t - 1.0).powi(3) + overshoot * (t - 1.0).powi(2))
+                        .max(0.0);
+                let (w, h) = (cell.w * scale, cell.h * scale);
+                cell = Rect::new(
+                    cell.x + (cell.w - w) / 2.0,
+                    cell.y + (cell.h - h) / 2.0,
+                    w,
+                    h,
+                );
+            }
+        }
+        draw_food(canvas, ctx, cell, colors, sprites)
     }
 }
 
@@ -154,6 +430,8 @@ impl Food {
 enum Ate {
     Itself,
     Food,
+    // Only happens when walls are turned on
+    Wall,
 }
 
 struct Snake {
@@ -162,8 +440,15 @@ struct Snake {
     body: VecDeque<Segment>,
     ate: Option<Ate>,
     last_update_dir: Direction,
-    next_dir: Option<Direction>,
+    // Turns pressed after this tick's turn was already picked, taken one per tick
+    next_dirs: VecDeque<Direction>,
     num_segments: u32,
+    // Cells the tail just left and how many ticks ago, for the fading trail. Only ever
+    // drawn, never part of the snake as far as collisions or food are concerned
+    vacated: VecDeque<(GridPosition, u32)>,
+    // Every cell the head moved into on the last update, and whether that step wrapped
+    // round the edge. Two of them after a dash, and none if it ran into a wall
+    steps: Vec<(GridPosition, bool)>,
 }
 
 impl Snake {
@@ -178,9 +463,59 @@ impl Snake {
             body,
             ate: None,
             last_update_dir: Direction::Right,
-            next_dir: None,
-            num_segments: num_segments,
+            next_dirs: VecDeque::new(),
+            num_segments,
+            vacated: VecDeque::new(),
+            steps: Vec::new(),
+        }
+    }
+
+    // Turns the snake before it has moved at all, as long as that doesn't point it
+    // straight back into its own body
+    fn face(&mut self, dir: Direction) {
+        let ahead = GridPosition::new_from_move(self.head.pos, dir);
+        if self.body.front().is_some_and(|seg| seg.pos == ahead) {
+            return;
+        }
+        self.dir = dir;
+        self.last_update_dir = dir;
+        self.next_dirs.clear();
+    }
+
+    // Shared by everything that can steer mid-game, keys or the on-screen pad. Returns
+    // true if the snake will turn on the very next tick.
+    // The first legal turn pressed in a tick is the one that tick makes, and every one after
+    // it is queued, each checked against the turn before it. Anything that isn't a legal turn
+    // is dropped without using anything up, so pressing back the way the snake came and then
+    // sideways in the same tick still gets the sideways turn
+    fn steer(&mut self, dir: Direction) -> bool {
+        if self.dir == self.last_update_dir && self.next_dirs.is_empty() {
+            // No turn picked for this tick yet and nothing waiting, so it can happen
+            // straight away. With turns still queued it has to go in after them, or it
+            // would jump ahead and the next one out could send the snake back on itself
+            if dir.turns_from(self.dir) {
+                self.dir = dir;
+                return true;
+            }
+        } else {
+            let last = self.next_dirs.back().copied().unwrap_or(self.dir);
+            if dir.turns_from(last) && self.next_dirs.len() < MAX_QUEUED_TURNS {
+                self.next_dirs.push_back(dir);
+            }
         }
+        false
+    }
+
+    // A quarter turn from wherever the snake will be heading once everything already
+    // queued has happened. A rotation is always a legal turn, so none of the queued ones
+    // can be dropped, which makes working it out now the same as waiting until it's used
+    fn rotate(&mut self, clockwise: bool) -> bool {
+        let heading = self.next_dirs.back().copied().unwrap_or(self.dir);
+        self.steer(if clockwise {
+            heading.rotate_cw()
+        } else {
+            heading.rotate_ccw()
+        })
     }
 
     fn eats(&self, food: &Food) -> bool {
@@ -196,15 +531,69 @@ impl Snake {
         false
     }
 
-    fn update(&mut self, food: &Food) {
-        // if last_update_dir is the same as dir, and next_dir is a thing, set dir to next_dir
-        if self.last_update_dir == self.dir && self.next_dir.is_some() {
-            self.dir = self.next_dir.unwrap();
-            self.next_dir = None;
+    // A dash takes two steps in the one tick. The cell in between counts just the same, so
+    // food there is eaten (and the snake keeps the growth), and anything deadly there ends
+    // the run before the second step is taken
+    fn update(&mut self, food: &Food, walls: bool, dash: bool) {
+        // Once the last turn has been made, the next queued one (if any) takes over
+        if self.last_update_dir == self.dir {
+            if let Some(next) = self.next_dirs.pop_front() {
+                self.dir = next;
+            }
+        }
+
+        // Every segment moves into the place of the one in front of it, so each one's old
+        // spot is whatever was at its place along the snake before the move. A segment that
+        // just grew on the end has nowhere to come from and stays put
+        let before: Vec<GridPosition> = std::iter::once(self.head.pos)
+            .chain(self.body.iter().map(|seg| seg.pos))
+            .collect();
+        for (_, age) in &mut self.vacated {
+            *age += 1;
+        }
+        self.vacated.retain(|&(_, age)| age < TRAIL_TICKS);
+        self.steps.clear();
+
+        self.step(Some(food), walls);
+        if dash {
+            match self.ate {
+                Some(Ate::Itself | Ate::Wall) => (),
+                // The food's gone now, so the second step can't eat it again
+                Some(Ate::Food) => {
+                    self.step(None, walls);
+                    if self.ate.is_none() {
+                        self.ate = Some(Ate::Food);
+                    }
+                }
+                None => self.step(Some(food), walls),
+            }
+        }
+
+        for (i, seg) in std::iter::once(&mut self.head)
+            .chain(self.body.iter_mut())
+            .enumerate()
+        {
+            seg.prev = before.get(i).copied().unwrap_or(seg.pos);
+        }
+
+        // Finally updated last_update_dir to show where we moved
+        self.last_update_dir = self.dir;
+    }
+
+    // Moves the head one cell the way the snake is facing
+    fn step(&mut self, food: Option<&Food>, walls: bool) {
+        // With walls up the snake stops dead at the edge instead of wrapping around
+        if walls && GridPosition::move_wraps(self.head.pos, self.dir) {
+            self.ate = Some(Ate::Wall);
+            return;
         }
 
         let new_head_pos = GridPosition::new_from_move(self.head.pos, self.dir);
         let new_head = Segment::new(new_head_pos);
+        self.steps.push((
+            new_head_pos,
+            GridPosition::move_wraps(self.head.pos, self.dir),
+        ));
 
         // Add head to the front of the body, then set it to the new head
         self.body.push_front(self.head);
@@ -213,7 +602,7 @@ impl Snake {
         // Now check if it ate something
         if self.eats_self() {
             self.ate = Some(Ate::Itself);
-        } else if self.eats(food) {
+        } else if food.is_some_and(|food| self.eats(food)) {
             self.ate = Some(Ate::Food);
             self.num_segments += 1;
         } else {
@@ -222,29 +611,70 @@ impl Snake {
 
         // If nothing was eaten, pop the end of the body to make it look like the body moved
         if self.ate.is_none() {
-            self.body.pop_back();
+            if let Some(tail) = self.body.pop_back() {
+                self.vacated.push_back((tail.pos, 0));
+            }
         }
-
-        // Finally updated last_update_dir to show where we moved
-        self.last_update_dir = self.dir;
     }
 
-    fn draw(&self, canvas: &mut graphics::Canvas) {
-        for seg in &self.body {
+    // Goes down before the snake, fading out over TRAIL_TICKS in the tail's color
+    fn draw_trail(&self, canvas: &mut graphics::Canvas, colors: &BoardColors) {
+        for &(pos, age) in &self.vacated {
+            let fade = 1.0 - age as f32 / TRAIL_TICKS as f32;
+            let color = Color::new(colors.tail.r, colors.tail.g, colors.tail.b, 0.5 * fade);
             canvas.draw(
                 &graphics::Quad,
                 graphics::DrawParam::new()
-                    .dest_rect(seg.pos.into())
-                    .color([0.3, 0.3, 0.0, 1.0]),
+                    .dest_rect(grow_rect(pos.into(), -SEGMENT_GAP))
+                    .color(color),
             );
         }
+    }
+
+    // Progress is how far through the current tick it is, from 0 to 1. Anything but 1
+    // draws the snake sliding from its last cells into these ones. Hidden leaves that many
+    // segments off the tail end, head and all if it's long enough
+    fn draw(
+        &self,
+        canvas: &mut graphics::Canvas,
+        ctx: &Context,
+        colors: &BoardColors,
+        sprites: &Sprites,
+        progress: f32,
+        hidden: usize,
+    ) -> GameResult {
+        if hidden > self.body.len() {
+            return Ok(());
+        }
+        let shown = self.body.len() - hidden;
+        let chain: Vec<GridPosition> = std::iter::once(self.head.pos)
+            .chain(self.body.iter().take(shown).map(|seg| seg.pos))
+            .collect();
+        let last = self.body.len().saturating_sub(1).max(1) as f32;
+        for (i, seg) in self.body.iter().take(shown).enumerate() {
+            let color = colors.body_at(i as f32 / last);
+            let joins = joined_sides(&chain, i + 1);
+            draw_body(
+                canvas,
+                ctx,
+                seg.cell(progress),
+                &joins,
+                color,
+                colors,
+                sprites,
+            )?;
+        }
 
-        canvas.draw(
-            &graphics::Quad,
-            graphics::DrawParam::new()
-                .dest_rect(self.head.pos.into())
-                .color([1.0, 0.5, 0.0, 1.0]),
-        );
+        let head = self.head.cell(progress);
+        let joins = joined_sides(&chain, 0);
+        draw_head(canvas, ctx, head, &joins, self.dir, colors, sprites)?;
+        // A head sprite brings its own face
+        if sprites.head.is_none() {
+            let dead = matches!(self.ate, Some(Ate::Itself | Ate::Wall));
+            draw_eyes(canvas, ctx, head, self.dir, colors.eyes, dead)?;
+        }
+
+        Ok(())
     }
 
     fn get_food_space(&self, rng: &mut Rand32) -> GridPosition {
@@ -265,27 +695,284 @@ impl Snake {
     }
 }
 
+// Everything the end screens show about a finished run
+#[derive(Debug, Clone, Default)]
+struct RunSummary {
+    length: u32,
+    food_eaten: u32,
+    // Game time rather than ticks, since the tick rate depends on the speed setting and the finale
+    time: std::time::Duration,
+    score: u32,
+    // The best score from before this run, so there's something to compare against
+    previous_best: Option<u32>,
+    previous_best_name: Option<String>,
+    // Same idea for the fastest win, only filled in when the run was won
+    previous_best_time: Option<std::time::Duration>,
+}
+
+impl RunSummary {
+    // 1 to 5 stars depending on how quickly the board was filled
+    fn stars(&self) -> usize {
+        1 + RATING_THRESHOLDS
+            .iter()
+            .filter(|&&threshold| self.time <= threshold)
+            .count()
+    }
+}
+
+// The screens that are open, bottom to top. Only the one on top gets updated and sees input,
+// and the bottom one never gets popped
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct ScreenStack(Vec<u8>);
+
+impl ScreenStack {
+    fn new(bottom: u8) -> Self {
+        ScreenStack(vec![bottom])
+    }
+
+    fn top(&self) -> u8 {
+        self.0[self.0.len() - 1]
+    }
+
+    // What an overlay like the quit prompt is drawn over. The bottom screen has nothing
+    // under it, so it's just drawn over itself
+    fn below(&self) -> u8 {
+        self.0[self.0.len().saturating_sub(2)]
+    }
+
+    fn push(&mut self, screen: u8) {
+        self.0.push(screen);
+    }
+
+    fn pop(&mut self) {
+        if self.0.len() > 1 {
+            self.0.pop();
+        }
+    }
+
+    // Swaps the top screen for another. With only the bottom one open, the new one goes
+    // on top of it instead
+    fn replace(&mut self, screen: u8) {
+        self.pop();
+        self.push(screen);
+    }
+
+    // The title, with anything that couldn't be loaded listed over it
+    fn after_loading(missing: &[&str]) -> Self {
+        let mut screens = ScreenStack::new(TITLE_SCREEN);
+        if !missing.is_empty() {
+            screens.push(LOAD_ERROR);
+        }
+        screens
+    }
+
+    // Closes everything down to the bottom screen
+    fn clear(&mut self) {
+        self.0.truncate(1);
+    }
+}
+
+// Fading out of one screen and into the next. The first half darkens the old screen,
+// the second half brightens the new one
+struct Fade {
+    from: u8,
+    started: std::time::Duration,
+}
+
+impl Fade {
+    // Which screen to draw right now and how dark to make it
+    fn frame(&self, now: std::time::Duration, to: u8) -> (u8, f32) {
+        let progress = (now - self.started).as_secs_f32() / FADE_LENGTH.as_secs_f32();
+        if progress < 0.5 {
+            (self.from, progress * 2.0)
+        } else {
+            (to, (2.0 - progress * 2.0).max(0.0))
+        }
+    }
+}
+
+// Overlays (pause, prompts) and the countdown running into the game already
+// show the same board, so fading those would just be a flicker
+fn fades_between(from: u8, to: u8) -> bool {
+    let overlays = [PAUSE_MENU, RESTART_PROMPT, QUIT_PROMPT];
+    let starting_game = from == COUNTDOWN && to == GAMEPLAY;
+    let dying = from == GAMEPLAY && to == DYING;
+    !(overlays.contains(&from) || overlays.contains(&to) || starting_game || dying)
+}
+
+// Everything that belongs to the run as a whole rather than the board it's played on
+#[derive(Debug, Default)]
+struct Run {
+    score: ScoreKeeper,
+    // How many game ticks the run has lasted
+    ticks: u32,
+    // How long those ticks add up to at the rates they ran at. Only moves when the game ticks,
+    // so pausing stops it
+    time: std::time::Duration,
+    // Set once the run reaches FINALE_LENGTH so the finale only kicks in once
+    finale: bool,
+    // Speedrun splits for the run, and how much longer the latest one stays on the HUD
+    splits: SplitTracker,
+    split_results: Vec<SplitResult>,
+    split_display_left: std::time::Duration,
+    // A responsive turn is waiting to be made before the next frame, and the regular tick
+    // it stood in for still has to be skipped
+    early_tick: bool,
+    skip_tick: bool,
+}
+
+impl Run {
+    // A turn waiting on an early tick waits for the first regular one after the pause
+    // instead. Whatever's queued on the snake stays as it was
+    fn pause(&mut self) {
+        self.early_tick = false;
+    }
+
+    // Whether a responsive turn wants its tick this frame
+    fn take_early_tick(&mut self) -> bool {
+        if self.early_tick {
+            self.early_tick = false;
+            self.skip_tick = true;
+            return true;
+        }
+        false
+    }
+
+    // Whether the regular tick goes ahead, or was already taken early
+    fn take_regular_tick(&mut self) -> bool {
+        !std::mem::take(&mut self.skip_tick)
+    }
+}
+
 struct GameState {
     snake: Snake,
     food: Food,
     rng: Rand32,
-    game_state: u8,
+    // Loading finishes by starting this over with just the title screen, which stays at the
+    // bottom from then on
+    screens: ScreenStack,
+    // How many of AudioManager::LOAD_STEPS the loading screen has got through
+    loaded: usize,
+    // What the load error screen lists
+    missing_sounds: Vec<&'static str>,
+    // When + or - last changed the music volume, so it can be shown for a moment
+    volume_changed: Option<std::time::Duration>,
     title_screen: OptionScreen,
     loss_screen: OptionScreen,
     win_screen: OptionScreen,
-    title_music: Source,
-    game_music: Source,
-    win_music: Source,
-    death_sound: Source,
-    loss_music: Source,
-    played_death_sound: bool,
+    audio: AudioManager,
+    settings: Settings,
+    high_scores: HighScores,
+    run: Run,
+    // Only there in rhythm mode, where it decides when the snake moves instead of the
+    // speed's tick rate
+    beat_clock: Option<BeatClock>,
+    // How far the game is through the current tick, from 0 to 1, for smooth movement. Only
+    // moves on during the game itself, so the snake holds still behind the pause menu
+    tick_progress: f32,
+    // Time spent actually playing this session, over every run. The day/night cycle goes by
+    // this, so it waits while the game's paused or sat in a menu
+    play_time: std::time::Duration,
+    // When the snake died, for the death sequence before the loss screen
+    dying_started: std::time::Duration,
+    // When the board started shaking, and how far it's knocked this frame
+    shake_started: Option<std::time::Duration>,
+    shake_offset: Point2<f32>,
+    // The head movements of this run, and the best run so far for the current preset
+    recording: GhostRecording,
+    best_recording: Option<GhostRecording>,
+    ghost: Option<Ghost>,
+    // What the end screens show, filled in the moment the run ends
+    summary: RunSummary,
+    // The "+N" floating up off each food that's been eaten
+    popups: Popups,
+    // Where the head ran into the body, if that's how the run ended
+    collision: Option<GridPosition>,
+    // The last couple of seconds of the run, and the slow motion playback of them
+    // that the loss screen shows before its buttons
+    replay_buffer: ReplayBuffer,
+    replay: Option<Replay>,
+    // The head's last few moves, drawn over the loss screen once the replay's done
+    head_trail: HeadTrail,
+    // How many times the head has been in each cell this run, for the end screen heatmap
+    visits: [[u32; GRID_SIZE.1 as usize]; GRID_SIZE.0 as usize],
+    show_heatmap: bool,
+    session_stats: SessionStats,
+    achievements: Achievements,
+    // Set when the run that just ended beat the stored best, for the end screen banner
+    new_high_score: bool,
+    pause_menu: OptionScreen,
+    // When the restart prompt was opened, for timing it out
+    restart_prompt_opened: std::time::Duration,
+    countdown_started: std::time::Duration,
+    // Last known cursor position, and the state hover was last worked out for
+    mouse_pos: Point2<f32>,
+    hover_state: u8,
+    settings_screen: SettingsScreen,
+    controls_screen: SettingsScreen,
+    gamepad_screen: SettingsScreen,
+    display_screen: SettingsScreen,
+    // The action waiting on a key press, while the controls screen is listening for one
+    capturing: Option<Action>,
+    // Why the last key pressed while capturing wasn't taken
+    bind_error: Option<BindError>,
+    credits: Credits,
+    name_entry: NameEntry,
+    leaderboard: Leaderboard,
+    // Where the last run placed on the leaderboard, if it made it on at all
+    leaderboard_rank: Option<usize>,
+    // Only set when the leaderboard is being shown right after a run
+    leaderboard_highlight: Option<usize>,
+    // What draw last showed, so it can tell when the state changes and start a fade
+    drawn_state: u8,
+    fade: Option<Fade>,
+    // All the text the player sees, in the language picked in settings
+    strings: Strings,
+    // What the window title was last set to, so it's only touched when it changes
+    window_title: String,
+    // Slithers around behind the title screen. Only exists while that's showing
+    title_snake: Option<TitleSnake>,
+    confetti: Confetti,
+    // Pictures for the snake and food, if resources has any
+    sprites: Sprites,
+    // The darker cells of the board and the lines between cells, built once in white and
+    // tinted by the theme. Both would need building again if the grid could change size
+    checkerboard: graphics::Mesh,
+    grid_lines: graphics::Mesh,
+    // Where the left stick is, and the direction it last sent. A held stick only steers
+    // once, when it moves into a new direction
+    stick: (f32, f32),
+    stick_dir: Option<Direction>,
+    // Where the current swipe started, or where it last turned the snake
+    swipe_from: Option<Point2<f32>>,
+    // Set from the command line, and Live unless a run is being recorded or played back
+    input_mode: InputMode,
+    // Why the last try at starting a playback was refused, shown on the title until one starts
+    playback_error: Option<String>,
+    // Where a click sent the snake, and the steps left to get there
+    auto_path: Option<AutoPath>,
+    // The way a press will turn under the single switch scheme, and how long it's been
+    // showing. Only counts up during play, so a pause doesn't skip it along
+    switch_preview: Direction,
+    switch_preview_shown: std::time::Duration,
+    // The last direction key pressed and when, for spotting a double tap
+    last_dir_press: Option<(Direction, std::time::Duration)>,
+    // Set by a double tap, and used up by the next tick
+    dash_pending: bool,
+}
+
+struct AutoPath {
+    target: GridPosition,
+    steps: VecDeque<Direction>,
 }
 
 impl GameState {
-    pub fn new(ctx: &mut Context) -> Self {
+    pub fn new(ctx: &mut Context, audio_enabled: bool) -> GameResult<Self> {
         let mut seed: [u8; 8] = [0; 8];
         getrandom::getrandom(&mut seed[..]).expect("Could not create RNG seed");
         let mut rng = Rand32::new(u64::from_ne_bytes(seed));
+        // Seeded off to the side so it never uses up numbers from the game's RNG
+        let confetti = Confetti::new(u64::from_ne_bytes(seed).rotate_left(32));
 
         let snake_pos = GridPosition::random(&mut rng, GRID_SIZE.0, GRID_SIZE.1);
         let random_direction = Direction::random_direction(&mut rng);
@@ -293,457 +980,4100 @@ impl GameState {
 
         let food_pos = snake.get_food_space(&mut rng);
 
-        let title_screen = OptionScreen::new("Snake Partially by Me", "Start", "Quit");
-        let loss_screen = OptionScreen::new("Game Over", "Try Again?", "Quit");
-        let win_screen = OptionScreen::new("You Won!", "Restart", "Quit");
-
-        let mut title_music =
-            Source::new(ctx, "/snake_jazz.mp3").expect("Could not find snake jazz");
-        title_music.set_repeat(true);
-        let mut game_music =
-            Source::new(ctx, "/megalovania.mp3").expect("Could not find megalovania");
-        game_music.set_repeat(true);
-        game_music.set_volume(0.25);
-        let mut win_music =
-            Source::new(ctx, "/congratulations.mp3").expect("Could not find congratulations");
-        win_music.set_repeat(true);
-        let mut loss_music =
-            Source::new(ctx, "/sad_violin.mp3").expect("Could not find sad violin");
-        loss_music.set_repeat(true);
-        let mut death_sound =
-            Source::new(ctx, "/snake.mp3").expect("Could not find snake snake snaaaaake");
-        death_sound.set_repeat(false);
-
-        GameState {
+        let settings = Settings::load(ctx);
+        let strings = Strings::load(ctx, settings.language);
+        let window_title = strings.get("window.title");
+        ctx.gfx.set_window_title(&window_title);
+
+        let audio = AudioManager::new(u64::from_ne_bytes(seed).rotate_left(16), audio_enabled);
+        let sprites = Sprites::load(ctx)?;
+
+        let title_screen = Self::title_screen(&strings, &settings);
+        let loss_screen = Self::loss_screen(&strings, &settings);
+        let win_screen = Self::win_screen(&strings, &settings);
+        let pause_menu = Self::pause_menu(&strings, &settings);
+        let settings_screen = Self::settings_screen(&strings, &settings);
+        let controls_screen = Self::controls_screen(&strings, &settings);
+        let gamepad_screen = Self::gamepad_screen(&strings, &settings);
+        let display_screen = Self::display_screen(&strings, &settings);
+
+        let best_recording = GhostRecording::load(ctx, settings.scoring);
+        let recording = GhostRecording::new(snake.head.pos);
+
+        let mut state = GameState {
             snake,
             food: Food::new(food_pos),
             rng,
-            game_state: TITLE_SCREEN,
+            screens: ScreenStack::new(LOADING_SCREEN),
+            loaded: 0,
+            missing_sounds: Vec::new(),
+            volume_changed: None,
             title_screen,
             loss_screen,
             win_screen,
-            title_music,
-            game_music,
-            win_music,
-            death_sound,
-            loss_music,
-            played_death_sound: false,
-        }
+            audio,
+            settings,
+            high_scores: HighScores::load(ctx),
+            run: Run::default(),
+            beat_clock: None,
+            tick_progress: 1.0,
+            play_time: std::time::Duration::ZERO,
+            dying_started: std::time::Duration::ZERO,
+            shake_started: None,
+            shake_offset: Point2 { x: 0.0, y: 0.0 },
+            recording,
+            best_recording,
+            ghost: None,
+            summary: RunSummary::default(),
+            new_high_score: false,
+            popups: Popups::default(),
+            collision: None,
+            replay_buffer: ReplayBuffer::default(),
+            replay: None,
+            head_trail: HeadTrail::default(),
+            visits: [[0; GRID_SIZE.1 as usize]; GRID_SIZE.0 as usize],
+            show_heatmap: false,
+            session_stats: SessionStats::default(),
+            achievements: Achievements::load(ctx),
+            pause_menu,
+            restart_prompt_opened: std::time::Duration::ZERO,
+            countdown_started: std::time::Duration::ZERO,
+            mouse_pos: Point2 { x: 0.0, y: 0.0 },
+            hover_state: LOADING_SCREEN,
+            settings_screen,
+            controls_screen,
+            gamepad_screen,
+            display_screen,
+            capturing: None,
+            bind_error: None,
+            credits: Credits::new(),
+            name_entry: NameEntry::new(),
+            leaderboard: Leaderboard::load(ctx),
+            leaderboard_rank: None,
+            leaderboard_highlight: None,
+            drawn_state: LOADING_SCREEN,
+            fade: None,
+            strings,
+            window_title,
+            title_snake: None,
+            confetti,
+            sprites,
+            checkerboard: checkerboard(ctx),
+            grid_lines: grid_lines(ctx),
+            stick: (0.0, 0.0),
+            stick_dir: None,
+            swipe_from: None,
+            input_mode: InputMode::Live,
+            playback_error: None,
+            auto_path: None,
+            switch_preview: Direction::Right,
+            switch_preview_shown: std::time::Duration::ZERO,
+            last_dir_press: None,
+            dash_pending: false,
+        };
+        state.apply_volumes();
+        Ok(state)
     }
 
-    fn draw_gameplay(&mut self, ctx: &mut Context) -> GameResult {
-        if self.title_music.playing() {
-            self.title_music.pause();
-        }
-        if self.death_sound.playing() {
-            self.death_sound.pause();
-        }
-        if self.loss_music.playing() {
-            self.loss_music.pause();
-        }
-        if self.win_music.playing() {
-            self.win_music.pause();
-        }
-        self.played_death_sound = false;
-        if !self.game_music.playing() {
-            self.game_music.play(ctx)?;
-        }
-
-        // First make a clear canvas
-        let mut canvas =
-            graphics::Canvas::from_frame(ctx, graphics::Color::from([0.0, 1.0, 0.0, 1.0]));
+    fn settings_screen(strings: &Strings, settings: &Settings) -> SettingsScreen {
+        SettingsScreen::new(
+            &strings.get("settings.heading"),
+            SETTING_ROWS,
+            true,
+            settings.high_contrast,
+            settings.menu_colors(),
+        )
+    }
 
-        // Then have the snake and food draw themselves
-        self.snake.draw(&mut canvas);
-        self.food.draw(&mut canvas);
+    // No arrows, since picking a row starts listening for a key rather than stepping through values
+    fn controls_screen(strings: &Strings, settings: &Settings) -> SettingsScreen {
+        SettingsScreen::new(
+            &strings.get("controls.heading"),
+            CONTROLS_ROWS,
+            false,
+            settings.high_contrast,
+            settings.menu_colors(),
+        )
+    }
 
-        // "Flush" the draw commands
-        canvas.finish(ctx)?;
+    fn gamepad_screen(strings: &Strings, settings: &Settings) -> SettingsScreen {
+        SettingsScreen::new(
+            &strings.get("gamepad.heading"),
+            GAMEPAD_ROWS,
+            true,
+            settings.high_contrast,
+            settings.menu_colors(),
+        )
+    }
 
-        Ok(())
+    fn display_screen(strings: &Strings, settings: &Settings) -> SettingsScreen {
+        SettingsScreen::new(
+            &strings.get("display.heading"),
+            DISPLAY_ROWS,
+            true,
+            settings.high_contrast,
+            settings.menu_colors(),
+        )
     }
 
-    fn draw_title(&mut self, ctx: &mut Context) -> GameResult {
-        if !self.title_music.playing() {
-            self.title_music.play(ctx)?;
-        }
+    fn title_screen(strings: &Strings, settings: &Settings) -> OptionScreen {
+        let labels = [
+            "title.start",
+            "title.settings",
+            "title.scores",
+            "title.stats",
+            "title.achievements",
+            "title.credits",
+            "title.quit",
+        ]
+        .map(|key| strings.get(key));
+        OptionScreen::new(
+            &strings.get("title.heading"),
+            &labels,
+            ButtonLayout::Horizontal,
+        )
+        .with_selection()
+        .with_colors(settings.menu_colors())
+        .with_high_contrast(settings.high_contrast)
+    }
 
-        let mut canvas =
-            graphics::Canvas::from_frame(ctx, graphics::Color::from([0.0, 0.0, 0.0, 1.0]));
+    fn loss_screen(strings: &Strings, settings: &Settings) -> OptionScreen {
+        OptionScreen::new(
+            &strings.get("loss.heading"),
+            &[strings.get("loss.retry"), strings.get("loss.quit")],
+            ButtonLayout::Horizontal,
+        )
+        .with_selection()
+        .with_colors(settings.menu_colors())
+        .with_high_contrast(settings.high_contrast)
+    }
 
-        self.title_screen.draw(&mut canvas, ctx)?;
+    fn win_screen(strings: &Strings, settings: &Settings) -> OptionScreen {
+        OptionScreen::new(
+            &strings.get("win.heading"),
+            &[strings.get("win.restart"), strings.get("win.quit")],
+            ButtonLayout::Horizontal,
+        )
+        .with_selection()
+        .with_colors(settings.menu_colors())
+        .with_high_contrast(settings.high_contrast)
+    }
 
-        canvas.finish(ctx)?;
+    // Escape backs out of the pause menu instead of quitting, Enter uses the selection
+    fn pause_menu(strings: &Strings, settings: &Settings) -> OptionScreen {
+        OptionScreen::new(
+            &strings.get("pause.heading"),
+            &[
+                strings.get("pause.resume"),
+                strings.get("pause.restart"),
+                strings.get("pause.quit"),
+            ],
+            ButtonLayout::Vertical,
+        )
+        .with_selection()
+        .with_shortcut(PAUSE_RESUME, Some(KeyCode::Escape))
+        .with_shortcut(PAUSE_QUIT, None)
+        .with_colors(settings.menu_colors())
+        .with_high_contrast(settings.high_contrast)
+    }
 
-        Ok(())
+    fn change_language(&mut self, ctx: &Context) {
+        self.strings = Strings::load(ctx, self.settings.language);
+        self.update_window_title(ctx);
+        self.rebuild_menus();
     }
 
-    fn draw_win(&mut self, ctx: &mut Context) -> GameResult {
-        if self.game_music.playing() {
-            self.game_music.pause();
-        }
-        if !self.win_music.playing() {
-            self.win_music.play(ctx)?;
+    // The score goes in the title while a game's going, so it can be seen with the window
+    // in the background. Everywhere else it's just the game's name
+    fn update_window_title(&mut self, ctx: &Context) {
+        let title = match self.screen() {
+            GAMEPLAY | COUNTDOWN => self
+                .strings
+                .format("window.score", &[&self.run.score.score]),
+            PAUSE_MENU | RESTART_PROMPT => self.strings.get("window.paused"),
+            _ => self.strings.get("window.title"),
+        };
+        if title != self.window_title {
+            ctx.gfx.set_window_title(&title);
+            self.window_title = title;
         }
+    }
 
-        let mut canvas =
-            graphics::Canvas::from_frame(ctx, graphics::Color::from([0.0, 0.0, 1.0, 1.0]));
-
-        self.win_screen.draw(&mut canvas, ctx)?;
-
-        canvas.finish(ctx)?;
+    // Rebuilds every screen that baked its text and look in when it was made. The settings
+    // screen keeps its place since that's where these get changed from
+    fn rebuild_menus(&mut self) {
+        self.title_screen = Self::title_screen(&self.strings, &self.settings);
+        self.loss_screen = Self::loss_screen(&self.strings, &self.settings);
+        self.win_screen = Self::win_screen(&self.strings, &self.settings);
+        self.pause_menu = Self::pause_menu(&self.strings, &self.settings);
 
-        Ok(())
+        let selected = self.settings_screen.selected;
+        self.settings_screen = Self::settings_screen(&self.strings, &self.settings);
+        self.settings_screen.selected = selected;
+        self.controls_screen = Self::controls_screen(&self.strings, &self.settings);
+        let selected = self.gamepad_screen.selected;
+        self.gamepad_screen = Self::gamepad_screen(&self.strings, &self.settings);
+        self.gamepad_screen.selected = selected;
+        let selected = self.display_screen.selected;
+        self.display_screen = Self::display_screen(&self.strings, &self.settings);
+        self.display_screen.selected = selected;
+        self.refresh_hover();
     }
 
-    fn draw_loss(&mut self, ctx: &mut Context) -> GameResult {
-        if self.game_music.playing() {
-            self.game_music.pause();
-        }
-        if !self.death_sound.playing() && !self.played_death_sound {
-            self.death_sound.play(ctx)?;
-            self.played_death_sound = true;
-        }
-        if !self.death_sound.playing() && self.played_death_sound {
-            if !self.loss_music.playing() {
-                self.loss_music.play(ctx)?;
-            }
-        }
-
-        let mut canvas =
-            graphics::Canvas::from_frame(ctx, graphics::Color::from([1.0, 0.0, 0.0, 1.0]));
+    fn toggle_high_contrast(&mut self, ctx: &Context) {
+        self.settings.high_contrast = !self.settings.high_contrast;
+        self.settings.save(ctx);
+        self.rebuild_menus();
+    }
 
-        self.loss_screen.draw(&mut canvas, ctx)?;
+    fn toggle_mute(&mut self, ctx: &Context) {
+        self.settings.muted = !self.settings.muted;
+        self.settings.save(ctx);
+        self.apply_volumes();
+    }
 
-        canvas.finish(ctx)?;
+    // The music goes quiet but keeps playing underneath, so nothing that starts a track
+    // when it isn't playing has to know about this, and unmuting picks up where it would be
+    fn toggle_music_mute(&mut self, ctx: &Context) {
+        self.settings.music_muted = !self.settings.music_muted;
+        self.settings.save(ctx);
+        self.apply_volumes();
+    }
 
-        Ok(())
+    // Turning it up or down brings the music back if it was muted, same as the settings row
+    fn step_music_volume(&mut self, ctx: &Context, delta: i32) {
+        self.settings.music_muted = false;
+        self.settings.music_volume = step_volume(self.settings.music_volume, delta);
+        self.settings.save(ctx);
+        self.apply_volumes();
+        self.volume_changed = Some(ctx.time.time_since_start());
     }
 
-    fn reset(&mut self) {
-        let snake_pos = GridPosition::random(&mut self.rng, GRID_SIZE.0, GRID_SIZE.1);
-        let random_direction = Direction::random_direction(&mut self.rng);
-        self.snake = Snake::new(snake_pos, random_direction);
-        self.food = Food::new(GridPosition::random(
-            &mut self.rng,
-            GRID_SIZE.0,
-            GRID_SIZE.1,
-        ));
-        self.game_state = GAMEPLAY;
+    // The mute hotkeys work from anywhere, except where the key is a letter being typed,
+    // the answer to a prompt, the key being picked for a binding, or bound to something else
+    fn hotkey_free(&self, key: KeyCode) -> bool {
+        !matches!(self.screen(), NAME_ENTRY | RESTART_PROMPT | QUIT_PROMPT)
+            && self.capturing.is_none()
+            && self.settings.bindings.action(key).is_none()
     }
-}
 
-impl event::EventHandler<ggez::GameError> for GameState {
-    fn update(&mut self, ctx: &mut Context) -> GameResult {
-        // built in timer that will cycle only when it is time
+    fn apply_volumes(&mut self) {
+        self.audio.apply_volumes(&self.settings);
+    }
 
-        while ctx.time.check_update_time(DESIRED_FPS) {
-            match self.game_state {
-                TITLE_SCREEN => {
-                    if self.title_screen.button1_clicked {
-                        self.game_state = GAMEPLAY;
-                    } else if self.title_screen.button2_clicked {
-                        std::process::exit(0);
+    // What each row of the settings screen currently says
+    fn setting_labels(&self) -> Vec<String> {
+        let (settings, strings) = (&self.settings, &self.strings);
+        (0..SETTING_ROWS)
+            .map(|row| match row {
+                SETTING_REDUCE_MOTION => {
+                    if settings.reduce_motion {
+                        strings.get("settings.reduce_motion_on")
+                    } else {
+                        strings.get("settings.reduce_motion_off")
                     }
-                    self.title_screen.button1_clicked = false;
-                    self.title_screen.button2_clicked = false;
-                }
-                GAME_LOSS => {
-                    if self.loss_screen.button1_clicked {
-                        self.reset();
-                    } else if self.loss_screen.button2_clicked {
-                        std::process::exit(0);
+                }
+                SETTING_MUSIC_VOLUME | SETTING_SFX_VOLUME | SETTING_TICK_SOUND
+                    if !self.audio.enabled() =>
+                {
+                    strings.get("settings.audio_disabled")
+                }
+                SETTING_MUSIC_VOLUME if settings.music_muted => strings.get("settings.music_muted"),
+                SETTING_MUSIC_VOLUME => strings.format("settings.music", &[&settings.music_volume]),
+                SETTING_SFX_VOLUME => strings.format("settings.sfx", &[&settings.sfx_volume]),
+                SETTING_TICK_SOUND => {
+                    if settings.tick_sound {
+                        strings.get("settings.tick_sound_on")
+                    } else {
+                        strings.get("settings.tick_sound_off")
                     }
-                    self.loss_screen.button1_clicked = false;
-                    self.loss_screen.button2_clicked = false;
-                }
-                GAME_WIN => {
-                    if self.win_screen.button1_clicked {
-                        self.reset();
-                    } else if self.win_screen.button2_clicked {
-                        std::process::exit(0);
+                }
+                SETTING_SPEED => strings.format(
+                    "settings.speed",
+                    &[&strings.get(&format!("speed.{}", settings.speed.key()))],
+                ),
+                SETTING_WALLS => {
+                    if settings.walls {
+                        strings.get("settings.walls_on")
+                    } else {
+                        strings.get("settings.walls_off")
                     }
-                    self.win_screen.button1_clicked = false;
-                    self.win_screen.button2_clicked = false;
                 }
-                GAMEPLAY => {
-                    // First update the snake
-                    self.snake.update(&self.food);
-                    // Check if the snake ate something
-                    if let Some(ate) = self.snake.ate {
-                        match ate {
-                            Ate::Food => {
-                                if self.snake.num_segments == TARGET_LENGTH {
-                                    self.game_state = GAME_WIN;
-                                } else {
-                                    self.food.pos = self.snake.get_food_space(&mut self.rng);
-                                }
-                            }
-                            Ate::Itself => {
-                                self.game_state = GAME_LOSS;
-                            }
-                        }
+                SETTING_THEME => strings.format(
+                    "settings.theme",
+                    &[&strings.get(&format!("theme.{}", settings.theme.key()))],
+                ),
+                SETTING_FADES => {
+                    if settings.fades {
+                        strings.get("settings.fades_on")
+                    } else {
+                        strings.get("settings.fades_off")
                     }
                 }
-                _ => (),
-            }
-        }
+                SETTING_LANGUAGE => {
+                    strings.format("settings.language", &[&settings.language.name()])
+                }
+                SETTING_PALETTE => strings.format(
+                    "settings.palette",
+                    &[&strings.get(&format!("palette.{}", settings.palette.key()))],
+                ),
+                SETTING_DPAD => strings.format(
+                    "settings.dpad",
+                    &[&strings.get(&format!("dpad.{}", settings.dpad.key()))],
+                ),
+                SETTING_HIGH_CONTRAST => {
+                    if settings.high_contrast {
+                        strings.get("settings.high_contrast_on")
+                    } else {
+                        strings.get("settings.high_contrast_off")
+                    }
+                }
+                SETTING_RESPONSIVE_TURNS => {
+                    if settings.responsive_turns {
+                        strings.get("settings.responsive_turns_on")
+                    } else {
+                        strings.get("settings.responsive_turns_off")
+                    }
+                }
+                SETTING_MOUSE_MODE => strings.format(
+                    "settings.mouse_mode",
+                    &[&strings.get(&format!("mouse.{}", settings.mouse_mode.key()))],
+                ),
+                SETTING_CONTROL_SCHEME => strings.format(
+                    "settings.control_scheme",
+                    &[&strings.get(&format!("scheme.{}", settings.control_scheme.key()))],
+                ),
+                SETTING_CONTROLS => strings.get("settings.controls"),
+                SETTING_GAMEPAD => strings.get("settings.gamepad"),
+                SETTING_DISPLAY => strings.get("settings.display"),
+                _ => strings.get("settings.back"),
+            })
+            .collect()
+    }
 
-        Ok(())
+    fn gamepad_labels(&self) -> Vec<String> {
+        let (settings, strings) = (&self.settings, &self.strings);
+        let on_off = |on: bool| strings.get(if on { "gamepad.on" } else { "gamepad.off" });
+        (0..GAMEPAD_ROWS)
+            .map(|row| match row {
+                GAMEPAD_DEADZONE => strings.format("gamepad.deadzone", &[&settings.stick_deadzone]),
+                GAMEPAD_SWAP_AXES => {
+                    strings.format("gamepad.swap_axes", &[&on_off(settings.stick_swap_axes)])
+                }
+                GAMEPAD_INVERT_X => {
+                    strings.format("gamepad.invert_x", &[&on_off(settings.stick_invert_x)])
+                }
+                GAMEPAD_INVERT_Y => {
+                    strings.format("gamepad.invert_y", &[&on_off(settings.stick_invert_y)])
+                }
+                _ => strings.get("settings.back"),
+            })
+            .collect()
     }
 
-    fn draw(&mut self, ctx: &mut Context) -> GameResult {
-        match self.game_state {
-            GAMEPLAY => self.draw_gameplay(ctx)?,
-            TITLE_SCREEN => self.draw_title(ctx)?,
-            GAME_LOSS => self.draw_loss(ctx)?,
-            GAME_WIN => self.draw_win(ctx)?,
-            _ => (),
+    fn adjust_gamepad_setting(&mut self, ctx: &Context, row: usize, delta: i32) {
+        let settings = &mut self.settings;
+        match row {
+            GAMEPAD_DEADZONE => settings.step_stick_deadzone(delta),
+            GAMEPAD_SWAP_AXES => settings.stick_swap_axes = !settings.stick_swap_axes,
+            GAMEPAD_INVERT_X => settings.stick_invert_x = !settings.stick_invert_x,
+            GAMEPAD_INVERT_Y => settings.stick_invert_y = !settings.stick_invert_y,
+            _ => {
+                self.pop_screen();
+                return;
+            }
         }
-
-        // Yield the thread until the next update and return success
-        ggez::timer::yield_now();
-        Ok(())
+        self.settings.save(ctx);
     }
 
-    // This fires when a key gets pressed
-    fn key_down_event(
-        &mut self,
-        _ctx: &mut Context,
-        input: KeyInput,
-        _repeated: bool,
-    ) -> Result<(), ggez::GameError> {
-        match self.game_state {
-            GAMEPLAY => {
-                // Try to turn the keycode into a direction
-                if let Some(dir) = input.keycode.and_then(Direction::from_keycode) {
-                    // If success, check if a new direction has been set
-                    // and make sure it's different from snake.dir
-                    // This is like buffering a new direction before the next one has been made
-                    if self.snake.dir != self.snake.last_update_dir
-                        && dir.inverse() != self.snake.dir
-                    {
-                        self.snake.next_dir = Some(dir);
-                    } else if dir.inverse() != self.snake.last_update_dir {
-                        // If no new direction has been set and it's not the inverse direction
-                        // of the previous move, set the snake dir to the new one pressed
-                        self.snake.dir = dir;
-                    }
-                }
-            }
-            TITLE_SCREEN => match input.keycode {
-                Some(KeyCode::Return) => {
-                    self.title_screen.button1_clicked = true;
-                }
-                Some(KeyCode::Escape) => {
-                    self.title_screen.button2_clicked = true;
+    fn display_labels(&self) -> Vec<String> {
+        let (settings, strings) = (&self.settings, &self.strings);
+        let on_off = |on: bool| strings.get(if on { "display.on" } else { "display.off" });
+        (0..DISPLAY_ROWS)
+            .map(|row| match row {
+                DISPLAY_SMOOTH_MOVEMENT => strings.format(
+                    "display.smooth_movement",
+                    &[&on_off(settings.smooth_movement)],
+                ),
+                DISPLAY_GRID_LINES => {
+                    strings.format("display.grid_lines", &[&on_off(settings.grid_lines)])
                 }
-                _ => (),
-            },
-            GAME_LOSS => match input.keycode {
-                Some(KeyCode::Return) => {
-                    self.loss_screen.button1_clicked = true;
+                DISPLAY_FADING_TRAIL => {
+                    strings.format("display.fading_trail", &[&on_off(settings.fading_trail)])
                 }
-                Some(KeyCode::Escape) => {
-                    self.loss_screen.button2_clicked = true;
+                DISPLAY_DAY_NIGHT => {
+                    strings.format("display.day_night", &[&on_off(settings.day_night)])
                 }
-                _ => (),
-            },
-            GAME_WIN => match input.keycode {
-                Some(KeyCode::Return) => {
-                    self.win_screen.button1_clicked = true;
+                _ => strings.get("settings.back"),
+            })
+            .collect()
+    }
+
+    fn adjust_display_setting(&mut self, ctx: &Context, row: usize, _delta: i32) {
+        let settings = &mut self.settings;
+        match row {
+            DISPLAY_SMOOTH_MOVEMENT => settings.smooth_movement = !settings.smooth_movement,
+            DISPLAY_GRID_LINES => settings.grid_lines = !settings.grid_lines,
+            DISPLAY_FADING_TRAIL => settings.fading_trail = !settings.fading_trail,
+            DISPLAY_DAY_NIGHT => settings.day_night = !settings.day_night,
+            _ => {
+                self.pop_screen();
+                return;
+            }
+        }
+        self.settings.save(ctx);
+    }
+
+    // Settings and its gamepad and display screens all step their rows with Left/Right
+    fn stepper_screen(&self) -> Option<&SettingsScreen> {
+        match self.screen() {
+            SETTINGS_SCREEN => Some(&self.settings_screen),
+            GAMEPAD_SCREEN => Some(&self.gamepad_screen),
+            DISPLAY_SCREEN => Some(&self.display_screen),
+            _ => None,
+        }
+    }
+
+    // Whichever button or row the keyboard is on in the current menu
+    fn menu_selection(&mut self) -> Option<usize> {
+        if self.screen() == CONTROLS_SCREEN {
+            return Some(self.controls_screen.selected);
+        }
+        if let Some(screen) = self.stepper_screen() {
+            return Some(screen.selected);
+        }
+        self.option_screen_mut().and_then(|screen| screen.selected)
+    }
+
+    fn stepper_screen_mut(&mut self) -> Option<&mut SettingsScreen> {
+        match self.screen() {
+            SETTINGS_SCREEN => Some(&mut self.settings_screen),
+            GAMEPAD_SCREEN => Some(&mut self.gamepad_screen),
+            DISPLAY_SCREEN => Some(&mut self.display_screen),
+            _ => None,
+        }
+    }
+
+    fn step_row(&mut self, ctx: &Context, row: usize, delta: i32) {
+        match self.screen() {
+            GAMEPAD_SCREEN => self.adjust_gamepad_setting(ctx, row, delta),
+            DISPLAY_SCREEN => self.adjust_display_setting(ctx, row, delta),
+            _ => self.adjust_setting(ctx, row, delta),
+        }
+    }
+
+    fn action_name(&self, action: Action) -> String {
+        self.strings.get(&format!("action.{}", action.key()))
+    }
+
+    fn control_labels(&self) -> Vec<String> {
+        let mut labels: Vec<String> = Action::ALL
+            .into_iter()
+            .map(|action| {
+                if self.capturing == Some(action) {
+                    self.strings
+                        .format("controls.waiting", &[&self.action_name(action)])
+                } else {
+                    let key = bindings::key_name(self.settings.bindings.get(action)).unwrap_or("?");
+                    self.strings
+                        .format("controls.binding", &[&self.action_name(action), &key])
+                }
+            })
+            .collect();
+        labels.push(if self.settings.vim_keys {
+            self.strings.get("controls.vim_keys_on")
+        } else {
+            self.strings.get("controls.vim_keys_off")
+        });
+        labels.push(if self.settings.dash {
+            self.strings.get("controls.dash_on")
+        } else {
+            self.strings.get("controls.dash_off")
+        });
+        labels.push(self.strings.get("controls.reset"));
+        labels.push(self.strings.get("settings.back"));
+        labels
+    }
+
+    fn open_controls(&mut self) {
+        self.controls_screen.selected = 0;
+        self.capturing = None;
+        self.bind_error = None;
+        self.push_screen(CONTROLS_SCREEN);
+    }
+
+    fn activate_control(&mut self, ctx: &Context, row: usize) {
+        self.bind_error = None;
+        match row {
+            CONTROLS_VIM_KEYS => {
+                self.settings.vim_keys = !self.settings.vim_keys;
+                self.settings.save(ctx);
+            }
+            CONTROLS_DASH => {
+                self.settings.dash = !self.settings.dash;
+                self.settings.save(ctx);
+            }
+            CONTROLS_RESET => {
+                self.settings.bindings = Default::default();
+                self.settings.save(ctx);
+            }
+            CONTROLS_BACK => self.pop_screen(),
+            _ => self.capturing = Some(Action::ALL[row]),
+        }
+    }
+
+    // A key that can't be used keeps it listening, with the reason shown underneath
+    fn capture_key(&mut self, ctx: &Context, action: Action, key: KeyCode) {
+        if key == KeyCode::Escape {
+            self.capturing = None;
+            self.bind_error = None;
+            return;
+        }
+        match self.settings.bindings.bind(action, key) {
+            Ok(()) => {
+                self.capturing = None;
+                self.bind_error = None;
+                self.settings.save(ctx);
+            }
+            Err(error) => self.bind_error = Some(error),
+        }
+    }
+
+    // The confirm binding works like Enter anywhere there's a menu
+    fn menu_key(&self, key: KeyCode) -> KeyCode {
+        translate_menu_key(&self.settings.bindings, key)
+    }
+
+    // Changes a setting and applies it right away, so volume changes can be heard immediately
+    fn adjust_setting(&mut self, ctx: &Context, row: usize, delta: i32) {
+        match row {
+            // Nothing to hear with --no-audio, so there's nothing to change
+            SETTING_MUSIC_VOLUME | SETTING_SFX_VOLUME | SETTING_TICK_SOUND
+                if !self.audio.enabled() =>
+            {
+                return
+            }
+            // Turning the music up or down brings it back, so the change can be heard
+            SETTING_MUSIC_VOLUME if self.settings.music_muted => self.settings.music_muted = false,
+            SETTING_MUSIC_VOLUME => {
+                self.settings.music_volume = step_volume(self.settings.music_volume, delta)
+            }
+            SETTING_SFX_VOLUME => {
+                self.settings.sfx_volume = step_volume(self.settings.sfx_volume, delta)
+            }
+            SETTING_TICK_SOUND => self.settings.tick_sound = !self.settings.tick_sound,
+            SETTING_SPEED => self.settings.speed = self.settings.speed.step(delta),
+            SETTING_WALLS => self.settings.walls = !self.settings.walls,
+            SETTING_THEME => {
+                self.settings.theme = self.settings.theme.step(delta);
+                // The menus take their colors when they're built
+                self.rebuild_menus();
+            }
+            SETTING_FADES => self.settings.fades = !self.settings.fades,
+            SETTING_REDUCE_MOTION => self.settings.reduce_motion = !self.settings.reduce_motion,
+            SETTING_LANGUAGE => {
+                self.settings.language = self.settings.language.step(delta);
+                self.change_language(ctx);
+            }
+            SETTING_PALETTE => self.settings.palette = self.settings.palette.step(delta),
+            SETTING_DPAD => self.settings.dpad = self.settings.dpad.step(delta),
+            SETTING_RESPONSIVE_TURNS => {
+                self.settings.responsive_turns = !self.settings.responsive_turns
+            }
+            SETTING_MOUSE_MODE => {
+                self.settings.mouse_mode = self.settings.mouse_mode.step(delta);
+                self.auto_path = None;
+            }
+            SETTING_CONTROL_SCHEME => {
+                self.settings.control_scheme = self.settings.control_scheme.step(delta);
+                // Waiting for the right way to come round takes time, so it starts out slow.
+                // Speed is still there to turn back up
+                if self.settings.control_scheme == ControlScheme::SingleSwitch {
+                    self.settings.speed = Speed::Slow;
+                }
+            }
+            SETTING_HIGH_CONTRAST => {
+                // Saves for itself, since the hotkey uses it too
+                self.toggle_high_contrast(ctx);
+                return;
+            }
+            SETTING_CONTROLS => {
+                self.open_controls();
+                return;
+            }
+            SETTING_GAMEPAD => {
+                self.gamepad_screen.selected = 0;
+                self.push_screen(GAMEPAD_SCREEN);
+                return;
+            }
+            SETTING_DISPLAY => {
+                self.display_screen.selected = 0;
+                self.push_screen(DISPLAY_SCREEN);
+                return;
+            }
+            _ => {
+                self.pop_screen();
+                return;
+            }
+        }
+        self.apply_volumes();
+        self.settings.save(ctx);
+    }
+
+    // Which screen isn't always the top of the stack, since a fade draws the old one for a moment
+    fn draw_screen(&mut self, ctx: &mut Context, screen: u8) -> GameResult {
+        match screen {
+            GAMEPLAY => self.draw_gameplay(ctx)?,
+            TITLE_SCREEN => self.draw_title(ctx)?,
+            GAME_LOSS => self.draw_loss(ctx)?,
+            GAME_WIN => self.draw_win(ctx)?,
+            PAUSE_MENU => self.draw_pause(ctx)?,
+            RESTART_PROMPT => self.draw_restart_prompt(ctx)?,
+            SETTINGS_SCREEN => self.draw_settings(ctx)?,
+            CONTROLS_SCREEN => self.draw_controls(ctx)?,
+            GAMEPAD_SCREEN => self.draw_gamepad_settings(ctx)?,
+            DISPLAY_SCREEN => self.draw_display_settings(ctx)?,
+            QUIT_PROMPT => self.draw_quit_prompt(ctx)?,
+            CREDITS_SCREEN => self.draw_credits(ctx)?,
+            STATS_SCREEN => self.draw_stats(ctx)?,
+            ACHIEVEMENTS_SCREEN => self.draw_achievements(ctx)?,
+            NAME_ENTRY => self.draw_name_entry(ctx)?,
+            COUNTDOWN => self.draw_countdown(ctx)?,
+            LEADERBOARD => self.draw_leaderboard(ctx)?,
+            LOADING_SCREEN => self.draw_loading(ctx)?,
+            LOAD_ERROR => self.draw_load_error(ctx)?,
+            DYING => self.draw_dying(ctx)?,
+            _ => (),
+        }
+
+        Ok(())
+    }
+
+    // Just the word and a bar filling up, since nothing else is ready yet
+    fn draw_loading(&mut self, ctx: &mut Context) -> GameResult {
+        let colors = self.settings.menu_colors();
+        let mut canvas = graphics::Canvas::from_frame(ctx, colors.background);
+
+        let mut loading = Text::new(self.strings.get("loading"));
+        loading.set_scale(32.0);
+        draw_centered_text(
+            &mut canvas,
+            ctx,
+            &loading,
+            SCREEN_SIZE.1 / 2.0 - 40.0,
+            colors.text,
+        )?;
+
+        let bar = Rect::new(
+            SCREEN_SIZE.0 / 4.0,
+            SCREEN_SIZE.1 / 2.0 + 10.0,
+            SCREEN_SIZE.0 / 2.0,
+            12.0,
+        );
+        let progress = self.loaded as f32 / AudioManager::LOAD_STEPS as f32;
+        canvas.draw(
+            &graphics::Quad,
+            graphics::DrawParam::new().dest_rect(bar).color(Color {
+                a: 0.2,
+                ..colors.text
+            }),
+        );
+        canvas.draw(
+            &graphics::Quad,
+            graphics::DrawParam::new()
+                .dest_rect(Rect::new(bar.x, bar.y, bar.w * progress, bar.h))
+                .color(colors.text),
+        );
+
+        canvas.finish(ctx)?;
+
+        Ok(())
+    }
+
+    // A piece of loading each frame. Nothing that goes wrong here can stop the game, since
+    // a sound that won't load is just left silent, so it always ends up at the title. The
+    // loading screen is gone for good after that, so the title takes the bottom of the stack
+    fn load_next(&mut self, ctx: &Context) {
+        self.audio.load_step(ctx, self.loaded);
+        self.loaded += 1;
+        if self.loaded == AudioManager::LOAD_STEPS {
+            self.apply_volumes();
+            self.missing_sounds = self.audio.missing();
+            self.screens = ScreenStack::after_loading(&self.missing_sounds);
+        }
+    }
+
+    // The title's underneath, but it stays quiet until this is out of the way
+    fn draw_load_error(&mut self, ctx: &mut Context) -> GameResult {
+        let colors = self.settings.menu_colors();
+        let mut canvas = graphics::Canvas::from_frame(ctx, colors.background);
+
+        let mut heading = Text::new(self.strings.get("load_error.heading"));
+        heading.set_scale(32.0);
+        draw_centered_text(&mut canvas, ctx, &heading, 60.0, colors.warning)?;
+
+        for (i, name) in self.missing_sounds.iter().enumerate() {
+            let mut line = Text::new(name.trim_start_matches('/'));
+            line.set_scale(20.0);
+            draw_centered_text(
+                &mut canvas,
+                ctx,
+                &line,
+                130.0 + i as f32 * 26.0,
+                colors.dim_text,
+            )?;
+        }
+
+        let mut hint = Text::new(self.strings.get("load_error.hint"));
+        hint.set_scale(16.0);
+        draw_centered_text(&mut canvas, ctx, &hint, SCREEN_SIZE.1 - 32.0, colors.text)?;
+
+        canvas.finish(ctx)?;
+
+        Ok(())
+    }
+
+    // The title music carries on underneath
+    fn draw_credits(&mut self, ctx: &mut Context) -> GameResult {
+        let mut canvas = graphics::Canvas::from_frame(ctx, self.settings.menu_colors().background);
+
+        self.credits
+            .draw(&mut canvas, ctx, &self.strings, self.settings.menu_colors())?;
+
+        canvas.finish(ctx)?;
+
+        Ok(())
+    }
+
+    fn draw_stats(&mut self, ctx: &mut Context) -> GameResult {
+        let mut canvas = graphics::Canvas::from_frame(ctx, self.settings.menu_colors().background);
+
+        self.session_stats
+            .draw(&mut canvas, ctx, &self.strings, self.settings.menu_colors())?;
+
+        canvas.finish(ctx)?;
+
+        Ok(())
+    }
+
+    fn draw_achievements(&mut self, ctx: &mut Context) -> GameResult {
+        let mut canvas = graphics::Canvas::from_frame(ctx, self.settings.menu_colors().background);
+
+        self.achievements.draw_list(
+            &mut canvas,
+            ctx,
+            &self.strings,
+            self.settings.menu_colors(),
+        )?;
+
+        canvas.finish(ctx)?;
+
+        Ok(())
+    }
+
+    fn draw_settings(&mut self, ctx: &mut Context) -> GameResult {
+        let colors = self.settings.menu_colors();
+        let mut canvas = graphics::Canvas::from_frame(ctx, colors.background);
+
+        let labels = self.setting_labels();
+        self.settings_screen.draw(&mut canvas, ctx, &labels)?;
+
+        // The volumes still change while the device is gone, they just can't be heard yet
+        if !self.audio.available() {
+            let mut note = Text::new(self.strings.get("settings.audio_unavailable"));
+            note.set_scale(18.0);
+            note.fragments_mut()[0].color = Some(colors.warning);
+            draw_centered_text(&mut canvas, ctx, &note, 80.0, colors.text)?;
+        }
+
+        canvas.finish(ctx)?;
+
+        Ok(())
+    }
+
+    fn draw_display_settings(&mut self, ctx: &mut Context) -> GameResult {
+        let mut canvas = graphics::Canvas::from_frame(ctx, self.settings.menu_colors().background);
+
+        let labels = self.display_labels();
+        self.display_screen.draw(&mut canvas, ctx, &labels)?;
+
+        canvas.finish(ctx)?;
+
+        Ok(())
+    }
+
+    fn draw_gamepad_settings(&mut self, ctx: &mut Context) -> GameResult {
+        let mut canvas = graphics::Canvas::from_frame(ctx, self.settings.menu_colors().background);
+
+        let labels = self.gamepad_labels();
+        self.gamepad_screen.draw(&mut canvas, ctx, &labels)?;
+        self.draw_stick_view(&mut canvas, ctx)?;
+
+        canvas.finish(ctx)?;
+
+        Ok(())
+    }
+
+    // The stick's whole range as a circle, the deadzone inside it, and a dot for where the
+    // stick is right now after swapping and inverting. The dot turns yellow once it's out
+    // of the deadzone, which is when it'll steer
+    fn draw_stick_view(&self, canvas: &mut graphics::Canvas, ctx: &Context) -> GameResult {
+        let colors = self.settings.menu_colors();
+        let center = Point2 {
+            x: SCREEN_SIZE.0 / 2.0,
+            y: self.gamepad_screen.bottom() + 40.0 + STICK_VIEW_RADIUS,
+        };
+        let deadzone = self.settings.stick_deadzone as f32 / 100.0;
+
+        let range = graphics::Mesh::new_circle(
+            ctx,
+            graphics::DrawMode::stroke(2.0),
+            center,
+            STICK_VIEW_RADIUS,
+            0.5,
+            colors.text,
+        )?;
+        canvas.draw(&range, graphics::DrawParam::new());
+        let dead = graphics::Mesh::new_circle(
+            ctx,
+            graphics::DrawMode::fill(),
+            center,
+            STICK_VIEW_RADIUS * deadzone,
+            0.5,
+            Color {
+                a: 0.2,
+                ..colors.text
+            },
+        )?;
+        canvas.draw(&dead, graphics::DrawParam::new());
+
+        // Up is positive on the stick but down on the screen
+        let (x, y) = self.settings.stick(self.stick);
+        let color = if x.hypot(y) < deadzone {
+            colors.text
+        } else {
+            colors.highlight
+        };
+        let dot = graphics::Mesh::new_circle(
+            ctx,
+            graphics::DrawMode::fill(),
+            Point2 {
+                x: center.x + x.clamp(-1.0, 1.0) * STICK_VIEW_RADIUS,
+                y: center.y - y.clamp(-1.0, 1.0) * STICK_VIEW_RADIUS,
+            },
+            6.0,
+            0.5,
+            color,
+        )?;
+        canvas.draw(&dot, graphics::DrawParam::new());
+
+        Ok(())
+    }
+
+    fn draw_controls(&mut self, ctx: &mut Context) -> GameResult {
+        let colors = self.settings.menu_colors();
+        let mut canvas = graphics::Canvas::from_frame(ctx, colors.background);
+
+        let labels = self.control_labels();
+        self.controls_screen.draw(&mut canvas, ctx, &labels)?;
+
+        let y = self.controls_screen.bottom() + 24.0;
+        let mut hint = Text::new(match self.capturing {
+            Some(action) => self
+                .strings
+                .format("controls.capture_hint", &[&self.action_name(action)]),
+            None => self.strings.get("controls.hint"),
+        });
+        hint.set_scale(18.0);
+        draw_centered_text(&mut canvas, ctx, &hint, y, colors.text)?;
+
+        if let Some(error) = self.bind_error {
+            let mut error = Text::new(match error {
+                BindError::Reserved => self.strings.get("controls.reserved"),
+                BindError::Unsupported => self.strings.get("controls.unsupported"),
+                BindError::Taken(other) => self
+                    .strings
+                    .format("controls.taken", &[&self.action_name(other)]),
+            });
+            error.set_scale(18.0);
+            error.fragments_mut()[0].color = Some(colors.warning);
+            draw_centered_text(&mut canvas, ctx, &error, y + 28.0, colors.text)?;
+        }
+
+        canvas.finish(ctx)?;
+
+        Ok(())
+    }
+
+    // Ticks per second, which comes from the speed setting and goes up for the finale
+    fn tick_rate(&self) -> u32 {
+        let rate = self.settings.speed.tick_rate();
+        if self.run.finale {
+            rate + FINALE_SPEEDUP
+        } else {
+            rate
+        }
+    }
+
+    // How long a tick lasts right now, whether it's from the tick rate or rhythm mode
+    fn tick_length(&self) -> std::time::Duration {
+        match &self.beat_clock {
+            Some(clock) => clock.step_length(),
+            None => std::time::Duration::from_secs(1) / self.tick_rate(),
+        }
+    }
+
+    fn start_finale(&mut self, ctx: &Context) {
+        self.run.finale = true;
+
+        self.audio
+            .set_music_pitch(ctx, Track::Game, FINALE_MUSIC_PITCH);
+    }
+
+    fn draw_gameplay(&mut self, ctx: &mut Context) -> GameResult {
+        // The end screen's sting is cut off the moment a new game is on screen
+        self.audio.stop_sting();
+        self.audio.request_music(Some(Track::Game));
+
+        // First make a clear canvas
+        let mut canvas = graphics::Canvas::from_frame(ctx, self.background());
+
+        self.draw_playfield(&mut canvas, ctx)?;
+        let colors = self.settings.menu_colors();
+        self.popups
+            .draw(&mut canvas, ctx, colors, self.settings.reduce_motion)?;
+        // Last, so nothing on the board can cover it
+        dpad::draw(&mut canvas, ctx, self.settings.dpad, colors)?;
+
+        // "Flush" the draw commands
+        canvas.finish(ctx)?;
+
+        Ok(())
+    }
+
+    // The board as it'll start, an arrow showing which way the snake is about to go,
+    // and the count over the top. The music waits for the game to actually start
+    fn draw_countdown(&mut self, ctx: &mut Context) -> GameResult {
+        self.audio.stop_sting();
+        self.audio.request_music(None);
+
+        let colors = self.settings.menu_colors();
+        let mut canvas = graphics::Canvas::from_frame(ctx, self.background());
+
+        self.draw_playfield(&mut canvas, ctx)?;
+        self.draw_direction_arrow(&mut canvas, ctx)?;
+
+        let elapsed = ctx.time.time_since_start() - self.countdown_started;
+        let remaining = COUNTDOWN_LENGTH.saturating_sub(elapsed);
+        let mut count = Text::new((remaining.as_secs() + 1).to_string());
+        count.set_scale(96.0);
+        let height = count.measure(ctx)?.y;
+        draw_centered_text(
+            &mut canvas,
+            ctx,
+            &count,
+            SCREEN_SIZE.1 / 2.0 - height / 2.0,
+            colors.text,
+        )?;
+
+        // The one moment before a run where there's time to read a reminder
+        if self.settings.vim_keys {
+            let mut hint = Text::new(self.strings.get("countdown.vim_keys"));
+            hint.set_scale(18.0);
+            draw_centered_text(
+                &mut canvas,
+                ctx,
+                &hint,
+                SCREEN_SIZE.1 / 2.0 + height / 2.0 + 16.0,
+                colors.text,
+            )?;
+        }
+
+        canvas.finish(ctx)?;
+
+        Ok(())
+    }
+
+    // A triangle in the cell in front of the head, pointing the way the snake is facing
+    fn draw_direction_arrow(&self, canvas: &mut graphics::Canvas, ctx: &mut Context) -> GameResult {
+        let cell: Rect = GridPosition::new_from_move(self.snake.head.pos, self.snake.dir).into();
+        let arrow = graphics::Mesh::new_polygon(
+            ctx,
+            graphics::DrawMode::fill(),
+            &arrow_points(cell, self.snake.dir),
+            Color {
+                a: 0.8,
+                ..self.settings.menu_colors().text
+            },
+        )?;
+        canvas.draw(&arrow, graphics::DrawParam::new());
+
+        Ok(())
+    }
+
+    // Where the day/night cycle has the background up to, or None when it's off. Palettes
+    // with a background of their own keep it, since they picked it for contrast
+    fn day_night_background(&self) -> Option<Color> {
+        if !self.settings.day_night || self.settings.board_colors().background.is_some() {
+            return None;
+        }
+        let phase = self.play_time.as_secs_f32() / DAY_NIGHT_CYCLE.as_secs_f32();
+        Some(self.settings.theme.day_night(phase))
+    }
+
+    fn background(&self) -> Color {
+        self.day_night_background()
+            .unwrap_or(self.settings.background())
+    }
+
+    // The cycle's checker is just its background a little darker, about as far off it as
+    // the themes' own checkers are
+    fn checker(&self) -> Option<Color> {
+        match self.day_night_background() {
+            Some(background) => Some(Color::new(
+                background.r * 0.9,
+                background.g * 0.9,
+                background.b * 0.9,
+                1.0,
+            )),
+            None => self.settings.checker(),
+        }
+    }
+
+    // Dusk and night would swallow a dark snake, so it gets lifted to stay readable
+    fn board_colors(&self) -> BoardColors {
+        let colors = self.settings.board_colors();
+        match self.day_night_background() {
+            Some(background) => colors.readable_on(background, self.settings.background()),
+            None => *colors,
+        }
+    }
+
+    // Goes straight over the background, before anything else on the board
+    fn draw_board_background(&self, canvas: &mut graphics::Canvas) {
+        if let Some(color) = self.checker() {
+            canvas.draw(&self.checkerboard, graphics::DrawParam::new().color(color));
+        }
+        if self.settings.grid_lines
+            && self.tick_length() >= std::time::Duration::from_secs(1) / GRID_LINES_MAX_RATE
+        {
+            canvas.draw(
+                &self.grid_lines,
+                graphics::DrawParam::new().color(self.settings.theme.grid_line()),
+            );
+        }
+    }
+
+    // Whether the snake is flashed white right now, and how many segments have gone from
+    // its tail
+    fn death_frame(&self, now: std::time::Duration) -> (bool, usize) {
+        let elapsed = now - self.dying_started;
+        let flashing = DEATH_FLASH_LENGTH * 2 * DEATH_FLASHES;
+        if elapsed < flashing {
+            let flash = (elapsed.as_secs_f32() / DEATH_FLASH_LENGTH.as_secs_f32()) as u32;
+            return (flash.is_multiple_of(2), 0);
+        }
+        let vanished = (elapsed - flashing).as_secs_f32() / DEATH_VANISH_LENGTH.as_secs_f32();
+        let segments = self.snake.body.len() + 1;
+        (false, (vanished * segments as f32) as usize)
+    }
+
+    // The death sequence's length, once the flashing's done and the whole snake is gone
+    fn death_length() -> std::time::Duration {
+        DEATH_FLASH_LENGTH * 2 * DEATH_FLASHES + DEATH_VANISH_LENGTH
+    }
+
+    // The sting goes off the moment the snake dies, over the gameplay track fading out.
+    // Reduced motion skips the flashing and goes straight to the loss screen
+    fn start_dying(&mut self, ctx: &Context) {
+        self.audio.music.end_run();
+        self.audio.start_sting(ctx, Sting::Loss);
+        if self.settings.reduce_motion {
+            self.finish_run(ctx, GAME_LOSS);
+        } else {
+            self.dying_started = ctx.time.time_since_start();
+            self.shake_started = Some(self.dying_started);
+            self.replace_screen(DYING);
+        }
+    }
+
+    // A jitter that shrinks as the shake wears off, and is exactly nothing once it's over.
+    // Two sine waves that never line up are random enough at this size, and keep the
+    // game's RNG out of it
+    fn update_shake(&mut self, now: std::time::Duration) {
+        self.shake_offset = Point2 { x: 0.0, y: 0.0 };
+        let Some(started) = self.shake_started else {
+            return;
+        };
+        let elapsed = (now - started).as_secs_f32();
+        let left = 1.0 - elapsed / SHAKE_LENGTH.as_secs_f32();
+        if left <= 0.0 || self.settings.reduce_motion {
+            self.shake_started = None;
+            return;
+        }
+        let amplitude = SHAKE_AMPLITUDE * left;
+        self.shake_offset = Point2 {
+            x: amplitude * (elapsed * 97.0).sin(),
+            y: amplitude * (elapsed * 71.0).cos(),
+        };
+    }
+
+    // Knocks everything drawn on the canvas by the shake. Only the board screens do this,
+    // and menus are never shaken, so nothing clickable ever moves out from under the mouse
+    fn shake(&self, canvas: &mut graphics::Canvas) {
+        let Point2 { x, y } = self.shake_offset;
+        if x != 0.0 || y != 0.0 {
+            canvas.set_screen_coordinates(Rect::new(-x, -y, SCREEN_SIZE.0, SCREEN_SIZE.1));
+        }
+    }
+
+    // The game just as it ended, HUD and all, with the snake flashing and then vanishing.
+    // No music is asked for, so the gameplay track carries on fading out
+    fn draw_dying(&mut self, ctx: &mut Context) -> GameResult {
+        let mut canvas = graphics::Canvas::from_frame(ctx, self.background());
+        self.shake(&mut canvas);
+
+        self.draw_playfield(&mut canvas, ctx)?;
+
+        canvas.finish(ctx)?;
+
+        Ok(())
+    }
+
+    // Just what's on the board, no HUD
+    fn draw_board(&self, canvas: &mut graphics::Canvas, ctx: &Context) -> GameResult {
+        self.draw_board_background(canvas);
+
+        // The ghost goes down first so the real snake is always drawn over it
+        if let Some(ghost) = &self.ghost {
+            ghost.draw(canvas, self.settings.menu_colors().text);
+        }
+
+        // Then have the snake and food draw themselves
+        let colors = &self.board_colors();
+        // A finished run's board shows exactly where everything ended up
+        let progress = if self.settings.smooth_movement
+            && matches!(self.screen(), GAMEPLAY | PAUSE_MENU | RESTART_PROMPT)
+        {
+            self.tick_progress
+        } else {
+            1.0
+        };
+        if self.settings.fading_trail && !self.settings.reduce_motion {
+            self.snake.draw_trail(canvas, colors);
+        }
+        let (flash, hidden) = if self.screen() == DYING {
+            self.death_frame(ctx.time.time_since_start())
+        } else {
+            (false, 0)
+        };
+        let snake_colors = if flash {
+            let white = self.settings.menu_colors().text;
+            BoardColors {
+                head: white,
+                body: white,
+                tail: white,
+                ..*colors
+            }
+        } else {
+            *colors
+        };
+        self.snake
+            .draw(canvas, ctx, &snake_colors, &self.sprites, progress, hidden)?;
+        self.food.draw(
+            canvas,
+            ctx,
+            colors,
+            &self.sprites,
+            self.settings.reduce_motion,
+        )
+    }
+
+    // Everything on the board plus the HUD, shared by gameplay and the pause menu behind it
+    fn draw_playfield(&self, canvas: &mut graphics::Canvas, ctx: &mut Context) -> GameResult {
+        self.draw_board(canvas, ctx)?;
+        let colors = self.settings.menu_colors();
+
+        if let Some(path) = &self.auto_path {
+            let marker = graphics::Mesh::new_rectangle(
+                ctx,
+                graphics::DrawMode::stroke(3.0),
+                path.target.into(),
+                colors.highlight,
+            )?;
+            canvas.draw(&marker, graphics::DrawParam::new());
+        }
+
+        if self.settings.control_scheme == ControlScheme::SingleSwitch {
+            let arrow = graphics::Mesh::new_polygon(
+                ctx,
+                graphics::DrawMode::fill(),
+                &arrow_points(self.snake.head.pos.into(), self.switch_preview),
+                Color {
+                    a: 0.8,
+                    ..colors.text
+                },
+            )?;
+            canvas.draw(&arrow, graphics::DrawParam::new());
+        }
+
+        self.draw_progress_bar(canvas);
+        self.draw_timer(canvas, ctx)?;
+
+        // The timer has the bottom left, so the badges go in the other corner
+        let badges = self.badges();
+        let width = badges::row_width(ctx, &self.strings, &badges)?;
+        badges::draw(
+            canvas,
+            ctx,
+            &self.strings,
+            colors,
+            &badges,
+            SCREEN_SIZE.0 - width - 8.0,
+            SCREEN_SIZE.1 - 30.0,
+        )?;
+
+        if let Some(clock) = &self.beat_clock {
+            self.draw_metronome(canvas, ctx, clock)?;
+        }
+
+        if self.run.finale {
+            let mut remaining = Text::new(self.strings.format(
+                "hud.sudden_death",
+                &[&(TARGET_LENGTH - self.snake.num_segments)],
+            ));
+            remaining.set_scale(24.0);
+            canvas.draw(
+                &remaining,
+                graphics::DrawParam::new()
+                    .dest(Point2 { x: 8.0, y: 8.0 })
+                    .color(colors.warning),
+            );
+        }
+
+        if !self.run.split_display_left.is_zero() {
+            if let Some(result) = self.run.split_results.last() {
+                let text = split_text(result, 24.0, colors);
+                let width = text.measure(ctx)?.x;
+                canvas.draw(
+                    &text,
+                    Point2 {
+                        x: SCREEN_SIZE.0 - width - 8.0,
+                        y: 8.0,
+                    },
+                );
+            }
+        }
+
+        Ok(())
+    }
+
+    // A light for each beat of the bar along the bottom, with the one the music's on lit up
+    // and pulsing, and the calibration offset above them
+    fn draw_metronome(
+        &self,
+        canvas: &mut graphics::Canvas,
+        ctx: &mut Context,
+        clock: &BeatClock,
+    ) -> GameResult {
+        let colors = self.settings.menu_colors();
+        let size = 12.0;
+        let gap = 10.0;
+        let width = BEATS_PER_BAR as f32 * (size + gap) - gap;
+        let y = SCREEN_SIZE.1 - 24.0;
+        for beat in 0..BEATS_PER_BAR {
+            let center = Point2 {
+                x: SCREEN_SIZE.0 / 2.0 - width / 2.0 + beat as f32 * (size + gap) + size / 2.0,
+                y,
+            };
+            let (size, color) = if beat == clock.beat_in_bar() {
+                let pulse = if self.settings.reduce_motion {
+                    0.0
+                } else {
+                    1.0 - clock.beat_phase()
+                };
+                (size * (1.0 + 0.5 * pulse), colors.text)
+            } else {
+                (
+                    size,
+                    Color {
+                        a: 0.3,
+                        ..colors.text
+                    },
+                )
+            };
+            canvas.draw(
+                &graphics::Quad,
+                graphics::DrawParam::new()
+                    .dest_rect(Rect::new(
+                        center.x - size / 2.0,
+                        center.y - size / 2.0,
+                        size,
+                        size,
+                    ))
+                    .color(color),
+            );
+        }
+
+        let mut offset = Text::new(self.strings.format(
+            "hud.rhythm_offset",
+            &[&format!("{:+}", self.settings.rhythm_offset)],
+        ));
+        offset.set_scale(16.0);
+        draw_centered_text(canvas, ctx, &offset, y - 34.0, colors.text)
+    }
+
+    // A thin see-through strip across the top showing how close the snake is to filling the board
+    fn draw_progress_bar(&self, canvas: &mut graphics::Canvas) {
+        let colors = self.settings.menu_colors();
+        let progress = self.snake.num_segments as f32 / TARGET_LENGTH as f32;
+        // Same point the finale kicks in
+        let fill = if self.snake.num_segments >= FINALE_LENGTH {
+            Color {
+                a: 0.7,
+                ..colors.warning
+            }
+        } else {
+            Color {
+                a: 0.6,
+                ..colors.text
+            }
+        };
+
+        canvas.draw(
+            &graphics::Quad,
+            graphics::DrawParam::new()
+                .dest_rect(Rect::new(0.0, 0.0, SCREEN_SIZE.0, PROGRESS_BAR_HEIGHT))
+                .color(Color {
+                    a: 0.3,
+                    ..colors.shade
+                }),
+        );
+        canvas.draw(
+            &graphics::Quad,
+            graphics::DrawParam::new()
+                .dest_rect(Rect::new(
+                    0.0,
+                    0.0,
+                    SCREEN_SIZE.0 * progress.min(1.0),
+                    PROGRESS_BAR_HEIGHT,
+                ))
+                .color(fill),
+        );
+    }
+
+    // Bottom left, on a dark backing so it reads over the snake and any background
+    fn draw_timer(&self, canvas: &mut graphics::Canvas, ctx: &mut Context) -> GameResult {
+        let colors = self.settings.menu_colors();
+        let seconds = self.run.time.as_secs();
+        let mut timer = Text::new(format!("{:02}:{:02}", seconds / 60, seconds % 60));
+        timer.set_scale(24.0);
+        let size = timer.measure(ctx)?;
+        let padding = 6.0;
+        let position = Point2 {
+            x: 8.0,
+            y: SCREEN_SIZE.1 - size.y - 8.0,
+        };
+
+        canvas.draw(
+            &graphics::Quad,
+            graphics::DrawParam::new()
+                .dest_rect(Rect::new(
+                    position.x - padding,
+                    position.y - padding,
+                    size.x + 2.0 * padding,
+                    size.y + 2.0 * padding,
+                ))
+                .color(Color {
+                    a: 0.5,
+                    ..colors.shade
+                }),
+        );
+        canvas.draw(
+            &timer,
+            graphics::DrawParam::new().dest(position).color(colors.text),
+        );
+
+        Ok(())
+    }
+
+    // The frozen board stays visible (but dimmed) behind the menu
+    fn draw_pause(&mut self, ctx: &mut Context) -> GameResult {
+        let mut canvas = graphics::Canvas::from_frame(ctx, self.background());
+
+        self.draw_playfield(&mut canvas, ctx)?;
+        canvas.draw(
+            &graphics::Quad,
+            graphics::DrawParam::new()
+                .dest_rect(Rect::new(0.0, 0.0, SCREEN_SIZE.0, SCREEN_SIZE.1))
+                .color(Color {
+                    a: 0.6,
+                    ..self.settings.menu_colors().shade
+                }),
+        );
+        self.pause_menu.draw(&mut canvas, ctx)?;
+
+        canvas.finish(ctx)?;
+
+        Ok(())
+    }
+
+    // Just a small box over the frozen board, nothing as heavy as an OptionScreen
+    fn draw_restart_prompt(&mut self, ctx: &mut Context) -> GameResult {
+        let mut canvas = graphics::Canvas::from_frame(ctx, self.background());
+
+        self.draw_playfield(&mut canvas, ctx)?;
+        draw_prompt_box(
+            &mut canvas,
+            ctx,
+            &self.strings.get("prompt.restart"),
+            self.settings.menu_colors(),
+        )?;
+
+        canvas.finish(ctx)?;
+
+        Ok(())
+    }
+
+    // The menu underneath gets drawn as normal, then the prompt goes on top of it
+    fn draw_quit_prompt(&mut self, ctx: &mut Context) -> GameResult {
+        self.draw_screen(ctx, self.screen_below())?;
+
+        // None keeps what was just drawn instead of clearing the frame
+        let mut canvas = graphics::Canvas::from_frame(ctx, None);
+        draw_prompt_box(
+            &mut canvas,
+            ctx,
+            &self.strings.get("prompt.quit"),
+            self.settings.menu_colors(),
+        )?;
+        canvas.finish(ctx)?;
+
+        Ok(())
+    }
+
+    fn open_quit_prompt(&mut self) {
+        self.push_screen(QUIT_PROMPT);
+    }
+
+    // Goes straight back to the menu with its selection untouched
+    fn close_quit_prompt(&mut self) {
+        self.pop_screen();
+        self.refresh_hover();
+    }
+
+    fn open_restart_prompt(&mut self, ctx: &Context) {
+        self.audio.request_music(None);
+        self.restart_prompt_opened = ctx.time.time_since_start();
+        self.push_screen(RESTART_PROMPT);
+    }
+
+    // Back to the game exactly as it was left, buffered turn and all. The music fades
+    // back in from where it stopped once the game's on screen again
+    fn close_restart_prompt(&mut self) {
+        self.pop_screen();
+    }
+
+    fn screen(&self) -> u8 {
+        self.screens.top()
+    }
+
+    fn screen_below(&self) -> u8 {
+        self.screens.below()
+    }
+
+    fn push_screen(&mut self, screen: u8) {
+        self.screens.push(screen);
+    }
+
+    // Back, from anywhere. The title screen never gets popped
+    fn pop_screen(&mut self) {
+        self.screens.pop();
+    }
+
+    // For moving along rather than opening something on top, like the countdown
+    // running into the game
+    fn replace_screen(&mut self, screen: u8) {
+        self.screens.replace(screen);
+    }
+
+    fn return_to_title(&mut self) {
+        self.screens.clear();
+    }
+
+    // The OptionScreen for the current state, if it has one
+    fn option_screen_mut(&mut self) -> Option<&mut OptionScreen> {
+        match self.screen() {
+            TITLE_SCREEN => Some(&mut self.title_screen),
+            // The buttons only turn up once the replay is over
+            GAME_LOSS if self.replay.is_none() => Some(&mut self.loss_screen),
+            GAME_WIN => Some(&mut self.win_screen),
+            PAUSE_MENU => Some(&mut self.pause_menu),
+            _ => None,
+        }
+    }
+
+    // Hover only ever shows on the current screen, under wherever the cursor is right now
+    fn refresh_hover(&mut self) {
+        self.title_screen.hovered = None;
+        self.loss_screen.hovered = None;
+        self.win_screen.hovered = None;
+        self.pause_menu.hovered = None;
+        self.settings_screen.hovered = None;
+        self.controls_screen.hovered = None;
+        self.gamepad_screen.hovered = None;
+        self.display_screen.hovered = None;
+
+        let Point2 { x, y } = self.mouse_pos;
+        if let Some(screen) = self.option_screen_mut() {
+            screen.update_hover(x, y);
+        } else if let Some(screen) = self.stepper_screen_mut() {
+            screen.update_hover(x, y);
+        } else if self.screen() == CONTROLS_SCREEN && self.capturing.is_none() {
+            self.controls_screen.update_hover(x, y);
+        }
+        self.hover_state = self.screen();
+    }
+
+    // The snake is left exactly as it was, so a turn buffered before pausing is still the
+    // first thing it does after resuming. It just waits for a proper tick rather than
+    // jumping forward the moment the menu closes
+    fn open_pause_menu(&mut self) {
+        self.run.pause();
+        self.audio.request_music(None);
+        self.pause_menu.take_clicked();
+        self.push_screen(PAUSE_MENU);
+    }
+
+    // The current scoring preset's name in the current language
+    fn scoring_name(&self) -> String {
+        self.strings
+            .get(&format!("scoring.{}", self.settings.scoring.key()))
+    }
+
+    fn draw_title(&mut self, ctx: &mut Context) -> GameResult {
+        self.audio.request_music(Some(Track::Title));
+
+        let colors = self.settings.menu_colors();
+        let mut canvas = graphics::Canvas::from_frame(ctx, colors.background);
+
+        self.title_snake
+            .get_or_insert_with(TitleSnake::new)
+            .draw(&mut canvas, self.settings.board_colors());
+        self.title_screen.draw(&mut canvas, ctx)?;
+
+        let mut scoring = Text::new(
+            self.strings
+                .format("title.scoring", &[&self.scoring_name()]),
+        );
+        scoring.set_scale(20.0);
+        draw_centered_text(
+            &mut canvas,
+            ctx,
+            &scoring,
+            SCREEN_SIZE.1 / 2.0 - 40.0,
+            colors.text,
+        )?;
+
+        // Read fresh every frame so a record set this session (or a preset change) shows right away
+        if let Some(best) = self.high_scores.best(self.settings.scoring) {
+            let mut best = Text::new(self.strings.format("title.best", &[&best]));
+            best.set_scale(24.0);
+            best.fragments_mut()[0].color = Some(colors.highlight);
+            draw_centered_text(
+                &mut canvas,
+                ctx,
+                &best,
+                SCREEN_SIZE.1 / 2.0 - 8.0,
+                colors.text,
+            )?;
+        }
+
+        // The reason comes from the recording's own check, so it's in English either way
+        if let Some(error) = &self.playback_error {
+            let mut heading = Text::new(self.strings.get("title.playback_error"));
+            heading.set_scale(18.0);
+            let mut reason = Text::new(error.as_str());
+            reason.set_scale(16.0);
+            for (line, y) in [(heading, 24.0), (reason, 48.0)] {
+                draw_centered_text(
+                    &mut canvas,
+                    ctx,
+                    &line,
+                    SCREEN_SIZE.1 / 2.0 + y,
+                    colors.warning,
+                )?;
+            }
+        }
+
+        canvas.finish(ctx)?;
+
+        Ok(())
+    }
+
+    fn draw_win(&mut self, ctx: &mut Context) -> GameResult {
+        self.audio.sting_then_loop(ctx, Sting::Win, Track::Win);
+
+        let colors = self.settings.menu_colors();
+        let mut canvas = graphics::Canvas::from_frame(ctx, self.background());
+        self.draw_end_board(&mut canvas, ctx, colors.win_tint)?;
+        self.confetti.draw(&mut canvas);
+
+        self.win_screen.draw(&mut canvas, ctx)?;
+        self.draw_run_summary(&mut canvas, ctx)?;
+
+        if self.run.finale {
+            let mut flourish = Text::new(self.strings.get("win.finale"));
+            flourish.set_scale(24.0);
+            flourish.fragments_mut()[0].color = Some(colors.highlight);
+            draw_centered_text(
+                &mut canvas,
+                ctx,
+                &flourish,
+                SCREEN_SIZE.1 / 2.0 - 136.0,
+                colors.text,
+            )?;
+        }
+
+        self.draw_split_table(&mut canvas, ctx)?;
+
+        let stars = self.summary.stars();
+        let stars = format!(
+            "{}{}",
+            "*".repeat(stars),
+            "-".repeat(RATING_THRESHOLDS.len() + 1 - stars)
+        );
+        let mut rating = Text::new(
+            self.strings
+                .format("win.completed", &[&format_time(self.summary.time), &stars]),
+        );
+        if let Some(best) = self.summary.previous_best_time {
+            rating.add(format!(
+                "   {}",
+                self.strings.format("win.best_time", &[&format_time(best)])
+            ));
+        }
+        rating.set_scale(20.0);
+        draw_centered_text(
+            &mut canvas,
+            ctx,
+            &rating,
+            SCREEN_SIZE.1 / 2.0 + 20.0,
+            colors.text,
+        )?;
+
+        canvas.finish(ctx)?;
+
+        Ok(())
+    }
+
+    fn draw_loss(&mut self, ctx: &mut Context) -> GameResult {
+        self.audio.sting_then_loop(ctx, Sting::Loss, Track::Loss);
+
+        let colors = self.settings.menu_colors();
+        let mut canvas = graphics::Canvas::from_frame(ctx, self.background());
+
+        // The replay draws the board by itself, so it needs the background put down first
+        if self.replay.is_some() {
+            self.draw_board_background(&mut canvas);
+        }
+        let board_colors = self.board_colors();
+        if let Some(replay) = &mut self.replay {
+            replay.draw(&mut canvas, ctx, &board_colors, &self.sprites)?;
+            let mut hint = Text::new(self.strings.get("loss.replay"));
+            hint.set_scale(24.0);
+            draw_centered_text(&mut canvas, ctx, &hint, 16.0, colors.text)?;
+            canvas.finish(ctx)?;
+            return Ok(());
+        }
+
+        self.draw_end_board(&mut canvas, ctx, colors.loss_tint)?;
+        self.head_trail.draw(&mut canvas, ctx, colors.highlight)?;
+        self.draw_collision(&mut canvas, ctx);
+
+        self.loss_screen.draw(&mut canvas, ctx)?;
+        self.draw_run_summary(&mut canvas, ctx)?;
+
+        canvas.finish(ctx)?;
+
+        Ok(())
+    }
+
+    // The board the run ended on, left exactly as it was and darkened so the menu reads
+    // over it. The theme tints it one way for a win and another for a loss
+    fn draw_end_board(
+        &self,
+        canvas: &mut graphics::Canvas,
+        ctx: &mut Context,
+        tint: Color,
+    ) -> GameResult {
+        self.draw_board(canvas, ctx)?;
+        canvas.draw(
+            &graphics::Quad,
+            graphics::DrawParam::new()
+                .dest_rect(Rect::new(0.0, 0.0, SCREEN_SIZE.0, SCREEN_SIZE.1))
+                .color(tint),
+        );
+
+        if self.show_heatmap {
+            self.draw_heatmap(canvas);
+        }
+
+        let mut hint = Text::new(self.strings.get(if self.show_heatmap {
+            "summary.hide_heatmap"
+        } else {
+            "summary.show_heatmap"
+        }));
+        hint.set_scale(16.0);
+        draw_centered_text(
+            canvas,
+            ctx,
+            &hint,
+            SCREEN_SIZE.1 - 24.0,
+            self.settings.menu_colors().text,
+        )
+    }
+
+    // Every visited cell goes from the theme's cold color (barely) to its hot one (the most
+    // visited cell of the run)
+    fn draw_heatmap(&self, canvas: &mut graphics::Canvas) {
+        let colors = self.settings.menu_colors();
+        let most = self.visits.iter().flatten().copied().max().unwrap_or(0);
+        if most == 0 {
+            return;
+        }
+
+        for (x, column) in self.visits.iter().enumerate() {
+            for (y, &visits) in column.iter().enumerate() {
+                if visits == 0 {
+                    continue;
+                }
+                let heat = visits as f32 / most as f32;
+                canvas.draw(
+                    &graphics::Quad,
+                    graphics::DrawParam::new()
+                        .dest_rect(GridPosition::new(x as i16, y as i16).into())
+                        .color(colors.heat(heat)),
+                );
+            }
+        }
+    }
+
+    // Straight to the loss screen's buttons, which get the same fresh start as any menu
+    // that's just opened
+    fn skip_replay(&mut self) {
+        self.replay = None;
+        self.loss_screen.reset_selection();
+        self.refresh_hover();
+    }
+
+    // The segment the snake bit flashes (on top of the dimming), with the head drawn
+    // a little smaller inside it so you can see both were there
+    fn draw_collision(&self, canvas: &mut graphics::Canvas, ctx: &Context) {
+        let Some(pos) = self.collision else {
+            return;
+        };
+
+        // Reduced motion holds it steady and solid instead
+        let flash = if self.settings.reduce_motion {
+            1.0
+        } else {
+            (ctx.time.time_since_start().as_secs_f32() * COLLISION_FLASH_SPEED).sin()
+        };
+        canvas.draw(
+            &graphics::Quad,
+            graphics::DrawParam::new()
+                .dest_rect(pos.into())
+                .color(Color {
+                    a: 0.6 + 0.4 * flash,
+                    ..self.settings.menu_colors().warning
+                }),
+        );
+        canvas.draw(
+            &graphics::Quad,
+            graphics::DrawParam::new()
+                .dest_rect(grow_rect(pos.into(), -GRID_CELL_SIZE.0 as f32 / 4.0))
+                .color(self.board_colors().head),
+        );
+    }
+
+    fn badges(&self) -> Vec<&'static str> {
+        badges::active(&self.settings, self.ghost.is_some())
+    }
+
+    // Shared by the win and loss screens, sits between the title and the buttons
+    fn draw_run_summary(&self, canvas: &mut graphics::Canvas, ctx: &mut Context) -> GameResult {
+        let summary = &self.summary;
+        let colors = self.settings.menu_colors();
+
+        // Same badges as the HUD, just over the score so screenshots say what the run was
+        let badges = self.badges();
+        let width = badges::row_width(ctx, &self.strings, &badges)?;
+        badges::draw(
+            canvas,
+            ctx,
+            &self.strings,
+            colors,
+            &badges,
+            SCREEN_SIZE.0 / 2.0 - width / 2.0,
+            SCREEN_SIZE.1 / 2.0 - 86.0,
+        )?;
+
+        let mut score = Text::new(
+            self.strings
+                .format("summary.score", &[&summary.score, &self.scoring_name()]),
+        );
+        score.set_scale(24.0);
+        draw_centered_text(canvas, ctx, &score, SCREEN_SIZE.1 / 2.0 - 58.0, colors.text)?;
+
+        let mut stats = Text::new(self.strings.format(
+            "summary.stats",
+            &[
+                &summary.length,
+                &summary.food_eaten,
+                &format_time(summary.time),
+            ],
+        ));
+        stats.set_scale(20.0);
+        draw_centered_text(canvas, ctx, &stats, SCREEN_SIZE.1 / 2.0 - 30.0, colors.text)?;
+
+        if self.new_high_score {
+            self.draw_new_high_score(canvas, ctx)?;
+        } else if let Some(best) = summary.previous_best {
+            let mut best = match &summary.previous_best_name {
+                Some(name) => Text::new(self.strings.format("summary.best_by", &[&best, name])),
+                None => Text::new(self.strings.format("summary.best", &[&best])),
+            };
+            best.set_scale(20.0);
+            draw_centered_text(canvas, ctx, &best, SCREEN_SIZE.1 / 2.0 - 4.0, colors.text)?;
+        }
+
+        Ok(())
+    }
+
+    // A pulsing banner, plus the old best crossed out next to the new one
+    fn draw_new_high_score(&self, canvas: &mut graphics::Canvas, ctx: &mut Context) -> GameResult {
+        let colors = self.settings.menu_colors();
+        // The end screens don't tick, so the pulse runs off the clock instead.
+        // Reduced motion leaves it sitting at its biggest, brightest size
+        let pulse = if self.settings.reduce_motion {
+            1.0
+        } else {
+            (ctx.time.time_since_start().as_secs_f32() * NEW_HIGH_SCORE_PULSE_SPEED).sin()
+        };
+        let mut banner = Text::new(self.strings.get("summary.new_high_score"));
+        banner.set_scale(32.0 + 4.0 * pulse);
+        banner.fragments_mut()[0].color = Some(Color {
+            a: 0.85 + 0.15 * pulse,
+            ..colors.highlight
+        });
+        draw_centered_text(
+            canvas,
+            ctx,
+            &banner,
+            SCREEN_SIZE.1 / 2.0 - 164.0,
+            colors.text,
+        )?;
+
+        let y = SCREEN_SIZE.1 / 2.0 - 4.0;
+        let Some(previous) = self.summary.previous_best else {
+            let mut best = Text::new(self.strings.format("summary.best", &[&self.summary.score]));
+            best.set_scale(20.0);
+            return draw_centered_text(canvas, ctx, &best, y, colors.text);
+        };
+
+        let mut label = Text::new(format!("{} ", self.strings.get("summary.best_label")));
+        let mut old = Text::new(previous.to_string());
+        let mut new = Text::new(format!("  {}", self.summary.score));
+        label.set_scale(20.0);
+        old.set_scale(20.0);
+        new.set_scale(20.0);
+        new.fragments_mut()[0].color = Some(colors.highlight);
+
+        let label_size = label.measure(ctx)?;
+        let old_size = old.measure(ctx)?;
+        let new_size = new.measure(ctx)?;
+        let mut x = SCREEN_SIZE.0 / 2.0 - (label_size.x + old_size.x + new_size.x) / 2.0;
+
+        let param = graphics::DrawParam::new().color(colors.text);
+        canvas.draw(&label, param.dest(Point2 { x, y }));
+        x += label_size.x;
+        canvas.draw(&old, param.dest(Point2 { x, y }));
+        // Text can't do strikethrough, so it's just a thin quad across the middle
+        canvas.draw(
+            &graphics::Quad,
+            graphics::DrawParam::new()
+                .dest_rect(Rect::new(x, y + old_size.y / 2.0, old_size.x, 2.0))
+                .color(colors.text),
+        );
+        x += old_size.x;
+        canvas.draw(&new, Point2 { x, y });
+
+        Ok(())
+    }
+
+    // Every split of the run in two columns across the top of the screen, golds in yellow
+    fn draw_split_table(&self, canvas: &mut graphics::Canvas, ctx: &mut Context) -> GameResult {
+        let rows = (TARGET_LENGTH / SPLIT_INTERVAL).div_ceil(2) as usize;
+        for (i, result) in self.run.split_results.iter().enumerate() {
+            let column = (i / rows) as f32;
+            let row = (i % rows) as f32;
+
+            let text = split_text(result, 20.0, self.settings.menu_colors());
+            let width = text.measure(ctx)?.x;
+            canvas.draw(
+                &text,
+                Point2 {
+                    x: SCREEN_SIZE.0 * (0.25 + 0.5 * column) - width / 2.0,
+                    y: 24.0 + row * 26.0,
+                },
+            );
+        }
+        Ok(())
+    }
+
+    // Called once a run is over (won or lost) to check it against the best score and the
+    // leaderboard. The game gives way to end_screen, with the leaderboard stacked on top
+    // if the run placed, and name entry on top of that if it set a new best. Each one
+    // pops off to reveal the next
+    fn finish_run(&mut self, ctx: &Context, end_screen: u8) {
+        if let InputMode::Record { path, log } = &self.input_mode {
+            log.save(path);
+        }
+
+        let preset = self.settings.scoring;
+        let end = if end_screen == GAME_WIN {
+            RunEnd::Won
+        } else if self.collision.is_some() {
+            RunEnd::HitSelf
+        } else {
+            RunEnd::HitWall
+        };
+        self.session_stats
+            .record(self.snake.num_segments, self.run.score.food_eaten, end);
+
+        self.summary = RunSummary {
+            length: self.snake.num_segments,
+            food_eaten: self.run.score.food_eaten,
+            time: self.run.time,
+            score: self.run.score.score,
+            previous_best: self.high_scores.best(preset),
+            previous_best_name: self.high_scores.best_name(preset).map(String::from),
+            previous_best_time: None,
+        };
+
+        if self.snake.num_segments == TARGET_LENGTH {
+            self.summary.previous_best_time = self.high_scores.best_time(preset);
+            self.high_scores.submit_time(ctx, preset, self.run.time);
+        }
+
+        self.high_scores
+            .submit_splits(ctx, preset, self.run.splits.times());
+        self.leaderboard_rank = self.leaderboard.insert(
+            ctx,
+            LeaderboardEntry::new(self.run.score.score, self.snake.num_segments, preset),
+        );
+        self.audio.music.end_run();
+        self.replace_screen(end_screen);
+        if let Some(rank) = self.leaderboard_rank {
+            self.open_leaderboard(Some(rank));
+        }
+
+        self.new_high_score = self.high_scores.submit(ctx, preset, self.run.score.score);
+        if self.new_high_score {
+            // A new best means this run becomes the ghost to chase
+            self.recording.save(ctx, preset);
+            self.best_recording = Some(self.recording.clone());
+
+            self.name_entry = NameEntry::new();
+            self.push_screen(NAME_ENTRY);
+        }
+    }
+
+    fn open_leaderboard(&mut self, highlight: Option<usize>) {
+        self.leaderboard_highlight = highlight;
+        self.push_screen(LEADERBOARD);
+    }
+
+    fn draw_leaderboard(&mut self, ctx: &mut Context) -> GameResult {
+        self.audio.music.fade_out(Track::Game);
+
+        let mut canvas = graphics::Canvas::from_frame(ctx, self.settings.menu_colors().background);
+
+        self.leaderboard.draw(
+            &mut canvas,
+            ctx,
+            self.settings.scoring,
+            self.leaderboard_highlight,
+            &self.strings,
+            self.settings.menu_colors(),
+        )?;
+
+        canvas.finish(ctx)?;
+
+        Ok(())
+    }
+
+    fn draw_name_entry(&mut self, ctx: &mut Context) -> GameResult {
+        self.audio.music.fade_out(Track::Game);
+
+        let mut canvas = graphics::Canvas::from_frame(ctx, self.settings.menu_colors().background);
+
+        self.name_entry
+            .draw(&mut canvas, ctx, &self.strings, self.settings.menu_colors())?;
+
+        canvas.finish(ctx)?;
+
+        Ok(())
+    }
+
+    // Does nothing while the name is too short to accept
+    fn confirm_name_entry(&mut self, ctx: &Context) {
+        if let Some(name) = self.name_entry.confirm() {
+            self.high_scores.set_name(ctx, self.settings.scoring, &name);
+            if let Some(rank) = self.leaderboard_rank {
+                self.leaderboard
+                    .set_name(ctx, self.settings.scoring, rank, &name);
+            }
+            self.pop_screen();
+        }
+    }
+
+    // The score was already saved in finish_run, so skipping only loses the name
+    fn skip_name_entry(&mut self) {
+        self.pop_screen();
+    }
+
+    // Rebuilds the board (snake and food) but leaves everything about the run alone.
+    // Only reset calls it for now, right after reset_run
+    fn reset_board(&mut self) {
+        let (snake, food) = new_board(&mut self.rng);
+        self.switch_preview = snake.dir;
+        self.switch_preview_shown = std::time::Duration::ZERO;
+        self.recording = GhostRecording::new(snake.head.pos);
+        self.snake = snake;
+        self.food = food;
+        self.collision = None;
+        self.replay_buffer.clear();
+        self.replay = None;
+        self.head_trail.clear();
+    }
+
+    // Wipes everything that belongs to the run as a whole. That includes the finale, and
+    // the music's pitch goes back with the rest of the music in reset
+    fn reset_run(&mut self) {
+        self.run = Run::default();
+        self.ghost = self.best_recording.clone().map(Ghost::new);
+        self.auto_path = None;
+        self.last_dir_press = None;
+        self.dash_pending = false;
+        self.new_high_score = false;
+        // Popups only make sense over the game they came from
+        self.popups.clear();
+        self.visits = [[0; GRID_SIZE.1 as usize]; GRID_SIZE.0 as usize];
+        self.show_heatmap = false;
+        self.beat_clock = (self.settings.speed == Speed::Rhythm).then(BeatClock::new);
+    }
+
+    // One step of the game: the snake moves and whatever it runs into gets dealt with
+    fn tick(&mut self, ctx: &mut Context) -> GameResult {
+        self.run.ticks += 1;
+        self.run.time += self.tick_length();
+        self.run.score.tick();
+        self.run.split_display_left = self
+            .run
+            .split_display_left
+            .saturating_sub(self.tick_length());
+        let rules = self.settings.scoring.rules();
+
+        match &mut self.input_mode {
+            InputMode::Live | InputMode::Record { .. } => match self.settings.mouse_mode {
+                MouseMode::Off => (),
+                MouseMode::Follow => self.steer_toward_mouse(),
+                MouseMode::ClickToPath => self.follow_auto_path(),
+            },
+            // Nothing else gets a say in where a played back snake goes
+            InputMode::Playback(log) => {
+                if let Some(dir) = log.turn_at(self.run.ticks) {
+                    self.snake.dir = dir;
+                }
+            }
+        }
+
+        // First update the snake (and the ghost alongside it)
+        let dash = std::mem::take(&mut self.dash_pending);
+        self.snake.update(&self.food, self.settings.walls, dash);
+        // Only ever from here, so menus and the pause menu are quiet without trying
+        if self.settings.tick_sound
+            && (1.0 / self.tick_length().as_secs_f32() <= TICK_SOUND_MAX_RATE
+                || self.run.ticks.is_multiple_of(2))
+        {
+            self.audio.play_sfx(ctx, Sfx::Tick);
+        }
+        self.recording.record(self.snake.dir);
+        // The ghost only ever moves one cell a tick, so it takes the dash a tick later
+        if dash {
+            self.recording.record(self.snake.dir);
+        }
+        // After the move, since that's when any queued turn has actually been taken
+        if let InputMode::Record { log, .. } = &mut self.input_mode {
+            log.record(self.run.ticks, self.snake.dir);
+        }
+        // A dash passes through two cells, and both of them count
+        for &(pos, wrapped) in &self.snake.steps {
+            self.visits[pos.x as usize][pos.y as usize] += 1;
+            self.head_trail.record(pos, wrapped);
+        }
+        self.replay_buffer.record(ReplayFrame {
+            snake: std::iter::once(self.snake.head.pos)
+                .chain(self.snake.body.iter().map(|seg| seg.pos))
+                .collect(),
+            dir: self.snake.dir,
+            food: self.food.pos,
+        });
+        if let Some(ghost) = &mut self.ghost {
+            ghost.update();
+        }
+        // Check if the snake ate something
+        if let Some(ate) = self.snake.ate {
+            match ate {
+                Ate::Food => {
+                    let mut points = self.run.score.food_eaten(&rules);
+                    // Rhythm mode pays double for food eaten right on the beat
+                    if self.beat_clock.as_ref().is_some_and(BeatClock::on_beat) {
+                        self.run.score.score += points;
+                        points *= 2;
+                    }
+                    self.audio
+                        .play_eat(ctx, self.run.score.combo, self.food.pos);
+                    self.popups.spawn(self.food.pos, points);
+                    if let Some(index) = self
+                        .run
+                        .splits
+                        .check(self.snake.num_segments, self.run.time)
+                    {
+                        let best = self.high_scores.best_splits(self.settings.scoring);
+                        self.run
+                            .split_results
+                            .push(SplitResult::new(index, self.run.time, best));
+                        self.run.split_display_left = SPLIT_DISPLAY_LENGTH;
+                    }
+                    if !self.run.finale && self.snake.num_segments >= FINALE_LENGTH {
+                        self.start_finale(ctx);
+                    }
+                    if self.snake.num_segments == TARGET_LENGTH {
+                        self.run.score.win_bonus(&rules, self.run.ticks);
+                        self.finish_run(ctx, GAME_WIN);
+                    } else {
+                        let pos = self.snake.get_food_space(&mut self.rng);
+                        self.food.respawn(pos, ctx.time.time_since_start());
+                    }
+                }
+                Ate::Itself | Ate::Wall => {
+                    if matches!(ate, Ate::Itself) {
+                        self.collision = Some(self.snake.head.pos);
+                    }
+                    // Half the speed the run was going at when it ended
+                    let frame_length = std::time::Duration::from_secs(2) / self.tick_rate();
+                    self.replay = Some(self.replay_buffer.take(frame_length));
+                    self.start_dying(ctx);
+                }
+            }
+        }
+
+        self.achievements.check(
+            ctx,
+            &RunProgress {
+                food_eaten: self.run.score.food_eaten,
+                length: self.snake.num_segments,
+                time: self.run.time,
+            },
+        );
+
+        Ok(())
+    }
+
+    // Picks the opening move during the countdown, read the same way as steer reads turns
+    fn face(&mut self, dir: Direction) {
+        let dir = match (self.settings.control_scheme, dir) {
+            (ControlScheme::Absolute, _) => dir,
+            (ControlScheme::Relative, Direction::Left) => self.snake.dir.rotate_ccw(),
+            (ControlScheme::Relative, Direction::Right) => self.snake.dir.rotate_cw(),
+            (ControlScheme::Relative, Direction::Up | Direction::Down) => return,
+            // Nothing's cycling yet, so the snake sets off the way it's already facing
+            (ControlScheme::SingleSwitch, _) => return,
+        };
+        self.snake.face(dir);
+    }
+
+    // Heads for the cell under the cursor, closing the longer of the two gaps first. A turn
+    // from the keyboard this tick wins, so the mouse only steers when nothing else has
+    fn steer_toward_mouse(&mut self) {
+        if self.snake.dir != self.snake.last_update_dir || !self.snake.next_dirs.is_empty() {
+            return;
+        }
+
+        let target = GridPosition::from_screen(self.mouse_pos.x, self.mouse_pos.y);
+        let head = self.snake.head.pos;
+        let (dx, dy) = (target.x - head.x, target.y - head.y);
+        let horizontal = (dx != 0).then_some(if dx > 0 {
+            Direction::Right
+        } else {
+            Direction::Left
+        });
+        let vertical = (dy != 0).then_some(if dy > 0 {
+            Direction::Down
+        } else {
+            Direction::Up
+        });
+        let order = if dx.abs() >= dy.abs() {
+            [horizontal, vertical]
+        } else {
+            [vertical, horizontal]
+        };
+
+        // Straight behind can't be turned into, so that falls to the other axis
+        if let Some(dir) = order
+            .into_iter()
+            .flatten()
+            .find(|dir| dir.inverse() != self.snake.dir)
+        {
+            self.snake.steer(dir);
+        }
+    }
+
+    // Plans a route to the clicked cell, if there's a way to get there
+    fn start_auto_path(&mut self, target: GridPosition) {
+        let snake = &self.snake;
+        let blocked = |pos: GridPosition| snake.body.iter().any(|seg| seg.pos == pos);
+        if blocked(target) || target == snake.head.pos {
+            self.auto_path = None;
+            return;
+        }
+        self.auto_path = autopath::find_path(snake.head.pos, target, self.settings.walls, blocked)
+            .map(|steps| AutoPath { target, steps });
+    }
+
+    // Takes the next step of the route. The board keeps changing under it (the snake grows,
+    // the tail moves on), so the moment a step would run into the body, or can't be taken,
+    // the route is dropped and the player has the snake back
+    fn follow_auto_path(&mut self) {
+        let Some(path) = &mut self.auto_path else {
+            return;
+        };
+        let Some(step) = path.steps.pop_front() else {
+            self.auto_path = None;
+            return;
+        };
+        let next = GridPosition::new_from_move(self.snake.head.pos, step);
+        let runs_into_body = self.snake.body.iter().any(|seg| seg.pos == next);
+        let turned = step == self.snake.dir || self.snake.steer(step);
+        if runs_into_body || !turned || path.steps.is_empty() {
+            self.auto_path = None;
+        }
+    }
+
+    // Moves the single switch preview on a quarter turn every SWITCH_ROTATE_INTERVAL. The
+    // way back into the neck can never be taken, so it's skipped over
+    fn cycle_switch_preview(&mut self, delta: std::time::Duration) {
+        self.switch_preview_shown += delta;
+        if self.switch_preview_shown < SWITCH_ROTATE_INTERVAL {
+            return;
+        }
+        self.switch_preview_shown = std::time::Duration::ZERO;
+        let heading = self
+            .snake
+            .next_dirs
+            .back()
+            .copied()
+            .unwrap_or(self.snake.dir);
+        self.switch_preview = self.switch_preview.rotate_cw();
+        if self.switch_preview == heading.inverse() {
+            self.switch_preview = self.switch_preview.rotate_cw();
+        }
+    }
+
+    // A second press the same way inside DASH_WINDOW dashes on the next tick. The input log
+    // only knows about turns, so a run being recorded or played back never dashes
+    fn check_dash(&mut self, dir: Direction, now: std::time::Duration) {
+        if !self.settings.dash || !matches!(self.input_mode, InputMode::Live) {
+            return;
+        }
+        match self.last_dir_press {
+            Some((last, at)) if last == dir && now - at <= DASH_WINDOW => {
+                self.dash_pending = true;
+                // A third tap starts counting again rather than dashing twice
+                self.last_dir_press = None;
+            }
+            _ => self.last_dir_press = Some((dir, now)),
+        }
+    }
+
+    // Every mid-game turn comes through here. Only a turn that takes effect on the coming
+    // tick can bring it forward, and only once per tick
+    fn steer(&mut self, dir: Direction) {
+        if let InputMode::Playback(_) = self.input_mode {
+            return;
+        }
+        // Steering by hand always takes over from a clicked route
+        self.auto_path = None;
+        let turned = match self.settings.control_scheme {
+            ControlScheme::Absolute => self.snake.steer(dir),
+            ControlScheme::Relative => match dir {
+                Direction::Left => self.snake.rotate(false),
+                Direction::Right => self.snake.rotate(true),
+                Direction::Up | Direction::Down => false,
+            },
+            // Any direction at all counts as the switch
+            ControlScheme::SingleSwitch => {
+                self.switch_preview_shown = std::time::Duration::ZERO;
+                self.snake.steer(self.switch_preview)
+            }
+        };
+        // A turn can't jump the beat in rhythm mode, so it waits like any other
+        if turned
+            && self.settings.responsive_turns
+            && self.beat_clock.is_none()
+            && !self.run.skip_tick
+        {
+            self.run.early_tick = true;
+        }
+    }
+
+    // A brand new game from scratch, used by the title screen and the retry buttons.
+    // It starts with a countdown so the opening move isn't a surprise
+    fn reset(&mut self, ctx: &Context) {
+        // The rules can be changed on the title screen or in settings after the recording
+        // was checked at startup, and playing it back under others would go its own way
+        if let InputMode::Playback(log) = &self.input_mode {
+            if let Err(e) = log.check(&self.settings) {
+                eprintln!("Not playing the recording back: {}", e);
+                self.playback_error = Some(e);
+                self.return_to_title();
+                return;
+            }
+        }
+        self.playback_error = None;
+
+        // Recorded and played back runs start from a known seed so the board comes out
+        // the same both times
+        match &mut self.input_mode {
+            InputMode::Live => (),
+            InputMode::Record { log, .. } => {
+                let seed = (self.rng.rand_u32() as u64) << 32 | self.rng.rand_u32() as u64;
+                *log = InputLog::new(seed, &self.settings);
+                self.rng = Rand32::new(seed);
+            }
+            InputMode::Playback(log) => self.rng = Rand32::new(log.seed),
+        }
+        self.title_snake = None;
+        self.audio.music.new_game();
+        self.reset_run();
+        self.reset_board();
+        self.countdown_started = ctx.time.time_since_start();
+        self.return_to_title();
+        self.push_screen(COUNTDOWN);
+    }
+}
+
+impl event::EventHandler<ggez::GameError> for GameState {
+    fn update(&mut self, ctx: &mut Context) -> GameResult {
+        // Not on the very first frame, so the loading screen gets drawn before any loading
+        if self.screen() == LOADING_SCREEN && ctx.time.ticks() > 1 {
+            self.load_next(ctx);
+        }
+
+        let delta = ctx.time.delta();
+        self.audio.update(ctx, delta);
+
+        // The credits scroll every frame so they don't move in 10 fps jumps
+        if self.screen() == CREDITS_SCREEN && self.credits.update(ctx.time.delta().as_secs_f32()) {
+            self.audio.play_sfx(ctx, Sfx::Death);
+        }
+
+        self.achievements.update_toasts(ctx.time.time_since_start());
+        if self.screen() == GAME_WIN && !self.settings.reduce_motion {
+            self.confetti.update(
+                ctx.time.time_since_start(),
+                ctx.time.delta(),
+                &self.settings.menu_colors().confetti,
+            );
+        } else {
+            self.confetti.clear();
+        }
+        if self.screen() == TITLE_SCREEN && !self.settings.reduce_motion {
+            if let Some(title_snake) = &mut self.title_snake {
+                title_snake.update(ctx.time.delta().as_secs_f32());
+            }
+        }
+        if self.screen() == GAMEPLAY {
+            self.popups.update(ctx.time.delta());
+            if self.settings.control_scheme == ControlScheme::SingleSwitch {
+                self.cycle_switch_preview(ctx.time.delta());
+            }
+        }
+
+        self.update_shake(ctx.time.time_since_start());
+
+        if self.screen() == DYING
+            && ctx.time.time_since_start() - self.dying_started >= Self::death_length()
+        {
+            self.finish_run(ctx, GAME_LOSS);
+        }
+
+        if self.screen() == GAME_LOSS
+            && self
+                .replay
+                .as_ref()
+                .is_some_and(|replay| replay.finished(ctx.time.time_since_start()))
+        {
+            self.skip_replay();
+        }
+
+        // built in timer that will cycle only when it is time
+
+        // With responsive turns a turn moves the snake right away, and the tick it would
+        // have waited for is skipped so turning can never speed the snake up
+        if self.screen() == GAMEPLAY && self.run.take_early_tick() {
+            self.tick(ctx)?;
+        }
+
+        while ctx.time.check_update_time(self.tick_rate()) {
+            // Only a button someone actually pressed gets here. Anything cleared behind the
+            // scenes, like the pause menu when it opens, never makes a sound
+            let clicked = self
+                .option_screen_mut()
+                .and_then(OptionScreen::take_clicked);
+            if clicked.is_some() {
+                self.audio.play_sfx(ctx, Sfx::Click);
+            }
+            match self.screen() {
+                TITLE_SCREEN => match clicked {
+                    Some(TITLE_START) => self.reset(ctx),
+                    Some(TITLE_SETTINGS) => self.push_screen(SETTINGS_SCREEN),
+                    Some(TITLE_LEADERBOARD) => self.open_leaderboard(None),
+                    Some(TITLE_STATS) => self.push_screen(STATS_SCREEN),
+                    Some(TITLE_ACHIEVEMENTS) => self.push_screen(ACHIEVEMENTS_SCREEN),
+                    Some(TITLE_CREDITS) => {
+                        self.credits.reset();
+                        self.push_screen(CREDITS_SCREEN);
+                    }
+                    Some(_) => self.open_quit_prompt(),
+                    None => (),
+                },
+                GAME_LOSS => match clicked {
+                    Some(0) => self.reset(ctx),
+                    Some(_) => self.open_quit_prompt(),
+                    None => (),
+                },
+                GAME_WIN => match clicked {
+                    Some(0) => self.reset(ctx),
+                    Some(_) => self.open_quit_prompt(),
+                    None => (),
+                },
+                PAUSE_MENU => match clicked {
+                    // The track fades back in from right where it was paused
+                    Some(PAUSE_RESUME) => self.pop_screen(),
+                    Some(PAUSE_RESTART) => self.reset(ctx),
+                    Some(PAUSE_QUIT) => self.return_to_title(),
+                    _ => (),
+                },
+                COUNTDOWN
+                    if ctx.time.time_since_start() - self.countdown_started >= COUNTDOWN_LENGTH =>
+                {
+                    self.replace_screen(GAMEPLAY);
+                }
+                // Nobody answered, so carry on with the game
+                RESTART_PROMPT
+                    if ctx.time.time_since_start() - self.restart_prompt_opened
+                        >= RESTART_PROMPT_TIMEOUT =>
+                {
+                    self.close_restart_prompt();
+                }
+                // Rhythm mode moves on the beat below instead
+                GAMEPLAY if self.beat_clock.is_some() => (),
+                // Unless a responsive turn already took this one early
+                GAMEPLAY if self.run.take_regular_tick() => self.tick(ctx)?,
+                _ => (),
+            }
+        }
+
+        if self.screen() == GAMEPLAY {
+            if let Some(clock) = &mut self.beat_clock {
+                clock.advance(
+                    ctx.time.delta(),
+                    self.audio.music.position(Track::Game),
+                    self.audio.music.bpm(Track::Game),
+                    self.settings.rhythm_offset,
+                );
+            }
+            while self.screen() == GAMEPLAY
+                && self.beat_clock.as_mut().is_some_and(BeatClock::next_step)
+            {
+                self.tick(ctx)?;
+            }
+        }
+        if self.screen() == GAMEPLAY {
+            self.play_time += ctx.time.delta();
+            self.tick_progress = match &self.beat_clock {
+                Some(clock) => clock.step_progress(),
+                None => {
+                    ctx.time.remaining_update_time().as_secs_f32()
+                        / self.tick_length().as_secs_f32()
+                }
+            }
+            .min(1.0);
+        }
+
+        // Once a frame at most, after all the ticks, rather than on every tick
+        self.update_window_title(ctx);
+
+        Ok(())
+    }
+
+    fn draw(&mut self, ctx: &mut Context) -> GameResult {
+        // A menu that just opened starts on its first button, and since the screen changed
+        // under a still cursor the old hover no longer applies
+        if self.hover_state != self.screen() {
+            if let Some(screen) = self.option_screen_mut() {
+                screen.reset_selection();
+            }
+            self.refresh_hover();
+        }
+
+        // The state has already changed by the time a fade starts, so input goes straight
+        // to the new screen and the fade is only ever something to look at
+        if self.drawn_state != self.screen() {
+            if self.settings.fades
+                && !self.settings.reduce_motion
+                && fades_between(self.drawn_state, self.screen())
+            {
+                self.fade = Some(Fade {
+                    from: self.drawn_state,
+                    started: ctx.time.time_since_start(),
+                });
+            }
+            self.drawn_state = self.screen();
+        }
+
+        let (screen, darkness) = match &self.fade {
+            Some(fade) => fade.frame(ctx.time.time_since_start(), self.screen()),
+            None => (self.screen(), 0.0),
+        };
+        if screen == self.screen() && darkness <= 0.0 {
+            self.fade = None;
+        }
+
+        self.draw_screen(ctx, screen)?;
+
+        if darkness > 0.0 {
+            // None keeps the screen that was just drawn
+            let mut canvas = graphics::Canvas::from_frame(ctx, None);
+            canvas.draw(
+                &graphics::Quad,
+                graphics::DrawParam::new()
+                    .dest_rect(Rect::new(0.0, 0.0, SCREEN_SIZE.0, SCREEN_SIZE.1))
+                    .color(Color {
+                        a: darkness,
+                        ..self.settings.menu_colors().shade
+                    }),
+            );
+            canvas.finish(ctx)?;
+        }
+
+        // Toasts, the mute icon and the volume go over everything, fades included
+        let colors = self.settings.menu_colors();
+        let mut canvas = graphics::Canvas::from_frame(ctx, None);
+        self.achievements.draw_toast(
+            &mut canvas,
+            ctx,
+            &self.strings,
+            colors,
+            self.settings.reduce_motion,
+        )?;
+        if self.settings.muted {
+            draw_muted_icon(&mut canvas, ctx, colors)?;
+        }
+        if self
+            .volume_changed
+            .is_some_and(|at| ctx.time.time_since_start() - at < VOLUME_INDICATOR_LENGTH)
+        {
+            let mut volume = Text::new(
+                self.strings
+                    .format("settings.music", &[&self.settings.music_volume]),
+            );
+            volume.set_scale(24.0);
+            draw_centered_text(&mut canvas, ctx, &volume, 40.0, colors.text)?;
+        }
+        canvas.finish(ctx)?;
+
+        // Yield the thread until the next update and return success
+        ggez::timer::yield_now();
+        Ok(())
+    }
+
+    // This fires when a key gets pressed
+    fn key_down_event(
+        &mut self,
+        ctx: &mut Context,
+        input: KeyInput,
+        repeated: bool,
+    ) -> Result<(), ggez::GameError> {
+        if repeated
+            && !input
+                .keycode
+                .is_some_and(|key| takes_repeat(&self.settings.bindings, self.screen(), key))
+        {
+            return Ok(());
+        }
+
+        // Works from anywhere, since it's no use hunting for the setting if you can't see it
+        if let Some(KeyCode::F2) = input.keycode {
+            self.toggle_high_contrast(ctx);
+            return Ok(());
+        }
+
+        match input.keycode {
+            Some(KeyCode::M) if self.hotkey_free(KeyCode::M) => {
+                self.toggle_mute(ctx);
+                return Ok(());
+            }
+            Some(KeyCode::N) if self.hotkey_free(KeyCode::N) => {
+                self.toggle_music_mute(ctx);
+                return Ok(());
+            }
+            Some(
+                key @ (KeyCode::Equals
+                | KeyCode::Plus
+                | KeyCode::NumpadAdd
+                | KeyCode::Minus
+                | KeyCode::NumpadSubtract),
+            ) if self.hotkey_free(key) => {
+                let delta = if matches!(key, KeyCode::Minus | KeyCode::NumpadSubtract) {
+                    -1
+                } else {
+                    1
+                };
+                self.step_music_volume(ctx, delta);
+                return Ok(());
+            }
+            _ => (),
+        }
+
+        // Noted so moving to another button or row can tick
+        let (screen, selection) = (self.screen(), self.menu_selection());
+        match self.screen() {
+            GAMEPLAY => {
+                let Some(key) = input.keycode else {
+                    return Ok(());
+                };
+                let bindings = self.settings.bindings;
+                match gameplay_key(&bindings, self.settings.vim_keys, key) {
+                    Some(GameplayKey::Pause) => {
+                        self.open_pause_menu();
+                        return Ok(());
+                    }
+                    Some(GameplayKey::Steer(dir)) => {
+                        self.steer(dir);
+                        self.check_dash(dir, ctx.time.time_since_start());
+                    }
+                    None if self.settings.control_scheme == ControlScheme::SingleSwitch
+                        && (key == KeyCode::Space
+                            || bindings.action(key) == Some(Action::Confirm)) =>
+                    {
+                        self.steer(self.switch_preview);
+                    }
+                    None if key == KeyCode::R => self.open_restart_prompt(ctx),
+                    None if key == KeyCode::T && self.hotkey_free(KeyCode::T) => {
+                        self.audio.music.next_song(Track::Game);
+                    }
+                    None if self.beat_clock.is_some()
+                        && matches!(key, KeyCode::LBracket | KeyCode::RBracket)
+                        && self.hotkey_free(key) =>
+                    {
+                        let delta = if key == KeyCode::LBracket { -1 } else { 1 };
+                        self.settings.step_rhythm_offset(delta);
+                        self.settings.save(ctx);
+                    }
+                    None => (),
+                }
+            }
+            // Picks the opening move
+            COUNTDOWN => {
+                if let Some(dir) = input.keycode.and_then(|key| {
+                    self.settings
+                        .bindings
+                        .direction(key, self.settings.vim_keys)
+                }) {
+                    self.face(dir);
+                }
+            }
+            RESTART_PROMPT => match input.keycode {
+                Some(KeyCode::Y) => self.reset(ctx),
+                Some(KeyCode::N) => self.close_restart_prompt(),
+                _ => (),
+            },
+            // Letting ggez do the quitting shuts the window and audio down properly
+            QUIT_PROMPT => match input.keycode {
+                Some(KeyCode::Return | KeyCode::Y) => ctx.request_quit(),
+                Some(KeyCode::Escape | KeyCode::N) => self.close_quit_prompt(),
+                _ => (),
+            },
+            // Letters come in through text_input_event
+            NAME_ENTRY => match input.keycode {
+                Some(KeyCode::Back) => self.name_entry.backspace(),
+                Some(KeyCode::Return) => self.confirm_name_entry(ctx),
+                Some(KeyCode::Escape) => self.skip_name_entry(),
+                _ => (),
+            },
+            LEADERBOARD | LOAD_ERROR => {
+                if let Some(KeyCode::Return | KeyCode::Escape) = input.keycode {
+                    self.pop_screen();
+                }
+            }
+            // Only Esc skips it. Enter mashed while dying would go on to skip the replay
+            // and then pick a button on the loss screen before anyone's seen it
+            DYING if input.keycode == Some(KeyCode::Escape) => self.finish_run(ctx, GAME_LOSS),
+            GAME_LOSS if self.replay.is_some() => {
+                if let Some(KeyCode::Space | KeyCode::Return | KeyCode::Escape) = input.keycode {
+                    self.skip_replay();
+                }
+            }
+            GAME_LOSS | GAME_WIN if input.keycode == Some(KeyCode::Tab) => {
+                self.show_heatmap = !self.show_heatmap;
+            }
+            CREDITS_SCREEN | STATS_SCREEN | ACHIEVEMENTS_SCREEN
+                if input.keycode == Some(KeyCode::Escape) =>
+            {
+                self.pop_screen();
+            }
+            CONTROLS_SCREEN => match (self.capturing, input.keycode) {
+                (Some(action), Some(key)) => self.capture_key(ctx, action, key),
+                (Some(_), None) => (),
+                (None, Some(key)) => match self.menu_key(key) {
+                    KeyCode::Up | KeyCode::W => self.controls_screen.move_selection(-1),
+                    KeyCode::Down | KeyCode::S => self.controls_screen.move_selection(1),
+                    KeyCode::Return => self.activate_control(ctx, self.controls_screen.selected),
+                    KeyCode::Escape => self.pop_screen(),
+                    _ => (),
+                },
+                (None, None) => (),
+            },
+            SETTINGS_SCREEN | GAMEPAD_SCREEN | DISPLAY_SCREEN => {
+                let selected = self.stepper_screen().map_or(0, |screen| screen.selected);
+                match input.keycode.map(|key| self.menu_key(key)) {
+                    Some(KeyCode::Up | KeyCode::W) => {
+                        if let Some(screen) = self.stepper_screen_mut() {
+                            screen.move_selection(-1);
+                        }
+                    }
+                    Some(KeyCode::Down | KeyCode::S) => {
+                        if let Some(screen) = self.stepper_screen_mut() {
+                            screen.move_selection(1);
+                        }
+                    }
+                    Some(KeyCode::Left | KeyCode::A) => self.step_row(ctx, selected, -1),
+                    Some(KeyCode::Right | KeyCode::D | KeyCode::Return) => {
+                        self.step_row(ctx, selected, 1)
+                    }
+                    Some(KeyCode::Escape) => self.pop_screen(),
+                    _ => (),
+                }
+            }
+            PAUSE_MENU
+                if input
+                    .keycode
+                    .and_then(|key| self.settings.bindings.action(key))
+                    == Some(Action::Pause) =>
+            {
+                self.pause_menu.press(PAUSE_RESUME);
+            }
+            TITLE_SCREEN if input.keycode == Some(KeyCode::Tab) => {
+                self.settings.scoring = self.settings.scoring.next();
+                self.settings.save(ctx);
+                self.best_recording = GhostRecording::load(ctx, self.settings.scoring);
+            }
+            // Every menu handles its own arrow keys, Enter and shortcuts
+            _ => {
+                let key = input.keycode.map(|key| self.menu_key(key));
+                if let (Some(screen), Some(key)) = (self.option_screen_mut(), key) {
+                    screen.key_pressed(key);
+                }
+            }
+        }
+
+        if self.screen() == screen && self.menu_selection() != selection {
+            self.audio.play_sfx(ctx, Sfx::Select);
+        }
+
+        Ok(())
+    }
+
+    fn mouse_button_down_event(
+        &mut self,
+        ctx: &mut Context,
+        button: MouseButton,
+        x: f32,
+        y: f32,
+    ) -> Result<(), ggez::GameError> {
+        if button == MouseButton::Left {
+            if let Some(screen) = self.option_screen_mut() {
+                screen.mouse_down(x, y);
+            } else if self.screen() == GAME_LOSS {
+                // Only gets here while the replay is still going
+                self.skip_replay();
+            } else if [
+                CREDITS_SCREEN,
+                STATS_SCREEN,
+                ACHIEVEMENTS_SCREEN,
+                LEADERBOARD,
+                LOAD_ERROR,
+            ]
+            .contains(&self.screen())
+            {
+                self.pop_screen();
+            } else if self.screen() == CONTROLS_SCREEN {
+                // Clicking somewhere else while it's listening gives up on the new key
+                if self.capturing.take().is_none() {
+                    if let Some(row) = self.controls_screen.hit_test(x, y) {
+                        self.controls_screen.selected = row;
+                        self.activate_control(ctx, row);
+                    }
                 }
-                Some(KeyCode::Escape) => {
-                    self.win_screen.button2_clicked = true;
+                self.bind_error = None;
+            } else if let Some(screen) = self.stepper_screen_mut() {
+                if let Some(row) = screen.hit_test(x, y) {
+                    let delta = screen.click_direction(row, x);
+                    screen.selected = row;
+                    self.step_row(ctx, row, delta);
                 }
-                _ => (),
-            },
+            } else if let Some(dir) = dpad::hit_test(self.settings.dpad, x, y) {
+                // ggez turns touches into clicks, so this covers touchscreens too
+                match self.screen() {
+                    GAMEPLAY => self.steer(dir),
+                    COUNTDOWN => self.face(dir),
+                    _ => (),
+                }
+            } else if self.screen() == GAMEPLAY
+                && self.settings.control_scheme == ControlScheme::SingleSwitch
+            {
+                // Plenty of switches show up as a mouse click
+                self.steer(self.switch_preview);
+            } else if self.screen() == GAMEPLAY
+                && self.settings.mouse_mode == MouseMode::ClickToPath
+            {
+                self.start_auto_path(GridPosition::from_screen(x, y));
+            }
+        }
+
+        Ok(())
+    }
+
+    // Menu buttons fire on release, not press
+    fn mouse_button_up_event(
+        &mut self,
+        _ctx: &mut Context,
+        button: MouseButton,
+        x: f32,
+        y: f32,
+    ) -> Result<(), ggez::GameError> {
+        if button == MouseButton::Left {
+            if let Some(screen) = self.option_screen_mut() {
+                screen.mouse_up(x, y);
+            }
+            // A press that started on some other screen shouldn't carry over to this one
+            self.title_screen.pressed = None;
+            self.loss_screen.pressed = None;
+            self.win_screen.pressed = None;
+            self.pause_menu.pressed = None;
+        }
+
+        Ok(())
+    }
+
+    // Menus treat the gamepad like the keyboard, so it goes through the same key handling
+    fn gamepad_button_down_event(
+        &mut self,
+        ctx: &mut Context,
+        btn: GamepadButton,
+        _id: GamepadId,
+    ) -> Result<(), ggez::GameError> {
+        // The D-pad steers the same way the arrow keys do
+        match (self.screen(), Direction::from_gamepad_button(btn)) {
+            (GAMEPLAY, Some(dir)) => {
+                self.steer(dir);
+                return Ok(());
+            }
+            (COUNTDOWN, Some(dir)) => {
+                self.face(dir);
+                return Ok(());
+            }
             _ => (),
         }
+        if btn == GamepadButton::Start {
+            match self.screen() {
+                GAMEPLAY => {
+                    self.open_pause_menu();
+                    return Ok(());
+                }
+                PAUSE_MENU => {
+                    self.pause_menu.press(PAUSE_RESUME);
+                    return Ok(());
+                }
+                _ => (),
+            }
+        }
+        if self.screen() == QUIT_PROMPT {
+            match btn {
+                GamepadButton::South => ctx.request_quit(),
+                GamepadButton::East => self.close_quit_prompt(),
+                _ => (),
+            }
+            return Ok(());
+        }
+        if let LEADERBOARD | LOAD_ERROR = self.screen() {
+            if let GamepadButton::South | GamepadButton::East = btn {
+                self.pop_screen();
+            }
+            return Ok(());
+        }
+        // There's no typing on a gamepad, so confirming just takes the default name
+        if self.screen() == NAME_ENTRY {
+            match btn {
+                GamepadButton::South => self.confirm_name_entry(ctx),
+                GamepadButton::East => self.skip_name_entry(),
+                _ => (),
+            }
+            return Ok(());
+        }
+
+        let key = match btn {
+            GamepadButton::DPadUp | GamepadButton::DPadLeft => Some(KeyCode::Up),
+            GamepadButton::DPadDown | GamepadButton::DPadRight => Some(KeyCode::Down),
+            GamepadButton::South => Some(KeyCode::Return),
+            // Start works as confirm on the title screen so it can start the game
+            GamepadButton::Start if self.screen() == TITLE_SCREEN => Some(KeyCode::Return),
+            _ => None,
+        };
+
+        if let (Some(screen), Some(key)) = (self.option_screen_mut(), key) {
+            screen.key_pressed(key);
+        }
+
+        Ok(())
+    }
+
+    fn gamepad_axis_event(
+        &mut self,
+        _ctx: &mut Context,
+        axis: Axis,
+        value: f32,
+        _id: GamepadId,
+    ) -> Result<(), ggez::GameError> {
+        match axis {
+            Axis::LeftStickX => self.stick.0 = value,
+            Axis::LeftStickY => self.stick.1 = value,
+            _ => return Ok(()),
+        }
+
+        let (x, y) = self.settings.stick(self.stick);
+        let dir = Direction::from_stick(x, y, self.settings.stick_deadzone as f32 / 100.0);
+        if dir == self.stick_dir {
+            return Ok(());
+        }
+        self.stick_dir = dir;
+        if let Some(dir) = dir {
+            match self.screen() {
+                GAMEPLAY => self.steer(dir),
+                COUNTDOWN => self.face(dir),
+                _ => (),
+            }
+        }
+
+        Ok(())
+    }
+
+    // Touches still act as clicks, which covers the menus and the D-pad. On the board a drag
+    // also steers, turning as soon as it's gone far enough so a swipe feels instant
+    fn touch_event(
+        &mut self,
+        ctx: &mut Context,
+        phase: TouchPhase,
+        x: f64,
+        y: f64,
+    ) -> Result<(), ggez::GameError> {
+        let (x, y) = (x as f32, y as f32);
+        ctx.mouse.handle_move(x, y);
+
+        match phase {
+            TouchPhase::Started => {
+                let on_dpad = dpad::hit_test(self.settings.dpad, x, y).is_some();
+                self.swipe_from = (!on_dpad).then_some(Point2 { x, y });
+                self.mouse_button_down_event(ctx, MouseButton::Left, x, y)?;
+            }
+            TouchPhase::Moved => {
+                if let Some(from) = self.swipe_from {
+                    let (dx, dy) = (x - from.x, y - from.y);
+                    let threshold = self.settings.swipe_distance as f32;
+                    if dx.hypot(dy) >= threshold {
+                        // The screen's y goes down, so it's flipped to match
+                        let dir = Direction::dominant(dx, -dy);
+                        match self.screen() {
+                            GAMEPLAY => self.steer(dir),
+                            COUNTDOWN => self.face(dir),
+                            _ => (),
+                        }
+                        self.swipe_from = Some(Point2 { x, y });
+                    }
+                }
+                let diff = ctx.mouse.last_delta();
+                self.mouse_motion_event(ctx, x, y, diff.x, diff.y)?;
+            }
+            TouchPhase::Ended | TouchPhase::Cancelled => {
+                self.swipe_from = None;
+                self.mouse_button_up_event(ctx, MouseButton::Left, x, y)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    // Clicking away from the window mid-game pauses it rather than letting the snake die
+    // unwatched
+    fn focus_event(&mut self, _ctx: &mut Context, gained: bool) -> Result<(), ggez::GameError> {
+        if !gained && self.screen() == GAMEPLAY {
+            self.open_pause_menu();
+        }
+
+        Ok(())
+    }
+
+    fn text_input_event(
+        &mut self,
+        _ctx: &mut Context,
+        character: char,
+    ) -> Result<(), ggez::GameError> {
+        if self.screen() == NAME_ENTRY {
+            self.name_entry.type_char(character);
+        }
+
+        Ok(())
+    }
+
+    fn mouse_motion_event(
+        &mut self,
+        _ctx: &mut Context,
+        x: f32,
+        y: f32,
+        _dx: f32,
+        _dy: f32,
+    ) -> Result<(), ggez::GameError> {
+        self.mouse_pos = Point2 { x, y };
+        self.refresh_hover();
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ButtonLayout {
+    // Side by side under the title
+    Horizontal,
+    // Stacked on top of each other under the title
+    Vertical,
+}
+
+struct Button {
+    rect: Rect,
+    label: Text,
+    clicked: bool,
+    shortcut: Option<KeyCode>,
+}
+
+struct OptionScreen {
+    title: Text,
+    buttons: Vec<Button>,
+    // Which button the mouse is over
+    hovered: Option<usize>,
+    // Which button the mouse went down on. It only fires if the mouse comes back up over it
+    pressed: Option<usize>,
+    // Screens that can be driven with the arrow keys keep track of a selected button
+    selected: Option<usize>,
+    colors: &'static MenuColors,
+    _vertical_center: Rect,
+    _horizontal_center: Rect,
+}
+
+impl OptionScreen {
+    // Enter presses the first button and Escape the last one, like the original two button screens
+    fn new(title: &str, labels: &[String], layout: ButtonLayout) -> Self {
+        let mut title = Text::new(title);
+        title.set_scale(32.0);
+
+        let center_x = SCREEN_SIZE.0 / 2.0;
+        let center_y = SCREEN_SIZE.1 / 2.0;
+
+        let button_padding = SCREEN_SIZE.0 * 0.052;
+        let button_height = SCREEN_SIZE.1 / 10.0;
+        let count = labels.len() as f32;
+
+        let rects: Vec<Rect> = match layout {
+            ButtonLayout::Horizontal => {
+                let button_width = SCREEN_SIZE.0 / 8.0;
+                // Gaps shrink when there are too many buttons to fit across at the usual spacing
+                let gap = f32::min(
+                    2.0 * button_padding,
+                    (SCREEN_SIZE.0 - count * button_width) / (count + 1.0),
+                );
+                let row_width = count * button_width + (count - 1.0) * gap;
+                (0..labels.len())
+                    .map(|i| {
+                        Rect::new(
+                            center_x - row_width / 2.0 + i as f32 * (button_width + gap),
+                            center_y + button_padding,
+                            button_width,
+                            button_height,
+                        )
+                    })
+                    .collect()
+            }
+            ButtonLayout::Vertical => {
+                let button_width = SCREEN_SIZE.0 / 4.0;
+                let gap = button_height / 4.0;
+                (0..labels.len())
+                    .map(|i| {
+                        Rect::new(
+                            center_x - button_width / 2.0,
+                            center_y - button_padding + i as f32 * (button_height + gap),
+                            button_width,
+                            button_height,
+                        )
+                    })
+                    .collect()
+            }
+        };
+
+        let last = labels.len().saturating_sub(1);
+        let buttons = labels
+            .iter()
+            .zip(rects)
+            .enumerate()
+            .map(|(i, (label, rect))| {
+                let mut label = Text::new(label.as_str());
+                label.set_scale(20.0);
+                let shortcut = if i == 0 {
+                    Some(KeyCode::Return)
+                } else if i == last {
+                    Some(KeyCode::Escape)
+                } else {
+                    None
+                };
+                Button {
+                    rect,
+                    label,
+                    clicked: false,
+                    shortcut,
+                }
+            })
+            .collect();
+
+        let vertical_center = Rect::new(center_x, 0.0, 1.0, SCREEN_SIZE.1);
+        let horizontal_center = Rect::new(0.0, center_y, SCREEN_SIZE.0, 1.0);
+
+        OptionScreen {
+            title,
+            buttons,
+            hovered: None,
+            pressed: None,
+            selected: None,
+            colors: Theme::Classic.menu_colors(),
+            _vertical_center: vertical_center,
+            _horizontal_center: horizontal_center,
+        }
+    }
+
+    // Turns on arrow key selection, starting on the first button
+    fn with_selection(mut self) -> Self {
+        self.selected = Some(0);
+        self
+    }
+
+    fn with_shortcut(mut self, index: usize, shortcut: Option<KeyCode>) -> Self {
+        self.buttons[index].shortcut = shortcut;
+        self
+    }
+
+    fn with_colors(mut self, colors: &'static MenuColors) -> Self {
+        self.colors = colors;
+        self
+    }
+
+    // Bigger text, so it's easier to read. The colors come from with_colors
+    fn with_high_contrast(mut self, high_contrast: bool) -> Self {
+        if high_contrast {
+            self.title.set_scale(32.0 * HIGH_CONTRAST_TEXT_SCALE);
+            for button in &mut self.buttons {
+                button.label.set_scale(20.0 * HIGH_CONTRAST_TEXT_SCALE);
+            }
+        }
+        self
+    }
+
+    // The one place that works out which button (if any) is under the cursor
+    fn hit_test(&self, x: f32, y: f32) -> Option<usize> {
+        self.buttons
+            .iter()
+            .position(|button| button.rect.contains(Point2 { x, y }))
+    }
+
+    fn press(&mut self, index: usize) {
+        if let Some(button) = self.buttons.get_mut(index) {
+            button.clicked = true;
+        }
+    }
+
+    fn mouse_down(&mut self, x: f32, y: f32) {
+        self.pressed = self.hit_test(x, y);
+        if let (Some(_), Some(i)) = (self.selected, self.pressed) {
+            self.selected = Some(i);
+        }
+    }
+
+    // Dragging off the button before letting go cancels the click
+    fn mouse_up(&mut self, x: f32, y: f32) {
+        if let Some(i) = self.pressed.take() {
+            if self.hit_test(x, y) == Some(i) {
+                self.press(i);
+            }
+        }
+    }
+
+    fn update_hover(&mut self, x: f32, y: f32) {
+        self.hovered = self.hit_test(x, y);
+        // The mouse and the keyboard share one selection so they never disagree
+        if let (Some(_), Some(hovered)) = (self.selected, self.hovered) {
+            self.selected = Some(hovered);
+        }
+    }
+
+    // Returns true if the key did something on this screen
+    fn key_pressed(&mut self, key: KeyCode) -> bool {
+        if let Some(selected) = self.selected {
+            match key {
+                KeyCode::Return => {
+                    self.press(selected);
+                    return true;
+                }
+                KeyCode::Up | KeyCode::Left | KeyCode::W => {
+                    self.move_selection(-1);
+                    return true;
+                }
+                KeyCode::Down | KeyCode::Right | KeyCode::S => {
+                    self.move_selection(1);
+                    return true;
+                }
+                _ => (),
+            }
+        }
+        match self
+            .buttons
+            .iter()
+            .position(|button| button.shortcut == Some(key))
+        {
+            Some(i) => {
+                self.press(i);
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn reset_selection(&mut self) {
+        if self.selected.is_some() {
+            self.selected = Some(0);
+        }
+    }
+
+    fn move_selection(&mut self, delta: isize) {
+        if let Some(selected) = self.selected {
+            let last = self.buttons.len() as isize - 1;
+            self.selected = Some((selected as isize + delta).clamp(0, last) as usize);
+        }
+    }
+
+    // Hands back the button that was clicked (if any) and clears every click
+    fn take_clicked(&mut self) -> Option<usize> {
+        let clicked = self.buttons.iter().position(|button| button.clicked);
+        for button in &mut self.buttons {
+            button.clicked = false;
+        }
+        clicked
+    }
+
+    // Hovered buttons are drawn a bit bigger and brighter, and held down ones sink in and darken
+    fn button_look(&self, index: usize) -> (Rect, Color) {
+        let rect = self.buttons[index].rect;
+        if self.pressed == Some(index) && self.hovered == Some(index) {
+            (grow_rect(rect, -BUTTON_PRESS_INSET), self.colors.pressed)
+        } else if self.hovered == Some(index) {
+            (grow_rect(rect, BUTTON_HOVER_GROWTH), self.colors.hover)
+        } else {
+            (rect, self.colors.button)
+        }
+    }
+
+    fn draw(&self, canvas: &mut graphics::Canvas, ctx: &mut Context) -> GameResult {
+        draw_centered_text(
+            canvas,
+            ctx,
+            &self.title,
+            SCREEN_SIZE.1 / 2.0 - (SCREEN_SIZE.1 * 0.15625),
+            self.colors.text,
+        )?;
+
+        for (i, button) in self.buttons.iter().enumerate() {
+            let (rect, color) = self.button_look(i);
+
+            if self.selected == Some(i) {
+                canvas.draw(
+                    &graphics::Quad,
+                    graphics::DrawParam::new()
+                        .dest_rect(grow_rect(rect, 4.0))
+                        .color(self.colors.highlight),
+                );
+            }
+
+            canvas.draw(
+                &graphics::Quad,
+                graphics::DrawParam::new().dest_rect(rect).color(color),
+            );
+
+            draw_button_label(canvas, ctx, &button.label, rect, self.colors.label)?;
+        }
+
+        // canvas.draw(
+        //     &graphics::Quad,
+        //     graphics::DrawParam::new()
+        //         .dest_rect(self._horizontal_center)
+        //         .color(Color::GREEN),
+        // );
+        // canvas.draw(
+        //     &graphics::Quad,
+        //     graphics::DrawParam::new()
+        //         .dest_rect(self._vertical_center)
+        //         .color(Color::GREEN),
+        // );
+
+        Ok(())
+    }
+}
+
+impl EventHandler for OptionScreen {
+    fn update(&mut self, _ctx: &mut Context) -> Result<(), ggez::GameError> {
+        self.take_clicked();
+
+        Ok(())
+    }
+
+    fn draw(&mut self, _ctx: &mut Context) -> Result<(), ggez::GameError> {
+        Ok(())
+    }
+}
+
+const TITLE_START: usize = 0;
+const TITLE_SETTINGS: usize = 1;
+const TITLE_LEADERBOARD: usize = 2;
+const TITLE_STATS: usize = 3;
+const TITLE_ACHIEVEMENTS: usize = 4;
+const TITLE_CREDITS: usize = 5;
+
+const PAUSE_RESUME: usize = 0;
+const PAUSE_RESTART: usize = 1;
+const PAUSE_QUIT: usize = 2;
+
+// Reduced motion goes first so it's one click (or Enter) away once settings is open
+const SETTING_REDUCE_MOTION: usize = 0;
+const SETTING_MUSIC_VOLUME: usize = 1;
+const SETTING_SFX_VOLUME: usize = 2;
+const SETTING_TICK_SOUND: usize = 3;
+const SETTING_SPEED: usize = 4;
+const SETTING_WALLS: usize = 5;
+const SETTING_THEME: usize = 6;
+const SETTING_FADES: usize = 7;
+const SETTING_LANGUAGE: usize = 8;
+const SETTING_PALETTE: usize = 9;
+const SETTING_HIGH_CONTRAST: usize = 10;
+const SETTING_DPAD: usize = 11;
+const SETTING_RESPONSIVE_TURNS: usize = 12;
+const SETTING_MOUSE_MODE: usize = 13;
+const SETTING_CONTROL_SCHEME: usize = 14;
+const SETTING_CONTROLS: usize = 15;
+const SETTING_GAMEPAD: usize = 16;
+const SETTING_DISPLAY: usize = 17;
+const SETTING_BACK: usize = 18;
+const SETTING_ROWS: usize = SETTING_BACK + 1;
+
+// The controls screen has a row per Action, then these two
+const CONTROLS_VIM_KEYS: usize = Action::ALL.len();
+const CONTROLS_DASH: usize = CONTROLS_VIM_KEYS + 1;
+const CONTROLS_RESET: usize = CONTROLS_DASH + 1;
+const CONTROLS_BACK: usize = CONTROLS_RESET + 1;
+const CONTROLS_ROWS: usize = CONTROLS_BACK + 1;
+
+const GAMEPAD_DEADZONE: usize = 0;
+const GAMEPAD_SWAP_AXES: usize = 1;
+const GAMEPAD_INVERT_X: usize = 2;
+const GAMEPAD_INVERT_Y: usize = 3;
+const GAMEPAD_BACK: usize = 4;
+const GAMEPAD_ROWS: usize = GAMEPAD_BACK + 1;
+
+const DISPLAY_SMOOTH_MOVEMENT: usize = 0;
+const DISPLAY_GRID_LINES: usize = 1;
+const DISPLAY_FADING_TRAIL: usize = 2;
+const DISPLAY_DAY_NIGHT: usize = 3;
+const DISPLAY_BACK: usize = 4;
+const DISPLAY_ROWS: usize = DISPLAY_BACK + 1;
+// The stick visualizer on the gamepad screen
+const STICK_VIEW_RADIUS: f32 = 80.0;
+
+// A list of rows, each one a setting whose value is changed with Left/Right
+// (or by clicking the left/right half of the row), with a Back row at the bottom
+struct SettingsScreen {
+    title: Text,
+    rows: Vec<Rect>,
+    selected: usize,
+    hovered: Option<usize>,
+    // Whether the rows (but not Back) get arrows to show they can be stepped through
+    arrows: bool,
+    high_contrast: bool,
+    colors: &'static MenuColors,
+}
+
+impl SettingsScreen {
+    fn new(
+        title: &str,
+        rows: usize,
+        arrows: bool,
+        high_contrast: bool,
+        colors: &'static MenuColors,
+    ) -> Self {
+        let mut title = Text::new(title);
+        title.set_scale(if high_contrast {
+            32.0 * HIGH_CONTRAST_TEXT_SCALE
+        } else {
+            32.0
+        });
+
+        // Rows get squeezed together once there are too many to fit at their full height
+        let row_width = SCREEN_SIZE.0 / 2.0;
+        let top = 110.0;
+        let gap = 6.0;
+        let pitch = f32::min(38.0, (SCREEN_SIZE.1 - top - 8.0) / rows as f32);
+        let row_height = pitch - gap;
+        let rows = (0..rows)
+            .map(|i| {
+                Rect::new(
+                    SCREEN_SIZE.0 / 2.0 - row_width / 2.0,
+                    top + i as f32 * pitch,
+                    row_width,
+                    row_height,
+                )
+            })
+            .collect();
+
+        SettingsScreen {
+            title,
+            rows,
+            selected: 0,
+            hovered: None,
+            arrows,
+            high_contrast,
+            colors,
+        }
+    }
 
-        Ok(())
+    // Where the last row ends, for anything drawn underneath
+    fn bottom(&self) -> f32 {
+        self.rows.last().map_or(0.0, |row| row.bottom())
     }
 
-    fn mouse_button_down_event(
-        &mut self,
-        _ctx: &mut Context,
-        button: MouseButton,
-        x: f32,
-        y: f32,
-    ) -> Result<(), ggez::GameError> {
-        if button == MouseButton::Left {
-            match self.game_state {
-                TITLE_SCREEN => {
-                    if self.title_screen.button1.contains(Point2 { x, y }) {
-                        self.title_screen.button1_clicked = true;
-                    }
+    fn hit_test(&self, x: f32, y: f32) -> Option<usize> {
+        self.rows
+            .iter()
+            .position(|row| row.contains(Point2 { x, y }))
+    }
 
-                    if self.title_screen.button2.contains(Point2 { x, y }) {
-                        self.title_screen.button2_clicked = true;
-                    }
-                }
-                GAME_LOSS => {
-                    if self.loss_screen.button1.contains(Point2 { x, y }) {
-                        self.loss_screen.button1_clicked = true;
-                    }
+    // Which way a click on a row changes it: the left half goes down, the right half goes up
+    fn click_direction(&self, row: usize, x: f32) -> i32 {
+        let rect = self.rows[row];
+        if x < rect.x + rect.w / 2.0 {
+            -1
+        } else {
+            1
+        }
+    }
 
-                    if self.loss_screen.button2.contains(Point2 { x, y }) {
-                        self.loss_screen.button2_clicked = true;
-                    }
-                }
-                GAME_WIN => {
-                    if self.win_screen.button1.contains(Point2 { x, y }) {
-                        self.win_screen.button1_clicked = true;
-                    }
+    fn update_hover(&mut self, x: f32, y: f32) {
+        self.hovered = self.hit_test(x, y);
+        if let Some(hovered) = self.hovered {
+            self.selected = hovered;
+        }
+    }
 
-                    if self.win_screen.button2.contains(Point2 { x, y }) {
-                        self.win_screen.button2_clicked = true;
-                    }
-                }
-                _ => (),
+    fn move_selection(&mut self, delta: isize) {
+        self.selected =
+            (self.selected as isize + delta).clamp(0, self.rows.len() as isize - 1) as usize;
+    }
+
+    fn draw(
+        &self,
+        canvas: &mut graphics::Canvas,
+        ctx: &mut Context,
+        labels: &[String],
+    ) -> GameResult {
+        draw_centered_text(canvas, ctx, &self.title, 60.0, self.colors.text)?;
+
+        for (i, (row, label)) in self.rows.iter().zip(labels).enumerate() {
+            if i == self.selected {
+                canvas.draw(
+                    &graphics::Quad,
+                    graphics::DrawParam::new()
+                        .dest_rect(grow_rect(*row, 4.0))
+                        .color(self.colors.highlight),
+                );
             }
+
+            let color = if self.hovered == Some(i) {
+                self.colors.hover
+            } else {
+                self.colors.button
+            };
+            canvas.draw(
+                &graphics::Quad,
+                graphics::DrawParam::new().dest_rect(*row).color(color),
+            );
+
+            // Everything but Back shows arrows so it's obvious the value can be changed
+            let text = if !self.arrows || i == self.rows.len() - 1 {
+                label.clone()
+            } else {
+                format!("<  {}  >", label)
+            };
+            let mut text = Text::new(text);
+            text.set_scale(if self.high_contrast {
+                20.0 * HIGH_CONTRAST_TEXT_SCALE
+            } else {
+                20.0
+            });
+            draw_button_label(canvas, ctx, &text, *row, self.colors.label)?;
         }
 
         Ok(())
     }
 }
 
-struct OptionScreen {
-    title: Text,
-    button1: Rect,
-    button2: Rect,
-    button1_text: Text,
-    button2_text: Text,
-    button1_clicked: bool,
-    button2_clicked: bool,
-    _vertical_center: Rect,
-    _horizontal_center: Rect,
+const BUTTON_HOVER_GROWTH: f32 = 4.0;
+// How much bigger menu text gets in high contrast mode
+const HIGH_CONTRAST_TEXT_SCALE: f32 = 1.2;
+const BUTTON_PRESS_INSET: f32 = 3.0;
+
+// A triangle filling the rect, pointing dir
+fn arrow_points(rect: Rect, dir: Direction) -> [[f32; 2]; 3] {
+    let (left, right, top, bottom) = (rect.x, rect.x + rect.w, rect.y, rect.y + rect.h);
+    let (center_x, center_y) = (rect.x + rect.w / 2.0, rect.y + rect.h / 2.0);
+    match dir {
+        Direction::Up => [[left, bottom], [right, bottom], [center_x, top]],
+        Direction::Down => [[left, top], [right, top], [center_x, bottom]],
+        Direction::Left => [[right, top], [right, bottom], [left, center_y]],
+        Direction::Right => [[left, top], [left, bottom], [right, center_y]],
+    }
 }
 
-impl OptionScreen {
-    fn new(title: &str, button1_text: &str, button2_text: &str) -> Self {
-        let mut title = Text::new(title);
-        title.set_scale(32.0);
+// A crossed out speaker in the top right corner, under where the splits go
+fn draw_muted_icon(
+    canvas: &mut graphics::Canvas,
+    ctx: &Context,
+    colors: &MenuColors,
+) -> GameResult {
+    let (x, y, size) = (SCREEN_SIZE.0 - 40.0, 40.0, 24.0);
+    let speaker = graphics::Mesh::new_polygon(
+        ctx,
+        graphics::DrawMode::fill(),
+        &[
+            [x, y + size * 0.35],
+            [x + size * 0.25, y + size * 0.35],
+            [x + size * 0.55, y],
+            [x + size * 0.55, y + size],
+            [x + size * 0.25, y + size * 0.65],
+            [x, y + size * 0.65],
+        ],
+        colors.text,
+    )?;
+    canvas.draw(&speaker, graphics::DrawParam::new());
 
-        let center_x = SCREEN_SIZE.0 / 2.0;
-        let center_y = SCREEN_SIZE.1 / 2.0;
+    let cross = [
+        [[x + size * 0.7, y + size * 0.3], [x + size, y + size * 0.7]],
+        [[x + size * 0.7, y + size * 0.7], [x + size, y + size * 0.3]],
+    ];
+    for line in cross {
+        let line = graphics::Mesh::new_line(ctx, &line, 3.0, colors.warning)?;
+        canvas.draw(&line, graphics::DrawParam::new());
+    }
 
-        let button_width = SCREEN_SIZE.0 / 8.0;
-        let button_height = SCREEN_SIZE.1 / 10.0;
-        let button_padding = SCREEN_SIZE.0 * 0.052;
+    Ok(())
+}
 
-        let button1 = Rect::new(
-            center_x - button_width - button_padding,
-            center_y + button_padding,
-            button_width,
-            button_height,
-        );
-        let button2 = Rect::new(
-            center_x + button_padding,
-            center_y + button_padding,
-            button_width,
-            button_height,
-        );
-        let mut button1_text = Text::new(button1_text);
-        let mut button2_text = Text::new(button2_text);
+// The same rect but bigger by amount on every side
+fn grow_rect(rect: Rect, amount: f32) -> Rect {
+    Rect::new(
+        rect.x - amount,
+        rect.y - amount,
+        rect.w + 2.0 * amount,
+        rect.h + 2.0 * amount,
+    )
+}
 
-        button1_text.set_scale(20.0);
-        button2_text.set_scale(20.0);
+// Space kept clear between a button's label and its edges
+const BUTTON_LABEL_MARGIN: f32 = 8.0;
 
-        let vertical_center = Rect::new(center_x, 0.0, 1.0, SCREEN_SIZE.1);
-        let horizontal_center = Rect::new(0.0, center_y, SCREEN_SIZE.0, 1.0);
+// Centers the label inside the button using its measured size, and shrinks it
+// if it's too long to fit so it never spills over the edge of the button
+fn draw_button_label(
+    canvas: &mut graphics::Canvas,
+    ctx: &mut Context,
+    label: &Text,
+    button: Rect,
+    color: Color,
+) -> GameResult {
+    let measurements = label.measure(ctx)?;
+    let fit = ((button.w - 2.0 * BUTTON_LABEL_MARGIN) / measurements.x).min(1.0);
 
-        OptionScreen {
-            title,
-            button1,
-            button2,
-            button1_text,
-            button2_text,
-            button1_clicked: false,
-            button2_clicked: false,
-            _vertical_center: vertical_center,
-            _horizontal_center: horizontal_center,
+    canvas.draw(
+        label,
+        graphics::DrawParam::new()
+            .dest(Point2 {
+                x: button.x + button.w / 2.0 - measurements.x * fit / 2.0,
+                y: button.y + button.h / 2.0 - measurements.y * fit / 2.0,
+            })
+            .scale([fit, fit])
+            .color(color),
+    );
+    Ok(())
+}
+
+// A dark box with a single line of text in the middle of the screen
+fn draw_prompt_box(
+    canvas: &mut graphics::Canvas,
+    ctx: &mut Context,
+    text: &str,
+    colors: &MenuColors,
+) -> GameResult {
+    let mut prompt = Text::new(text);
+    prompt.set_scale(32.0);
+    let measurements = prompt.measure(ctx)?;
+    let padding = 16.0;
+    canvas.draw(
+        &graphics::Quad,
+        graphics::DrawParam::new()
+            .dest_rect(Rect::new(
+                SCREEN_SIZE.0 / 2.0 - measurements.x / 2.0 - padding,
+                SCREEN_SIZE.1 / 2.0 - measurements.y / 2.0 - padding,
+                measurements.x + 2.0 * padding,
+                measurements.y + 2.0 * padding,
+            ))
+            .color(Color {
+                a: 0.8,
+                ..colors.shade
+            }),
+    );
+    draw_centered_text(
+        canvas,
+        ctx,
+        &prompt,
+        SCREEN_SIZE.1 / 2.0 - measurements.y / 2.0,
+        colors.text,
+    )
+}
+
+// What the menus take a key as. The confirm binding is Enter wherever it's been moved to
+fn translate_menu_key(bindings: &Bindings, key: KeyCode) -> KeyCode {
+    if bindings.action(key) == Some(Action::Confirm) {
+        return KeyCode::Return;
+    }
+    // The numpad arrows move through menus the same as the real ones
+    match key {
+        KeyCode::Numpad8 => KeyCode::Up,
+        KeyCode::Numpad2 => KeyCode::Down,
+        KeyCode::Numpad4 => KeyCode::Left,
+        KeyCode::Numpad6 => KeyCode::Right,
+        _ => key,
+    }
+}
+
+// Holding a key down only counts the first press. The arrows in menus are the exception,
+// so holding one scrolls or keeps stepping a setting. Anything else would go on acting on
+// whatever its first press opened, like a held pause key unpausing straight away
+fn takes_repeat(bindings: &Bindings, screen: u8, key: KeyCode) -> bool {
+    !matches!(screen, GAMEPLAY | COUNTDOWN)
+        && bindings.action(key) != Some(Action::Pause)
+        && matches!(
+            translate_menu_key(bindings, key),
+            KeyCode::Up | KeyCode::Down | KeyCode::Left | KeyCode::Right
+        )
+}
+
+// The keys that do the same thing in the game whatever else is going on
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum GameplayKey {
+    Pause,
+    Steer(Direction),
+}
+
+fn gameplay_key(bindings: &Bindings, vim_keys: bool, key: KeyCode) -> Option<GameplayKey> {
+    if key == KeyCode::Escape || bindings.action(key) == Some(Action::Pause) {
+        return Some(GameplayKey::Pause);
+    }
+    bindings.direction(key, vim_keys).map(GameplayKey::Steer)
+}
+
+// A snake somewhere random heading a random way, and its first food
+fn new_board(rng: &mut Rand32) -> (Snake, Food) {
+    let snake_pos = GridPosition::random(rng, GRID_SIZE.0, GRID_SIZE.1);
+    let snake = Snake::new(snake_pos, Direction::random_direction(rng));
+    let food = Food::new(snake.get_food_space(rng));
+    (snake, food)
+}
+
+// Draws the text centered horizontally on the screen with its top edge at y. Any part of
+// the text with a color of its own keeps it
+fn draw_centered_text(
+    canvas: &mut graphics::Canvas,
+    ctx: &mut Context,
+    text: &Text,
+    y: f32,
+    color: Color,
+) -> GameResult {
+    let width = text.measure(ctx)?.x;
+    canvas.draw(
+        text,
+        graphics::DrawParam::new()
+            .dest(Point2 {
+                x: SCREEN_SIZE.0 / 2.0 - width / 2.0,
+                y,
+            })
+            .color(color),
+    );
+    Ok(())
+}
+
+// As m:ss.t
+fn format_time(time: std::time::Duration) -> String {
+    let tenths = time.as_millis() / 100;
+    format!("{}:{:02}.{}", tenths / 600, tenths / 10 % 60, tenths % 10)
+}
+
+// Text for one split, e.g. "150: 1:02.3 (-0.4)"
+fn split_text(result: &SplitResult, scale: f32, colors: &MenuColors) -> Text {
+    let delta = match result.delta {
+        Some(delta) => {
+            let tenths = delta / 100;
+            let sign = if tenths < 0 { '-' } else { '+' };
+            format!(" ({}{}.{})", sign, tenths.abs() / 10, tenths.abs() % 10)
+        }
+        None => String::new(),
+    };
+
+    let mut text = Text::new(format!(
+        "{}: {}{}",
+        result.length(),
+        format_time(result.time),
+        delta
+    ));
+    text.set_scale(scale);
+    text.fragments_mut()[0].color = Some(if result.gold() {
+        colors.highlight
+    } else {
+        colors.text
+    });
+    text
+}
+
+// Every other cell of the board in one mesh, rather than hundreds of separate quads a frame
+fn checkerboard(ctx: &Context) -> graphics::Mesh {
+    let cells = (0..GRID_SIZE.0)
+        .flat_map(|x| (0..GRID_SIZE.1).map(move |y| GridPosition::new(x, y)))
+        .filter(|pos| (pos.x + pos.y) % 2 == 1)
+        .map(Rect::from);
+    rects_mesh(ctx, cells)
+}
+
+// A pixel wide line along every edge between two cells
+fn grid_lines(ctx: &Context) -> graphics::Mesh {
+    let (w, h) = (GRID_CELL_SIZE.0 as f32, GRID_CELL_SIZE.1 as f32);
+    let columns = (1..GRID_SIZE.0).map(|x| Rect::new(x as f32 * w - 0.5, 0.0, 1.0, SCREEN_SIZE.1));
+    let rows = (1..GRID_SIZE.1).map(|y| Rect::new(0.0, y as f32 * h - 0.5, SCREEN_SIZE.0, 1.0));
+    rects_mesh(ctx, columns.chain(rows))
+}
+
+// A white mesh of filled rects, to be tinted whatever color when it's drawn
+fn rects_mesh(ctx: &Context, rects: impl Iterator<Item = Rect>) -> graphics::Mesh {
+    let mut vertices = Vec::new();
+    let mut indices = Vec::new();
+    for rect in rects {
+        let first = vertices.len() as u32;
+        for position in [
+            [rect.left(), rect.top()],
+            [rect.right(), rect.top()],
+            [rect.right(), rect.bottom()],
+            [rect.left(), rect.bottom()],
+        ] {
+            vertices.push(graphics::Vertex {
+                position,
+                uv: [0.0, 0.0],
+                color: [1.0, 1.0, 1.0, 1.0],
+            });
         }
+        indices.extend([first, first + 1, first + 2, first, first + 2, first + 3]);
     }
+    graphics::Mesh::from_data(
+        ctx,
+        graphics::MeshData {
+            vertices: &vertices,
+            indices: &indices,
+        },
+    )
+}
 
-    fn draw(&self, canvas: &mut graphics::Canvas, ctx: &mut Context) -> GameResult {
-        let title_width = self.title.measure(ctx)?.x;
-        canvas.draw(
-            &self.title,
-            Point2 {
-                x: SCREEN_SIZE.0 / 2.0 - title_width / 2.0,
-                y: SCREEN_SIZE.1 / 2.0 - (SCREEN_SIZE.1 * 0.15625),
-            },
-        );
+// A border drawn just inside the cell, so neighbouring cells' borders don't overlap
+fn draw_cell_border(
+    canvas: &mut graphics::Canvas,
+    ctx: &Context,
+    cell: Rect,
+    color: Color,
+    width: f32,
+) -> GameResult {
+    let outline = graphics::Mesh::new_rectangle(
+        ctx,
+        graphics::DrawMode::stroke(width),
+        grow_rect(cell, -width / 2.0),
+        color,
+    )?;
+    canvas.draw(&outline, graphics::DrawParam::new());
+    Ok(())
+}
 
-        canvas.draw(
-            &graphics::Quad,
-            graphics::DrawParam::new()
-                .dest_rect(self.button1)
-                .color(Color::WHITE),
-        );
+// Which sides of the segment at index i along the snake touch the segments either side of
+// it. Those are the sides it's joined up across
+fn joined_sides(chain: &[GridPosition], i: usize) -> Vec<Direction> {
+    let neighbours = [i.checked_sub(1), Some(i + 1)];
+    neighbours
+        .into_iter()
+        .flatten()
+        .filter_map(|j| chain.get(j))
+        .filter_map(|&other| chain[i].direction_to(other))
+        .collect()
+}
 
-        let button1_text_measurements = self.button1_text.measure(ctx)?;
-        let button1_center = Point2 {
-            x: self.button1.x + self.button1.w / 2.0 - button1_text_measurements.x / 2.0,
-            y: self.button1.y + self.button1.h / 2.0 - button1_text_measurements.y / 2.0,
-        };
+// A segment sits a little inside its cell, except on the sides it's joined to the next one
+// across, so the snake reads as one shape rather than a row of tiles
+fn segment_rect(cell: Rect, joins: &[Direction]) -> Rect {
+    let mut rect = grow_rect(cell, -SEGMENT_GAP);
+    for dir in joins {
+        match dir {
+            Direction::Up => {
+                rect.h += rect.y - cell.y;
+                rect.y = cell.y;
+            }
+            Direction::Down => rect.h = cell.bottom() - rect.y,
+            Direction::Left => {
+                rect.w += rect.x - cell.x;
+                rect.x = cell.x;
+            }
+            Direction::Right => rect.w = cell.right() - rect.x,
+        }
+    }
+    rect
+}
 
+// The head and tail are joined on one side at most, so they get rounded off into end caps.
+// The joined side is squared back up so it still meets the next segment flush. A segment
+// in the middle stays square, since rounding it would notch the snake wherever a free side
+// meets a joined one
+fn draw_segment(
+    canvas: &mut graphics::Canvas,
+    cell: Rect,
+    joins: &[Direction],
+    color: Color,
+    sprites: &Sprites,
+) {
+    let rect = segment_rect(cell, joins);
+    if joins.len() >= 2 {
         canvas.draw(
-            &self.button1_text,
-            graphics::DrawParam::new()
-                .dest(button1_center)
-                .color(Color::BLACK),
+            &graphics::Quad,
+            graphics::DrawParam::new().dest_rect(rect).color(color),
         );
+        return;
+    }
 
+    sprites::draw_shape(canvas, &sprites.cap, cell, color);
+    let cap = grow_rect(cell, -SEGMENT_GAP);
+    let center = cap.center();
+    for dir in joins {
+        let square = match dir {
+            Direction::Up => Rect::new(cap.x, rect.y, cap.w, center.y - rect.y),
+            Direction::Down => Rect::new(cap.x, center.y, cap.w, rect.bottom() - center.y),
+            Direction::Left => Rect::new(rect.x, cap.y, center.x - rect.x, cap.h),
+            Direction::Right => Rect::new(center.x, cap.y, rect.right() - center.x, cap.h),
+        };
         canvas.draw(
             &graphics::Quad,
-            graphics::DrawParam::new()
-                .dest_rect(self.button2)
-                .color(Color::WHITE),
+            graphics::DrawParam::new().dest_rect(square).color(color),
         );
+    }
+}
 
-        let button2_text_measurements = self.button2_text.measure(ctx)?;
-        let button2_center = Point2 {
-            x: self.button2.x + self.button2.w / 2.0 - button2_text_measurements.x / 2.0,
-            y: self.button2.y + self.button2.h / 2.0 - button2_text_measurements.y / 2.0,
-        };
+// These are shared by the snake and the death replay so both pick up the palette's shape
+// cues. They take the cell's rect rather than its grid position, since a smoothly moving
+// snake is drawn partway between cells. Sprites fill the whole cell and don't join up.
+// The body's color is passed in since it changes along the snake
+fn draw_body(
+    canvas: &mut graphics::Canvas,
+    ctx: &Context,
+    cell: Rect,
+    joins: &[Direction],
+    color: Color,
+    colors: &BoardColors,
+    sprites: &Sprites,
+) -> GameResult {
+    let rect = match &sprites.body {
+        Some(image) => {
+            sprites::draw(canvas, image, cell, Direction::Up, color);
+            cell
+        }
+        None => {
+            draw_segment(canvas, cell, joins, color, sprites);
+            segment_rect(cell, joins)
+        }
+    };
+    if let Some(outline) = colors.body_outline {
+        draw_cell_border(canvas, ctx, rect, outline, 1.0)?;
+    }
 
-        canvas.draw(
-            &self.button2_text,
-            graphics::DrawParam::new()
-                .dest(button2_center)
-                .color(Color::BLACK),
-        );
+    Ok(())
+}
 
-        // canvas.draw(
-        //     &graphics::Quad,
-        //     graphics::DrawParam::new()
-        //         .dest_rect(self._horizontal_center)
-        //         .color(Color::GREEN),
-        // );
-        // canvas.draw(
-        //     &graphics::Quad,
-        //     graphics::DrawParam::new()
-        //         .dest_rect(self._vertical_center)
-        //         .color(Color::GREEN),
-        // );
+fn draw_head(
+    canvas: &mut graphics::Canvas,
+    ctx: &Context,
+    cell: Rect,
+    joins: &[Direction],
+    dir: Direction,
+    colors: &BoardColors,
+    sprites: &Sprites,
+) -> GameResult {
+    let rect = match &sprites.head {
+        Some(image) => {
+            sprites::draw(canvas, image, cell, dir, colors.head);
+            cell
+        }
+        None => {
+            draw_segment(canvas, cell, joins, colors.head, sprites);
+            segment_rect(cell, joins)
+        }
+    };
+    if let Some(border) = colors.head_border {
+        draw_cell_border(canvas, ctx, rect, border, colors.border_width)?;
+    }
 
-        Ok(())
+    Ok(())
+}
+
+// Where the eyes go in a cell, as their centers from its top left corner. They sit along
+// the edge the snake is heading toward, so it's clear which way it faces before it moves
+fn eye_offsets(dir: Direction) -> [(f32, f32); 2] {
+    let (w, h) = (GRID_CELL_SIZE.0 as f32, GRID_CELL_SIZE.1 as f32);
+    let inset = EYE_SIZE / 2.0 + 4.0;
+    match dir {
+        Direction::Up => [(w * 0.3, inset), (w * 0.7, inset)],
+        Direction::Down => [(w * 0.3, h - inset), (w * 0.7, h - inset)],
+        Direction::Left => [(inset, h * 0.3), (inset, h * 0.7)],
+        Direction::Right => [(w - inset, h * 0.3), (w - inset, h * 0.7)],
     }
 }
 
-impl EventHandler for OptionScreen {
-    fn update(&mut self, _ctx: &mut Context) -> Result<(), ggez::GameError> {
-        self.button1_clicked = false;
-        self.button2_clicked = false;
+// Drawn over the head. A dead snake gets an X for each eye instead
+fn draw_eyes(
+    canvas: &mut graphics::Canvas,
+    ctx: &Context,
+    cell: Rect,
+    dir: Direction,
+    color: Color,
+    dead: bool,
+) -> GameResult {
+    let half = EYE_SIZE / 2.0;
+    for (x, y) in eye_offsets(dir) {
+        let (x, y) = (cell.x + x, cell.y + y);
+        if dead {
+            for line in [
+                [[x - half, y - half], [x + half, y + half]],
+                [[x - half, y + half], [x + half, y - half]],
+            ] {
+                let line = graphics::Mesh::new_line(ctx, &line, 2.0, color)?;
+                canvas.draw(&line, graphics::DrawParam::new());
+            }
+        } else {
+            canvas.draw(
+                &graphics::Quad,
+                graphics::DrawParam::new()
+                    .dest_rect(Rect::new(x - half, y - half, EYE_SIZE, EYE_SIZE))
+                    .color(color),
+            );
+        }
+    }
 
-        Ok(())
+    Ok(())
+}
+
+// Takes a rect like the snake's, since food that's just appeared is smaller than its cell
+fn draw_food(
+    canvas: &mut graphics::Canvas,
+    ctx: &Context,
+    cell: Rect,
+    colors: &BoardColors,
+    sprites: &Sprites,
+) -> GameResult {
+    // Too small for a border to fit while it's only just started popping in
+    let border = colors
+        .food_border
+        .filter(|_| cell.w > colors.border_width * 2.0);
+    // A sprite already has a shape of its own, so it takes the place of the diamond too
+    if let Some(image) = &sprites.food {
+        sprites::draw(canvas, image, cell, Direction::Up, colors.food);
+        if let Some(border) = border {
+            draw_cell_border(canvas, ctx, cell, border, colors.border_width)?;
+        }
+        return Ok(());
+    }
+    if colors.diamond_food {
+        sprites::draw_shape(canvas, &sprites.food_diamond, cell, colors.food);
+        return Ok(());
     }
 
-    fn draw(&mut self, _ctx: &mut Context) -> Result<(), ggez::GameError> {
-        Ok(())
+    sprites::draw_shape(canvas, &sprites.food_circle, cell, colors.food);
+    // Round, to match the food it goes round
+    if let Some(border) = border {
+        let outline = graphics::Mesh::new_circle(
+            ctx,
+            graphics::DrawMode::stroke(colors.border_width),
+            [cell.x + cell.w / 2.0, cell.y + cell.h / 2.0],
+            (cell.w - colors.border_width) / 2.0,
+            0.1,
+            border,
+        )?;
+        canvas.draw(&outline, graphics::DrawParam::new());
     }
+
+    Ok(())
 }
 
 fn main() -> GameResult {
@@ -756,8 +5086,505 @@ fn main() -> GameResult {
         // Now we build. If it fails it'll panic with the message "Failed to build ggez context"
         .build()?;
 
+    let input_mode = InputMode::from_args().map_err(ggez::GameError::CustomError)?;
+    // For machines with no use for sound, like CI taking screenshots. No sound is ever loaded
+    // or played, but ggez has already opened the sound device by the time this is read, so
+    // it's no help on a machine without one
+    let audio_enabled = !std::env::args().any(|arg| arg == "--no-audio")
+        && std::env::var_os("SNAKE_NO_AUDIO").is_none();
+
     // Make a gamestate
-    let state = GameState::new(&mut ctx);
+    let mut state = GameState::new(&mut ctx, audio_enabled)?;
+    if let InputMode::Playback(log) = &input_mode {
+        log.check(&state.settings)
+            .map_err(ggez::GameError::CustomError)?;
+    }
+    state.input_mode = input_mode;
     // Run the jawn
     event::run(ctx, event_loop, state);
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Heading right along the middle of the board, with the food well out of the way
+    fn snake() -> Snake {
+        Snake::new(GridPosition::new(10, 10), Direction::Left)
+    }
+
+    // Which way the snake moved on each of the next n ticks
+    fn tick(snake: &mut Snake, n: usize) -> Vec<Direction> {
+        let food = Food::new(GridPosition::new(0, 0));
+        (0..n)
+            .map(|_| {
+                snake.update(&food, false, false);
+                snake.last_update_dir
+            })
+            .collect()
+    }
+
+    fn no_u_turns(start: Direction, moves: &[Direction]) -> bool {
+        std::iter::once(start)
+            .chain(moves.iter().copied())
+            .collect::<Vec<_>>()
+            .windows(2)
+            .all(|pair| pair[1] != pair[0].inverse())
+    }
+
+    #[test]
+    fn a_dash_reports_both_cells() {
+        let mut snake = snake();
+        snake.update(&Food::new(GridPosition::new(0, 0)), false, true);
+        assert_eq!(
+            snake.steps,
+            [
+                (GridPosition::new(11, 10), false),
+                (GridPosition::new(12, 10), false),
+            ]
+        );
+    }
+
+    #[test]
+    fn a_wrap_is_noted_on_the_step_that_made_it() {
+        let mut snake = Snake::new(GridPosition::new(GRID_SIZE.0 - 2, 5), Direction::Left);
+        let food = Food::new(GridPosition::new(0, 0));
+        snake.update(&food, false, true);
+        assert_eq!(
+            snake.steps,
+            [
+                (GridPosition::new(GRID_SIZE.0 - 1, 5), false),
+                (GridPosition::new(0, 5), true),
+            ]
+        );
+        // Walls stop it dead, so it never got anywhere
+        let mut snake = Snake::new(GridPosition::new(GRID_SIZE.0 - 1, 5), Direction::Left);
+        snake.update(&food, true, false);
+        assert!(snake.steps.is_empty());
+    }
+
+    #[test]
+    fn two_quick_turns_make_a_u_turn_over_two_ticks() {
+        let mut snake = snake();
+        assert!(snake.steer(Direction::Up));
+        assert!(!snake.steer(Direction::Left));
+        let moves = tick(&mut snake, 2);
+        assert_eq!(moves, [Direction::Up, Direction::Left]);
+        assert!(snake.ate.is_none());
+    }
+
+    #[test]
+    fn a_turn_never_jumps_ahead_of_the_queue() {
+        let mut snake = snake();
+        snake.steer(Direction::Up);
+        snake.steer(Direction::Left);
+        snake.steer(Direction::Down);
+        // Up's been made and Left and Down are still waiting, so Right has to go after them
+        let mut moves = tick(&mut snake, 1);
+        assert!(!snake.steer(Direction::Right));
+        moves.extend(tick(&mut snake, 4));
+        assert_eq!(
+            moves,
+            [
+                Direction::Up,
+                Direction::Left,
+                Direction::Down,
+                Direction::Right,
+                Direction::Right,
+            ]
+        );
+        assert!(no_u_turns(Direction::Right, &moves));
+        assert!(snake.ate.is_none());
+    }
+
+    #[test]
+    fn a_queued_turn_is_checked_against_the_one_before_it() {
+        let mut snake = snake();
+        snake.steer(Direction::Up);
+        snake.steer(Direction::Left);
+        tick(&mut snake, 1);
+        // Heading up with Left still to come, so Right would be straight back
+        snake.steer(Direction::Right);
+        let moves = tick(&mut snake, 3);
+        assert_eq!(moves, [Direction::Left; 3]);
+        assert!(snake.ate.is_none());
+    }
+
+    #[test]
+    fn a_press_back_the_way_it_came_uses_nothing_up() {
+        let mut snake = snake();
+        assert!(!snake.steer(Direction::Left));
+        assert!(!snake.steer(Direction::Right));
+        assert!(snake.steer(Direction::Up));
+        assert!(snake.next_dirs.is_empty());
+        assert_eq!(tick(&mut snake, 1), [Direction::Up]);
+    }
+
+    #[test]
+    fn several_presses_in_one_tick_each_check_against_the_last() {
+        let mut snake = snake();
+        snake.steer(Direction::Up);
+        // Straight back from Up, so dropped, and Right is still a turn from Up
+        snake.steer(Direction::Down);
+        snake.steer(Direction::Right);
+        snake.steer(Direction::Right);
+        assert_eq!(snake.next_dirs, [Direction::Right]);
+        assert_eq!(tick(&mut snake, 2), [Direction::Up, Direction::Right]);
+    }
+
+    #[test]
+    fn the_queue_stops_at_its_limit() {
+        let mut snake = snake();
+        for dir in [
+            Direction::Up,
+            Direction::Left,
+            Direction::Down,
+            Direction::Right,
+            Direction::Up,
+        ] {
+            snake.steer(dir);
+        }
+        assert_eq!(snake.next_dirs.len(), MAX_QUEUED_TURNS);
+        let moves = tick(&mut snake, 5);
+        assert_eq!(
+            moves,
+            [
+                Direction::Up,
+                Direction::Left,
+                Direction::Down,
+                Direction::Right,
+                Direction::Right,
+            ]
+        );
+    }
+
+    // The pause menu never touches the snake and nothing ticks behind it, so a turn made
+    // and one queued before pausing are the first two moves after resuming, whatever
+    // gets pressed in the menu
+    #[test]
+    fn a_turn_pressed_before_pausing_is_the_first_move_after() {
+        let mut snake = snake();
+        let mut run = Run::default();
+        let mut screens = ScreenStack::new(TITLE_SCREEN);
+        screens.push(GAMEPLAY);
+
+        // Two turns in, the first waiting on the early tick a responsive turn asks for,
+        // and then paused before the frame it would have come on
+        snake.steer(Direction::Up);
+        snake.steer(Direction::Left);
+        run.early_tick = true;
+        run.pause();
+        screens.push(PAUSE_MENU);
+        assert_ne!(screens.top(), GAMEPLAY);
+
+        screens.pop();
+        assert_eq!(screens.top(), GAMEPLAY);
+        assert_eq!(snake.dir, Direction::Up);
+        assert_eq!(snake.last_update_dir, Direction::Right);
+        assert_eq!(snake.next_dirs, [Direction::Left]);
+
+        // No early tick after all, so the first regular one makes the turn
+        assert!(!run.take_early_tick());
+        assert!(run.take_regular_tick());
+        assert_eq!(tick(&mut snake, 2), [Direction::Up, Direction::Left]);
+    }
+
+    #[test]
+    fn an_early_tick_stands_in_for_the_next_regular_one() {
+        let mut run = Run {
+            early_tick: true,
+            ..Run::default()
+        };
+        assert!(run.take_early_tick());
+        assert!(!run.take_regular_tick());
+        assert!(run.take_regular_tick());
+    }
+
+    #[test]
+    fn rotations_stack_on_the_ones_already_queued() {
+        let mut snake = snake();
+        assert!(snake.rotate(true));
+        assert!(!snake.rotate(true));
+        assert!(!snake.rotate(true));
+        assert_eq!(
+            tick(&mut snake, 3),
+            [Direction::Down, Direction::Left, Direction::Up]
+        );
+        assert!(snake.ate.is_none());
+    }
+
+    #[test]
+    fn a_rotation_one_way_then_the_other_straightens_out() {
+        let mut snake = snake();
+        snake.rotate(false);
+        snake.rotate(true);
+        assert_eq!(tick(&mut snake, 2), [Direction::Up, Direction::Right]);
+
+        // Made part way through the queue, it still turns from the last one in it
+        snake.rotate(false);
+        snake.rotate(false);
+        tick(&mut snake, 1);
+        snake.rotate(true);
+        assert_eq!(tick(&mut snake, 2), [Direction::Left, Direction::Up]);
+    }
+
+    #[test]
+    fn the_numpad_arrows_steer_and_nothing_else_on_it_does() {
+        let arrows = [
+            (KeyCode::Numpad8, Direction::Up),
+            (KeyCode::Numpad2, Direction::Down),
+            (KeyCode::Numpad4, Direction::Left),
+            (KeyCode::Numpad6, Direction::Right),
+        ];
+        for (key, dir) in arrows {
+            assert_eq!(Direction::from_keycode(key), Some(dir));
+        }
+        for key in [
+            KeyCode::Numpad0,
+            KeyCode::Numpad1,
+            KeyCode::Numpad3,
+            KeyCode::Numpad5,
+            KeyCode::Numpad7,
+            KeyCode::Numpad9,
+            KeyCode::NumpadAdd,
+            KeyCode::NumpadSubtract,
+            KeyCode::NumpadEnter,
+            KeyCode::Numlock,
+        ] {
+            assert_eq!(Direction::from_keycode(key), None, "{:?}", key);
+        }
+    }
+
+    #[test]
+    fn only_the_arrows_of_the_navigation_cluster_steer() {
+        let arrows = [
+            (KeyCode::Up, Direction::Up),
+            (KeyCode::Down, Direction::Down),
+            (KeyCode::Left, Direction::Left),
+            (KeyCode::Right, Direction::Right),
+        ];
+        for (key, dir) in arrows {
+            assert_eq!(Direction::from_keycode(key), Some(dir));
+        }
+        for key in [
+            KeyCode::Home,
+            KeyCode::End,
+            KeyCode::PageUp,
+            KeyCode::PageDown,
+            KeyCode::Insert,
+            KeyCode::Delete,
+        ] {
+            assert_eq!(Direction::from_keycode(key), None, "{:?}", key);
+        }
+    }
+
+    #[test]
+    fn menus_take_a_numpad_arrow_as_the_arrow_it_steers_like() {
+        let bindings = Bindings::default();
+        for key in [
+            KeyCode::Numpad8,
+            KeyCode::Numpad2,
+            KeyCode::Numpad4,
+            KeyCode::Numpad6,
+        ] {
+            let arrow = translate_menu_key(&bindings, key);
+            assert_ne!(arrow, key);
+            assert_eq!(Direction::from_keycode(arrow), Direction::from_keycode(key));
+        }
+        assert_eq!(
+            translate_menu_key(&bindings, KeyCode::Numpad5),
+            KeyCode::Numpad5
+        );
+        assert_eq!(translate_menu_key(&bindings, KeyCode::Home), KeyCode::Home);
+    }
+
+    #[test]
+    fn the_title_screen_stays_at_the_bottom() {
+        let mut screens = ScreenStack::new(TITLE_SCREEN);
+        screens.pop();
+        assert_eq!(screens, ScreenStack::new(TITLE_SCREEN));
+        assert_eq!(screens.below(), TITLE_SCREEN);
+
+        screens.push(SETTINGS_SCREEN);
+        screens.push(CONTROLS_SCREEN);
+        assert_eq!(screens.top(), CONTROLS_SCREEN);
+        assert_eq!(screens.below(), SETTINGS_SCREEN);
+        screens.pop();
+        screens.pop();
+        screens.pop();
+        assert_eq!(screens.top(), TITLE_SCREEN);
+    }
+
+    #[test]
+    fn a_game_runs_through_the_stack_and_back_to_the_title() {
+        let mut screens = ScreenStack::new(TITLE_SCREEN);
+        screens.push(COUNTDOWN);
+        screens.replace(GAMEPLAY);
+        screens.push(PAUSE_MENU);
+        screens.push(QUIT_PROMPT);
+        assert_eq!(screens.below(), PAUSE_MENU);
+        screens.pop();
+        screens.pop();
+        assert_eq!(screens.top(), GAMEPLAY);
+
+        screens.replace(DYING);
+        screens.replace(GAME_LOSS);
+        screens.push(LEADERBOARD);
+        screens.clear();
+        assert_eq!(screens, ScreenStack::new(TITLE_SCREEN));
+    }
+
+    #[test]
+    fn replacing_the_bottom_screen_goes_on_top_of_it() {
+        let mut screens = ScreenStack::new(LOADING_SCREEN);
+        screens.replace(TITLE_SCREEN);
+        assert_eq!(screens.below(), LOADING_SCREEN);
+        // Which is why loading starts the stack over instead
+        screens.clear();
+        assert_eq!(screens.top(), LOADING_SCREEN);
+    }
+
+    #[test]
+    fn loading_with_sounds_missing_says_so_over_the_title() {
+        let screens = ScreenStack::after_loading(&["/snake_jazz", "/snake"]);
+        assert_eq!(screens.top(), LOAD_ERROR);
+        assert_eq!(screens.below(), TITLE_SCREEN);
+        let mut screens = screens;
+        screens.pop();
+        assert_eq!(screens, ScreenStack::after_loading(&[]));
+        assert_eq!(screens.top(), TITLE_SCREEN);
+    }
+
+    // What each press does in the game, with None for a repeat that's dropped
+    fn presses(bindings: &Bindings, presses: &[(KeyCode, bool)]) -> Vec<Option<GameplayKey>> {
+        presses
+            .iter()
+            .map(|&(key, repeated)| {
+                if repeated && !takes_repeat(bindings, GAMEPLAY, key) {
+                    None
+                } else {
+                    gameplay_key(bindings, false, key)
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn holding_a_key_only_counts_the_first_press() {
+        let bindings = Bindings::default();
+        // Up held, Left tapped while it's still down, and Up still repeating after
+        let held = presses(
+            &bindings,
+            &[
+                (KeyCode::Up, false),
+                (KeyCode::Up, true),
+                (KeyCode::Left, false),
+                (KeyCode::Up, true),
+                (KeyCode::Up, true),
+            ],
+        );
+        assert_eq!(
+            held,
+            [
+                Some(GameplayKey::Steer(Direction::Up)),
+                None,
+                Some(GameplayKey::Steer(Direction::Left)),
+                None,
+                None,
+            ]
+        );
+
+        let mut snake = snake();
+        for press in held.into_iter().flatten() {
+            if let GameplayKey::Steer(dir) = press {
+                snake.steer(dir);
+            }
+        }
+        // The repeats didn't queue an Up behind the Left
+        assert_eq!(
+            tick(&mut snake, 3),
+            [Direction::Up, Direction::Left, Direction::Left]
+        );
+    }
+
+    #[test]
+    fn holding_pause_only_pauses() {
+        let bindings = Bindings::default();
+        let pause = bindings.get(Action::Pause);
+        assert_eq!(
+            presses(&bindings, &[(pause, false), (pause, true)]),
+            [Some(GameplayKey::Pause), None]
+        );
+        // The repeats land on the pause menu the first press opened, where the pause key
+        // would resume
+        assert!(!takes_repeat(&bindings, PAUSE_MENU, pause));
+        assert!(!takes_repeat(&bindings, PAUSE_MENU, KeyCode::Escape));
+    }
+
+    #[test]
+    fn only_menu_arrows_repeat() {
+        let bindings = Bindings::default();
+        assert!(takes_repeat(&bindings, SETTINGS_SCREEN, KeyCode::Down));
+        assert!(takes_repeat(&bindings, TITLE_SCREEN, KeyCode::Numpad8));
+        assert!(!takes_repeat(&bindings, COUNTDOWN, KeyCode::Left));
+        assert!(!takes_repeat(&bindings, TITLE_SCREEN, KeyCode::Return));
+        assert!(!takes_repeat(&bindings, SETTINGS_SCREEN, KeyCode::Escape));
+        assert!(!takes_repeat(&bindings, LEADERBOARD, KeyCode::Escape));
+        assert!(!takes_repeat(&bindings, TITLE_SCREEN, KeyCode::M));
+        assert!(!takes_repeat(&bindings, TITLE_SCREEN, KeyCode::Minus));
+
+        // An arrow given to pause doesn't repeat either
+        let mut bindings = bindings;
+        bindings.bind(Action::Up, KeyCode::I).unwrap();
+        bindings.bind(Action::Pause, KeyCode::Up).unwrap();
+        assert!(!takes_repeat(&bindings, PAUSE_MENU, KeyCode::Up));
+    }
+
+    // Eats once and plays on a bit, the way a run goes on any board
+    fn play(run: &mut Run, ticks: u32) {
+        let rules = scoring::ScoringPreset::Classic.rules();
+        for _ in 0..ticks {
+            run.ticks += 1;
+            run.time += std::time::Duration::from_millis(100);
+            run.score.tick();
+        }
+        run.score.food_eaten(&rules);
+        if let Some(index) = run.splits.check(SPLIT_INTERVAL, run.time) {
+            run.split_results
+                .push(SplitResult::new(index, run.time, &[]));
+        }
+        run.finale = true;
+    }
+
+    #[test]
+    fn a_run_carries_on_over_new_boards_until_a_retry() {
+        let mut rng = Rand32::new(7);
+        let mut run = Run::default();
+        let (first, _) = new_board(&mut rng);
+        play(&mut run, 20);
+        let first_score = run.score.score;
+
+        // Two more boards, like levels 2 and 3
+        let mut heads = vec![first.head.pos];
+        for _ in 0..2 {
+            let (snake, food) = new_board(&mut rng);
+            assert_ne!(snake.head.pos, food.pos);
+            heads.push(snake.head.pos);
+            play(&mut run, 20);
+        }
+        assert_ne!(heads[0], heads[2]);
+        assert_eq!(run.ticks, 60);
+        assert_eq!(run.time, std::time::Duration::from_secs(6));
+        assert_eq!(run.score.food_eaten, 3);
+        assert!(run.score.score > first_score);
+        assert_eq!(run.splits.times().len(), 1);
+
+        // A retry from there has nothing of the old run, not even the first board's score
+        run = Run::default();
+        assert_eq!(run.score.score, 0);
+        assert_eq!(run.score.food_eaten, 0);
+        assert_eq!((run.ticks, run.time), (0, std::time::Duration::ZERO));
+        assert!(run.splits.times().is_empty() && run.split_results.is_empty());
+        assert!(!run.finale);
+    }
+}
@@ -0,0 +1,84 @@
+use crate::settings::MenuColors;
+use crate::strings::Strings;
+use crate::{draw_centered_text, SCREEN_SIZE};
+use ggez::graphics::{self, Text};
+use ggez::{Context, GameResult};
+
+pub const MIN_NAME_LENGTH: usize = 3;
+pub const MAX_NAME_LENGTH: usize = 8;
+const DEFAULT_NAME: &str = "AAA";
+
+// The cursor spends this long on and then the same again off
+const CURSOR_BLINK_SECONDS: f32 = 0.5;
+
+// Arcade style: capital letters only, typed in one at a time
+pub struct NameEntry {
+    name: String,
+}
+
+impl NameEntry {
+    pub fn new() -> Self {
+        NameEntry {
+            name: String::new(),
+        }
+    }
+
+    pub fn type_char(&mut self, c: char) {
+        if c.is_ascii_alphabetic() && self.name.len() < MAX_NAME_LENGTH {
+            self.name.push(c.to_ascii_uppercase());
+        }
+    }
+
+    pub fn backspace(&mut self) {
+        self.name.pop();
+    }
+
+    // The name to store, or None if there's something typed that's still too short
+    pub fn confirm(&self) -> Option<String> {
+        if self.name.is_empty() {
+            Some(DEFAULT_NAME.to_string())
+        } else if self.name.len() >= MIN_NAME_LENGTH {
+            Some(self.name.clone())
+        } else {
+            None
+        }
+    }
+
+    pub fn draw(
+        &self,
+        canvas: &mut graphics::Canvas,
+        ctx: &mut Context,
+        strings: &Strings,
+        colors: &MenuColors,
+    ) -> GameResult {
+        let mut title = Text::new(strings.get("name_entry.heading"));
+        title.set_scale(40.0);
+        title.fragments_mut()[0].color = Some(colors.highlight);
+        draw_centered_text(
+            canvas,
+            ctx,
+            &title,
+            SCREEN_SIZE.1 / 2.0 - 120.0,
+            colors.text,
+        )?;
+
+        // Unused slots show as underscores so you can see how long the name is allowed to be
+        let blink_on = (ctx.time.time_since_start().as_secs_f32() / (2.0 * CURSOR_BLINK_SECONDS))
+            .fract()
+            < 0.5;
+        let mut field = Text::new(self.name.clone());
+        if self.name.len() < MAX_NAME_LENGTH {
+            field.add(if blink_on { "|" } else { " " });
+            field.add("_".repeat(MAX_NAME_LENGTH - self.name.len() - 1));
+        }
+        field.set_scale(48.0);
+        draw_centered_text(canvas, ctx, &field, SCREEN_SIZE.1 / 2.0 - 40.0, colors.text)?;
+
+        let mut hint =
+            Text::new(strings.format("name_entry.hint", &[&MIN_NAME_LENGTH, &MAX_NAME_LENGTH]));
+        hint.set_scale(20.0);
+        draw_centered_text(canvas, ctx, &hint, SCREEN_SIZE.1 / 2.0 + 40.0, colors.text)?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,85 @@
+use crate::settings::MenuColors;
+use crate::{GridPosition, GRID_CELL_SIZE};
+use ggez::graphics::{self, Color, Text};
+use ggez::mint::Point2;
+use ggez::{Context, GameResult};
+use std::collections::VecDeque;
+use std::time::Duration;
+
+const POPUP_LENGTH: Duration = Duration::from_millis(500);
+// A quick combo can spawn a few at once, but there's never a reason to have more than this
+const MAX_POPUPS: usize = 8;
+// How far a popup floats up over its lifetime, in pixels
+const POPUP_RISE: f32 = GRID_CELL_SIZE.1 as f32;
+
+struct Popup {
+    cell: GridPosition,
+    age: Duration,
+    value: u32,
+}
+
+// The "+N" that floats up off the food when it's eaten
+#[derive(Default)]
+pub struct Popups {
+    popups: VecDeque<Popup>,
+}
+
+impl Popups {
+    pub fn spawn(&mut self, cell: GridPosition, value: u32) {
+        if self.popups.len() == MAX_POPUPS {
+            self.popups.pop_front();
+        }
+        self.popups.push_back(Popup {
+            cell,
+            age: Duration::ZERO,
+            value,
+        });
+    }
+
+    // Called every frame rather than every tick so they float smoothly
+    pub fn update(&mut self, delta: Duration) {
+        for popup in &mut self.popups {
+            popup.age += delta;
+        }
+        self.popups.retain(|popup| popup.age < POPUP_LENGTH);
+    }
+
+    pub fn clear(&mut self) {
+        self.popups.clear();
+    }
+
+    // With reduced motion they stay put and only fade
+    pub fn draw(
+        &self,
+        canvas: &mut graphics::Canvas,
+        ctx: &mut Context,
+        colors: &MenuColors,
+        reduce_motion: bool,
+    ) -> GameResult {
+        for popup in &self.popups {
+            let progress = popup.age.as_secs_f32() / POPUP_LENGTH.as_secs_f32();
+            let rise = if reduce_motion { 0.0 } else { POPUP_RISE };
+
+            let mut text = Text::new(format!("+{}", popup.value));
+            text.set_scale(20.0);
+            let size = text.measure(ctx)?;
+
+            // Starts centered on the cell the food was in
+            let x = (popup.cell.x as f32 + 0.5) * GRID_CELL_SIZE.0 as f32 - size.x / 2.0;
+            let y = (popup.cell.y as f32 + 0.5) * GRID_CELL_SIZE.1 as f32
+                - size.y / 2.0
+                - progress * rise;
+            canvas.draw(
+                &text,
+                graphics::DrawParam::new()
+                    .dest(Point2 { x, y })
+                    .color(Color {
+                        a: 1.0 - progress,
+                        ..colors.text
+                    }),
+            );
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,157 @@
+use crate::scoring::ScoringPreset;
+use crate::storage;
+use ggez::Context;
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+const HIGH_SCORE_FILE: &str = "/highscores.txt";
+// Older files counted times in ticks, back when a tick was always a tenth of a second
+const OLD_TICK_LENGTH: Duration = Duration::from_millis(100);
+
+// Best scores are kept per scoring preset so Classic and Arcade numbers never get compared.
+// The best split times live in the same file as "splits_ms.<preset>=ms,ms,..."
+// and the fastest wins as "time_ms.<preset>=ms". Whoever set the best score is "name.<preset>=NAME".
+// Times are real game time rather than ticks, since how long a tick lasts depends on the speed
+#[derive(Debug, Default)]
+pub struct HighScores {
+    best: BTreeMap<String, u32>,
+    splits: BTreeMap<String, Vec<Duration>>,
+    times: BTreeMap<String, Duration>,
+    names: BTreeMap<String, String>,
+}
+
+// A time saved as whole milliseconds, or in the old ticks
+fn parse_time(value: &str, unit: Duration) -> Option<Duration> {
+    value.parse::<u32>().ok().map(|count| unit * count)
+}
+
+impl HighScores {
+    pub fn load(ctx: &Context) -> Self {
+        let mut scores = HighScores::default();
+        for (key, value) in storage::load(ctx, HIGH_SCORE_FILE) {
+            // Skip anything we can't make sense of instead of throwing the whole file away
+            let splits = key
+                .strip_prefix("splits_ms.")
+                .map(|preset| (preset, Duration::from_millis(1)))
+                .or_else(|| key.strip_prefix("splits.").map(|p| (p, OLD_TICK_LENGTH)));
+            let time = key
+                .strip_prefix("time_ms.")
+                .map(|preset| (preset, Duration::from_millis(1)))
+                .or_else(|| key.strip_prefix("time.").map(|p| (p, OLD_TICK_LENGTH)));
+            if let Some((preset, unit)) = splits {
+                if ScoringPreset::from_key(preset).is_none() {
+                    continue;
+                }
+                let splits: Option<Vec<Duration>> = value
+                    .split(',')
+                    .map(|split| parse_time(split, unit))
+                    .collect();
+                if let Some(splits) = splits {
+                    scores.splits.insert(preset.to_string(), splits);
+                }
+            } else if let Some((preset, unit)) = time {
+                if ScoringPreset::from_key(preset).is_none() {
+                    continue;
+                }
+                if let Some(time) = parse_time(&value, unit) {
+                    scores.times.insert(preset.to_string(), time);
+                }
+            } else if let Some(preset) = key.strip_prefix("name.") {
+                if ScoringPreset::from_key(preset).is_some() && !value.is_empty() {
+                    scores.names.insert(preset.to_string(), value);
+                }
+            } else if ScoringPreset::from_key(&key).is_some() {
+                if let Ok(score) = value.parse::<u32>() {
+                    scores.best.insert(key, score);
+                }
+            }
+        }
+        scores
+    }
+
+    fn save(&self, ctx: &Context) {
+        let mut entries: BTreeMap<String, String> = self
+            .best
+            .iter()
+            .map(|(key, score)| (key.clone(), score.to_string()))
+            .collect();
+        for (key, splits) in &self.splits {
+            let splits: Vec<String> = splits
+                .iter()
+                .map(|split| split.as_millis().to_string())
+                .collect();
+            entries.insert(format!("splits_ms.{}", key), splits.join(","));
+        }
+        for (key, time) in &self.times {
+            entries.insert(format!("time_ms.{}", key), time.as_millis().to_string());
+        }
+        for (key, name) in &self.names {
+            entries.insert(format!("name.{}", key), name.clone());
+        }
+        storage::save(ctx, HIGH_SCORE_FILE, &entries);
+    }
+
+    pub fn best(&self, preset: ScoringPreset) -> Option<u32> {
+        self.best.get(preset.key()).copied()
+    }
+
+    // Returns true (and saves) if the score beat the stored best for that preset
+    pub fn submit(&mut self, ctx: &Context, preset: ScoringPreset, score: u32) -> bool {
+        // A run that never scored isn't worth remembering
+        if score == 0 || self.best(preset).is_some_and(|best| best >= score) {
+            return false;
+        }
+        self.best.insert(preset.key().to_string(), score);
+        // The old name belonged to the old score
+        self.names.remove(preset.key());
+        self.save(ctx);
+        true
+    }
+
+    pub fn best_name(&self, preset: ScoringPreset) -> Option<&str> {
+        self.names.get(preset.key()).map(String::as_str)
+    }
+
+    // Puts a name to the best score, which has to have been submitted already
+    pub fn set_name(&mut self, ctx: &Context, preset: ScoringPreset, name: &str) {
+        self.names
+            .insert(preset.key().to_string(), name.to_string());
+        self.save(ctx);
+    }
+
+    pub fn best_splits(&self, preset: ScoringPreset) -> &[Duration] {
+        self.splits
+            .get(preset.key())
+            .map_or(&[], |splits| splits.as_slice())
+    }
+
+    // Keeps the fastest time seen for each split, so the stored table is the sum of your golds
+    pub fn submit_splits(&mut self, ctx: &Context, preset: ScoringPreset, splits: &[Duration]) {
+        if splits.is_empty() {
+            return;
+        }
+
+        let best = self.splits.entry(preset.key().to_string()).or_default();
+        for (i, &time) in splits.iter().enumerate() {
+            match best.get_mut(i) {
+                Some(best_time) => *best_time = (*best_time).min(time),
+                None => best.push(time),
+            }
+        }
+        self.save(ctx);
+    }
+
+    pub fn best_time(&self, preset: ScoringPreset) -> Option<Duration> {
+        self.times.get(preset.key()).copied()
+    }
+
+    // Returns true (and saves) if this was the fastest win so far for that preset
+    pub fn submit_time(&mut self, ctx: &Context, preset: ScoringPreset, time: Duration) -> bool {
+        if self.best_time(preset).is_some_and(|best| best <= time) {
+            return false;
+        }
+        self.times.insert(preset.key().to_string(), time);
+        self.save(ctx);
+        true
+    }
+}
@@ -0,0 +1,99 @@
+use crate::settings::BoardColors;
+use crate::sprites::Sprites;
+use crate::{draw_body, draw_eyes, draw_food, draw_head, joined_sides, Direction, GridPosition};
+use ggez::{graphics, Context, GameResult};
+use std::collections::VecDeque;
+use std::time::Duration;
+
+// About two seconds of a normal speed game
+pub const REPLAY_TICKS: usize = 20;
+
+// Just the cells, so a frame costs a few bytes per segment and nothing else
+#[derive(Debug, Clone)]
+pub struct ReplayFrame {
+    // Head first
+    pub snake: Vec<GridPosition>,
+    // Which way the head was facing, for turning its sprite
+    pub dir: Direction,
+    pub food: GridPosition,
+}
+
+// The last REPLAY_TICKS frames of the run, oldest first
+#[derive(Debug, Default)]
+pub struct ReplayBuffer {
+    frames: VecDeque<ReplayFrame>,
+}
+
+impl ReplayBuffer {
+    pub fn record(&mut self, frame: ReplayFrame) {
+        if self.frames.len() == REPLAY_TICKS {
+            self.frames.pop_front();
+        }
+        self.frames.push_back(frame);
+    }
+
+    pub fn clear(&mut self) {
+        self.frames.clear();
+    }
+
+    // Hands the frames over to play back, leaving the buffer empty
+    pub fn take(&mut self, frame_length: Duration) -> Replay {
+        Replay {
+            frames: std::mem::take(&mut self.frames).into(),
+            frame_length,
+            started: None,
+        }
+    }
+}
+
+pub struct Replay {
+    frames: Vec<ReplayFrame>,
+    frame_length: Duration,
+    // Set by the first draw, so time spent on name entry or the leaderboard doesn't count
+    started: Option<Duration>,
+}
+
+impl Replay {
+    fn frame_index(&self, now: Duration) -> usize {
+        let elapsed = self.started.map_or(Duration::ZERO, |started| now - started);
+        (elapsed.as_secs_f32() / self.frame_length.as_secs_f32()) as usize
+    }
+
+    pub fn finished(&self, now: Duration) -> bool {
+        self.started.is_some() && self.frame_index(now) >= self.frames.len()
+    }
+
+    pub fn draw(
+        &mut self,
+        canvas: &mut graphics::Canvas,
+        ctx: &Context,
+        colors: &BoardColors,
+        sprites: &Sprites,
+    ) -> GameResult {
+        let now = ctx.time.time_since_start();
+        self.started.get_or_insert(now);
+        // Holds on the last frame until whoever's playing it notices it's finished
+        let index = self
+            .frame_index(now)
+            .min(self.frames.len().saturating_sub(1));
+        let Some(frame) = self.frames.get(index) else {
+            return Ok(());
+        };
+
+        let Some((&head, body)) = frame.snake.split_first() else {
+            return Ok(());
+        };
+        let last = body.len().saturating_sub(1).max(1) as f32;
+        for (i, &pos) in body.iter().enumerate() {
+            let color = colors.body_at(i as f32 / last);
+            let joins = joined_sides(&frame.snake, i + 1);
+            draw_body(canvas, ctx, pos.into(), &joins, color, colors, sprites)?;
+        }
+        let joins = joined_sides(&frame.snake, 0);
+        draw_head(canvas, ctx, head.into(), &joins, frame.dir, colors, sprites)?;
+        if sprites.head.is_none() {
+            draw_eyes(canvas, ctx, head.into(), frame.dir, colors.eyes, false)?;
+        }
+        draw_food(canvas, ctx, frame.food.into(), colors, sprites)
+    }
+}
@@ -0,0 +1,129 @@
+// Scoring is kept as plain data so a new preset is just another set of numbers
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScoringPreset {
+    Classic,
+    Arcade,
+}
+
+impl ScoringPreset {
+    pub const ALL: [ScoringPreset; 2] = [ScoringPreset::Classic, ScoringPreset::Arcade];
+
+    // What gets written to the settings and high score files
+    pub fn key(self) -> &'static str {
+        match self {
+            ScoringPreset::Classic => "classic",
+            ScoringPreset::Arcade => "arcade",
+        }
+    }
+
+    pub fn from_key(key: &str) -> Option<ScoringPreset> {
+        match key {
+            "classic" => Some(ScoringPreset::Classic),
+            "arcade" => Some(ScoringPreset::Arcade),
+            _ => None,
+        }
+    }
+
+    pub fn next(self) -> Self {
+        match self {
+            ScoringPreset::Classic => ScoringPreset::Arcade,
+            ScoringPreset::Arcade => ScoringPreset::Classic,
+        }
+    }
+
+    pub fn rules(self) -> ScoringRules {
+        match self {
+            // 1 point per food and nothing else
+            ScoringPreset::Classic => ScoringRules {
+                points_per_food: 1,
+                decay_per_interval: 0,
+                decay_interval: 0,
+                min_food_points: 1,
+                combo_window: 0,
+                combo_step: 0.0,
+                max_multiplier: 1.0,
+                win_bonus: 0,
+                win_bonus_decay_ticks: 0,
+            },
+            // Food loses value the longer it sits there, eating quickly builds a combo,
+            // and winning quickly is worth a lot
+            ScoringPreset::Arcade => ScoringRules {
+                points_per_food: 10,
+                decay_per_interval: 1,
+                decay_interval: 5,
+                min_food_points: 2,
+                combo_window: 30,
+                combo_step: 0.5,
+                max_multiplier: 4.0,
+                win_bonus: 5000,
+                win_bonus_decay_ticks: 1,
+            },
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct ScoringRules {
+    pub points_per_food: u32,
+    // Food is worth decay_per_interval less for every decay_interval ticks it goes uneaten,
+    // but never less than min_food_points (an interval of 0 turns decay off)
+    pub decay_per_interval: u32,
+    pub decay_interval: u32,
+    pub min_food_points: u32,
+    // Eating again within combo_window ticks raises the multiplier by combo_step
+    // up to max_multiplier (a window of 0 turns combos off)
+    pub combo_window: u32,
+    pub combo_step: f32,
+    pub max_multiplier: f32,
+    // Winning is worth win_bonus minus a point every win_bonus_decay_ticks ticks
+    pub win_bonus: u32,
+    pub win_bonus_decay_ticks: u32,
+}
+
+#[derive(Debug, Default)]
+pub struct ScoreKeeper {
+    pub score: u32,
+    pub combo: u32,
+    pub food_eaten: u32,
+    ticks_since_food: u32,
+}
+
+impl ScoreKeeper {
+    pub fn tick(&mut self) {
+        self.ticks_since_food = self.ticks_since_food.saturating_add(1);
+    }
+
+    // Adds the food's value to the score and returns how many points it was worth
+    pub fn food_eaten(&mut self, rules: &ScoringRules) -> u32 {
+        let decay = self
+            .ticks_since_food
+            .checked_div(rules.decay_interval)
+            .map_or(0, |intervals| intervals * rules.decay_per_interval);
+        let base = rules
+            .points_per_food
+            .saturating_sub(decay)
+            .max(rules.min_food_points);
+
+        if rules.combo_window > 0 && self.ticks_since_food <= rules.combo_window {
+            self.combo += 1;
+        } else {
+            self.combo = 0;
+        }
+        let multiplier = (1.0 + self.combo as f32 * rules.combo_step).min(rules.max_multiplier);
+
+        let points = (base as f32 * multiplier).round() as u32;
+        self.score += points;
+        self.food_eaten += 1;
+        self.ticks_since_food = 0;
+        points
+    }
+
+    pub fn win_bonus(&mut self, rules: &ScoringRules, elapsed_ticks: u32) -> u32 {
+        let penalty = elapsed_ticks
+            .checked_div(rules.win_bonus_decay_ticks)
+            .unwrap_or(0);
+        let bonus = rules.win_bonus.saturating_sub(penalty);
+        self.score += bonus;
+        bonus
+    }
+}
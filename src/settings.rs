@@ -0,0 +1,991 @@
+use crate::bindings::Bindings;
+use crate::scoring::ScoringPreset;
+use crate::storage;
+use ggez::graphics::Color;
+use ggez::Context;
+use std::collections::BTreeMap;
+
+const SETTINGS_FILE: &str = "/settings.txt";
+
+// Volumes go up and down in steps of this many percent
+pub const VOLUME_STEP: u8 = 10;
+const DEFAULT_SWIPE_DISTANCE: u8 = 40;
+// The stick's deadzone is a percentage of how far it can be pushed
+const DEFAULT_STICK_DEADZONE: u8 = 50;
+const STICK_DEADZONE_STEP: u8 = 5;
+const MIN_STICK_DEADZONE: u8 = 5;
+const MAX_STICK_DEADZONE: u8 = 95;
+// Any shorter and a tap that wobbles a little would turn the snake
+const MIN_SWIPE_DISTANCE: u8 = 10;
+// The rhythm offset is in milliseconds either side of the music
+const RHYTHM_OFFSET_STEP: i16 = 10;
+const MAX_RHYTHM_OFFSET: i16 = 500;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Speed {
+    Slow,
+    Normal,
+    Fast,
+    // The snake moves in time with the music instead of at a set rate
+    Rhythm,
+}
+
+impl Speed {
+    const ALL: [Speed; 4] = [Speed::Slow, Speed::Normal, Speed::Fast, Speed::Rhythm];
+
+    pub fn key(self) -> &'static str {
+        match self {
+            Speed::Slow => "slow",
+            Speed::Normal => "normal",
+            Speed::Fast => "fast",
+            Speed::Rhythm => "rhythm",
+        }
+    }
+
+    // Game ticks per second. Rhythm mode only uses this for the menus, since the game
+    // itself follows the beat
+    pub fn tick_rate(self) -> u32 {
+        match self {
+            Speed::Slow => 7,
+            Speed::Normal | Speed::Rhythm => 10,
+            Speed::Fast => 14,
+        }
+    }
+
+    pub fn step(self, delta: i32) -> Self {
+        step(&Self::ALL, self, delta)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Theme {
+    Classic,
+    Dark,
+    Desert,
+}
+
+impl Theme {
+    const ALL: [Theme; 3] = [Theme::Classic, Theme::Dark, Theme::Desert];
+
+    pub fn key(self) -> &'static str {
+        match self {
+            Theme::Classic => "classic",
+            Theme::Dark => "dark",
+            Theme::Desert => "desert",
+        }
+    }
+
+    pub fn background(self) -> Color {
+        match self {
+            Theme::Classic => Color::new(0.0, 1.0, 0.0, 1.0),
+            Theme::Dark => Color::new(0.1, 0.15, 0.1, 1.0),
+            Theme::Desert => Color::new(0.93, 0.79, 0.55, 1.0),
+        }
+    }
+
+    // Every other cell of the board, so it's easier to judge distances across it
+    pub fn checker(self) -> Color {
+        match self {
+            Theme::Classic => Color::new(0.0, 0.88, 0.0, 1.0),
+            Theme::Dark => Color::new(0.14, 0.2, 0.14, 1.0),
+            Theme::Desert => Color::new(0.88, 0.73, 0.49, 1.0),
+        }
+    }
+
+    // See-through, so the lines still show on a palette's own background
+    pub fn grid_line(self) -> Color {
+        match self {
+            Theme::Classic => Color::new(0.0, 0.35, 0.0, 0.5),
+            Theme::Dark => Color::new(0.4, 0.5, 0.4, 0.5),
+            Theme::Desert => Color::new(0.55, 0.4, 0.2, 0.5),
+        }
+    }
+
+    // Midday, evening, midnight and morning, for the day/night cycle. Midday is the
+    // theme's own background, so the board starts out looking the way it always does
+    fn day_night_keys(self) -> [Color; 4] {
+        match self {
+            Theme::Classic => [
+                self.background(),
+                Color::new(0.75, 0.5, 0.25, 1.0),
+                Color::new(0.02, 0.07, 0.1, 1.0),
+                Color::new(0.5, 0.65, 0.4, 1.0),
+            ],
+            Theme::Dark => [
+                self.background(),
+                Color::new(0.16, 0.1, 0.12, 1.0),
+                Color::new(0.02, 0.03, 0.05, 1.0),
+                Color::new(0.1, 0.11, 0.15, 1.0),
+            ],
+            Theme::Desert => [
+                self.background(),
+                Color::new(0.85, 0.5, 0.3, 1.0),
+                Color::new(0.1, 0.08, 0.16, 1.0),
+                Color::new(0.72, 0.56, 0.55, 1.0),
+            ],
+        }
+    }
+
+    // The background partway round the day/night cycle, with phase going from 0 at midday
+    // round to 1 back at midday again. Each key color blends into the next one
+    pub fn day_night(self, phase: f32) -> Color {
+        let keys = self.day_night_keys();
+        let position = phase.rem_euclid(1.0) * keys.len() as f32;
+        let from = position as usize % keys.len();
+        mix(keys[from], keys[(from + 1) % keys.len()], position.fract())
+    }
+
+    // The snake and food for the standard palette. The colorblind ones keep their own
+    pub fn board_colors(self) -> &'static BoardColors {
+        match self {
+            Theme::Classic => &CLASSIC_COLORS,
+            Theme::Dark => &DARK_COLORS,
+            Theme::Desert => &DESERT_COLORS,
+        }
+    }
+
+    pub fn menu_colors(self) -> &'static MenuColors {
+        match self {
+            Theme::Classic => &CLASSIC_MENU_COLORS,
+            Theme::Dark => &DARK_MENU_COLORS,
+            Theme::Desert => &DESERT_MENU_COLORS,
+        }
+    }
+
+    pub fn step(self, delta: i32) -> Self {
+        step(&Self::ALL, self, delta)
+    }
+}
+
+// Everything off the board: the menus, their buttons and all the text
+#[derive(Debug, Clone, Copy)]
+pub struct MenuColors {
+    pub background: Color,
+    pub text: Color,
+    // Column headings and things that aren't unlocked yet
+    pub dim_text: Color,
+    // Best scores, new records, the selected button
+    pub highlight: Color,
+    pub warning: Color,
+    pub button: Color,
+    pub hover: Color,
+    pub pressed: Color,
+    // Text on the buttons
+    pub label: Color,
+    // What's laid over the board to darken it behind prompts and menus. Its alpha is
+    // picked wherever it's used
+    pub shade: Color,
+    // Over the board a run ended on, so the end screens read over it
+    pub win_tint: Color,
+    pub loss_tint: Color,
+    // The end screen heatmap, from the least visited cells to the most
+    pub heat_cold: Color,
+    pub heat_hot: Color,
+    // Thrown over the win screen
+    pub confetti: [Color; 5],
+}
+
+impl MenuColors {
+    // Heat goes from 0 for the least visited cells to 1 for the most
+    pub fn heat(&self, heat: f32) -> Color {
+        mix(self.heat_cold, self.heat_hot, heat)
+    }
+}
+
+const CLASSIC_MENU_COLORS: MenuColors = MenuColors {
+    background: Color::new(0.0, 0.0, 0.0, 1.0),
+    text: Color::new(1.0, 1.0, 1.0, 1.0),
+    dim_text: Color::new(0.6, 0.6, 0.6, 1.0),
+    highlight: Color::new(1.0, 1.0, 0.0, 1.0),
+    warning: Color::new(1.0, 0.0, 0.0, 1.0),
+    button: Color::new(0.85, 0.85, 0.85, 1.0),
+    hover: Color::new(1.0, 1.0, 1.0, 1.0),
+    pressed: Color::new(0.6, 0.6, 0.6, 1.0),
+    label: Color::new(0.0, 0.0, 0.0, 1.0),
+    shade: Color::new(0.0, 0.0, 0.0, 1.0),
+    win_tint: Color::new(0.0, 0.0, 0.25, 0.6),
+    loss_tint: Color::new(0.25, 0.0, 0.0, 0.6),
+    heat_cold: Color::new(0.0, 0.0, 1.0, 0.8),
+    heat_hot: Color::new(1.0, 0.0, 0.0, 0.8),
+    confetti: [
+        Color::RED,
+        Color::YELLOW,
+        Color::GREEN,
+        Color::CYAN,
+        Color::MAGENTA,
+    ],
+};
+
+const DARK_MENU_COLORS: MenuColors = MenuColors {
+    background: Color::new(0.05, 0.07, 0.05, 1.0),
+    text: Color::new(0.85, 0.9, 0.85, 1.0),
+    dim_text: Color::new(0.45, 0.52, 0.45, 1.0),
+    highlight: Color::new(0.6, 0.9, 0.35, 1.0),
+    warning: Color::new(1.0, 0.4, 0.35, 1.0),
+    button: Color::new(0.22, 0.3, 0.22, 1.0),
+    hover: Color::new(0.32, 0.44, 0.32, 1.0),
+    pressed: Color::new(0.15, 0.2, 0.15, 1.0),
+    label: Color::new(0.9, 0.95, 0.9, 1.0),
+    shade: Color::new(0.0, 0.02, 0.0, 1.0),
+    win_tint: Color::new(0.0, 0.05, 0.15, 0.7),
+    loss_tint: Color::new(0.15, 0.02, 0.0, 0.7),
+    heat_cold: Color::new(0.2, 0.35, 0.9, 0.8),
+    heat_hot: Color::new(0.95, 0.35, 0.2, 0.8),
+    confetti: [
+        Color::new(1.0, 0.4, 0.35, 1.0),
+        Color::new(0.95, 0.85, 0.35, 1.0),
+        Color::new(0.6, 0.9, 0.35, 1.0),
+        Color::new(0.35, 0.8, 0.85, 1.0),
+        Color::new(0.85, 0.45, 0.85, 1.0),
+    ],
+};
+
+const DESERT_MENU_COLORS: MenuColors = MenuColors {
+    background: Color::new(0.24, 0.15, 0.08, 1.0),
+    text: Color::new(0.98, 0.92, 0.8, 1.0),
+    dim_text: Color::new(0.7, 0.6, 0.45, 1.0),
+    highlight: Color::new(1.0, 0.72, 0.3, 1.0),
+    warning: Color::new(0.95, 0.3, 0.2, 1.0),
+    button: Color::new(0.93, 0.79, 0.55, 1.0),
+    hover: Color::new(1.0, 0.9, 0.7, 1.0),
+    pressed: Color::new(0.75, 0.6, 0.38, 1.0),
+    label: Color::new(0.3, 0.18, 0.05, 1.0),
+    shade: Color::new(0.15, 0.08, 0.02, 1.0),
+    win_tint: Color::new(0.1, 0.1, 0.25, 0.6),
+    loss_tint: Color::new(0.3, 0.08, 0.0, 0.6),
+    heat_cold: Color::new(0.2, 0.45, 0.55, 0.8),
+    heat_hot: Color::new(0.95, 0.3, 0.1, 0.8),
+    confetti: [
+        Color::new(0.95, 0.3, 0.2, 1.0),
+        Color::new(1.0, 0.72, 0.3, 1.0),
+        Color::new(0.55, 0.7, 0.3, 1.0),
+        Color::new(0.3, 0.6, 0.65, 1.0),
+        Color::new(0.85, 0.45, 0.55, 1.0),
+    ],
+};
+
+// Pure white buttons that go yellow under the mouse read better than the themes' softer
+// colors, so high contrast overrides the theme here too
+const HIGH_CONTRAST_MENU_COLORS: MenuColors = MenuColors {
+    background: Color::new(0.0, 0.0, 0.0, 1.0),
+    text: Color::new(1.0, 1.0, 1.0, 1.0),
+    dim_text: Color::new(0.8, 0.8, 0.8, 1.0),
+    highlight: Color::new(1.0, 1.0, 0.0, 1.0),
+    warning: Color::new(1.0, 0.2, 0.2, 1.0),
+    button: Color::new(1.0, 1.0, 1.0, 1.0),
+    hover: Color::new(1.0, 1.0, 0.0, 1.0),
+    pressed: Color::new(0.6, 0.6, 0.6, 1.0),
+    label: Color::new(0.0, 0.0, 0.0, 1.0),
+    shade: Color::new(0.0, 0.0, 0.0, 1.0),
+    win_tint: Color::new(0.0, 0.0, 0.0, 0.75),
+    loss_tint: Color::new(0.0, 0.0, 0.0, 0.75),
+    heat_cold: Color::new(0.0, 0.5, 1.0, 0.9),
+    heat_hot: Color::new(1.0, 1.0, 0.0, 0.9),
+    confetti: [
+        Color::WHITE,
+        Color::YELLOW,
+        Color::CYAN,
+        Color::MAGENTA,
+        Color::GREEN,
+    ],
+};
+
+// The colors everything on the board is drawn in. The palettes for colorblind players
+// add shape cues too, so nothing depends on telling two colors apart
+#[derive(Debug, Clone, Copy)]
+pub struct BoardColors {
+    // None keeps the theme's background
+    pub background: Option<Color>,
+    pub head: Color,
+    // The body fades from body just behind the head to tail at the very end
+    pub body: Color,
+    pub tail: Color,
+    pub food: Color,
+    // Outlines so the head and food stand out by more than color
+    pub head_border: Option<Color>,
+    pub food_border: Option<Color>,
+    pub border_width: f32,
+    // A thin outline round every body segment, so the cells read apart from each other
+    pub body_outline: Option<Color>,
+    pub diamond_food: bool,
+    pub eyes: Color,
+}
+
+const CLASSIC_COLORS: BoardColors = BoardColors {
+    background: None,
+    head: Color::new(1.0, 0.5, 0.0, 1.0),
+    body: Color::new(0.3, 0.3, 0.0, 1.0),
+    tail: Color::new(0.12, 0.12, 0.0, 1.0),
+    food: Color::new(0.0, 0.0, 1.0, 1.0),
+    head_border: None,
+    food_border: None,
+    border_width: 0.0,
+    body_outline: None,
+    diamond_food: false,
+    eyes: Color::BLACK,
+};
+
+const DARK_COLORS: BoardColors = BoardColors {
+    background: None,
+    head: Color::new(0.95, 0.6, 0.2, 1.0),
+    body: Color::new(0.55, 0.75, 0.35, 1.0),
+    tail: Color::new(0.22, 0.35, 0.15, 1.0),
+    food: Color::new(0.9, 0.25, 0.3, 1.0),
+    head_border: None,
+    food_border: None,
+    border_width: 0.0,
+    body_outline: None,
+    diamond_food: false,
+    eyes: Color::BLACK,
+};
+
+const DESERT_COLORS: BoardColors = BoardColors {
+    background: None,
+    head: Color::new(0.6, 0.25, 0.1, 1.0),
+    body: Color::new(0.5, 0.38, 0.15, 1.0),
+    tail: Color::new(0.32, 0.22, 0.1, 1.0),
+    food: Color::new(0.15, 0.5, 0.2, 1.0),
+    head_border: None,
+    food_border: None,
+    border_width: 0.0,
+    body_outline: None,
+    diamond_food: false,
+    eyes: Color::BLACK,
+};
+
+// Blue against orange stays apart for red-green colorblindness (deuteranopia and protanopia)
+const BLUE_ORANGE_COLORS: BoardColors = BoardColors {
+    background: Some(Color::new(0.15, 0.15, 0.15, 1.0)),
+    head: Color::new(0.35, 0.7, 0.9, 1.0),
+    body: Color::new(0.0, 0.45, 0.7, 1.0),
+    tail: Color::new(0.0, 0.22, 0.4, 1.0),
+    food: Color::new(0.9, 0.6, 0.0, 1.0),
+    head_border: Some(Color::WHITE),
+    food_border: None,
+    border_width: 3.0,
+    body_outline: None,
+    diamond_food: true,
+    eyes: Color::BLACK,
+};
+
+// Teal against magenta stays apart for blue-yellow colorblindness (tritanopia)
+const TEAL_MAGENTA_COLORS: BoardColors = BoardColors {
+    background: Some(Color::new(0.15, 0.15, 0.15, 1.0)),
+    head: Color::new(0.6, 0.9, 0.85, 1.0),
+    body: Color::new(0.0, 0.6, 0.5, 1.0),
+    tail: Color::new(0.0, 0.3, 0.25, 1.0),
+    food: Color::new(0.9, 0.2, 0.5, 1.0),
+    head_border: Some(Color::WHITE),
+    food_border: None,
+    border_width: 3.0,
+    body_outline: None,
+    diamond_food: true,
+    eyes: Color::BLACK,
+};
+
+impl BoardColors {
+    // The same colors with the snake and food pushed lighter or darker as far as they need
+    // to go to stand out against background, when it's been changed from usual. Anything
+    // that already stands out is left alone
+    pub fn readable_on(&self, background: Color, usual: Color) -> BoardColors {
+        let readable = |color| readable(color, background, usual);
+        BoardColors {
+            head: readable(self.head),
+            body: readable(self.body),
+            tail: readable(self.tail),
+            food: readable(self.food),
+            ..*self
+        }
+    }
+
+    // Fraction is how far along the body, from 0 just behind the head to 1 at the tail
+    pub fn body_at(&self, fraction: f32) -> Color {
+        let lerp = |from: f32, to: f32| from + (to - from) * fraction;
+        Color::new(
+            lerp(self.body.r, self.tail.r),
+            lerp(self.body.g, self.tail.g),
+            lerp(self.body.b, self.tail.b),
+            lerp(self.body.a, self.tail.a),
+        )
+    }
+}
+
+fn mix(from: Color, to: Color, amount: f32) -> Color {
+    let lerp = |from: f32, to: f32| from + (to - from) * amount;
+    Color::new(
+        lerp(from.r, to.r),
+        lerp(from.g, to.g),
+        lerp(from.b, to.b),
+        lerp(from.a, to.a),
+    )
+}
+
+// Relative luminance, the way the WCAG contrast ratio works it out
+fn luminance(color: Color) -> f32 {
+    let linear = |c: f32| {
+        if c <= 0.03928 {
+            c / 12.92
+        } else {
+            ((c + 0.055) / 1.055).powf(2.4)
+        }
+    };
+    0.2126 * linear(color.r) + 0.7152 * linear(color.g) + 0.0722 * linear(color.b)
+}
+
+// From 1 for the same color up to 21 for black against white
+fn contrast(a: Color, b: Color) -> f32 {
+    let (a, b) = (luminance(a), luminance(b));
+    (a.max(b) + 0.05) / (a.min(b) + 0.05)
+}
+
+// The contrast ratio the snake and food are kept above when the background changes under
+// them. Three to one is the usual lowest for shapes that need to be made out
+const MIN_CONTRAST: f32 = 3.0;
+
+// Moves the color towards white on a dark background or black on a light one, only as far
+// as it has to. A color that never had MIN_CONTRAST against the usual background is only
+// kept as readable as it normally is, so the board looks the same whenever it's back to
+// usual. Found by halving, since a few steps are plenty
+fn readable(color: Color, background: Color, usual: Color) -> Color {
+    let target = MIN_CONTRAST.min(contrast(color, usual));
+    if contrast(color, background) >= target {
+        return color;
+    }
+    // Where white and black stand out from the background by the same amount
+    let towards = if luminance(background) < 0.18 {
+        Color::WHITE
+    } else {
+        Color::BLACK
+    };
+    let (mut low, mut high) = (0.0, 1.0);
+    for _ in 0..8 {
+        let middle = (low + high) / 2.0;
+        if contrast(mix(color, towards, middle), background) >= target {
+            high = middle;
+        } else {
+            low = middle;
+        }
+    }
+    mix(color, towards, high)
+}
+
+// Overrides whichever palette is picked while high contrast is on
+const HIGH_CONTRAST_COLORS: BoardColors = BoardColors {
+    background: Some(Color::new(0.02, 0.02, 0.02, 1.0)),
+    head: Color::new(1.0, 1.0, 0.0, 1.0),
+    body: Color::new(0.9, 0.85, 0.0, 1.0),
+    tail: Color::new(0.65, 0.6, 0.0, 1.0),
+    food: Color::new(1.0, 0.0, 1.0, 1.0),
+    head_border: Some(Color::WHITE),
+    food_border: Some(Color::WHITE),
+    border_width: 2.0,
+    body_outline: Some(Color::WHITE),
+    diamond_food: false,
+    eyes: Color::BLACK,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Palette {
+    Standard,
+    BlueOrange,
+    TealMagenta,
+}
+
+impl Palette {
+    const ALL: [Palette; 3] = [Palette::Standard, Palette::BlueOrange, Palette::TealMagenta];
+
+    pub fn key(self) -> &'static str {
+        match self {
+            Palette::Standard => "standard",
+            Palette::BlueOrange => "blue_orange",
+            Palette::TealMagenta => "teal_magenta",
+        }
+    }
+
+    // The standard palette goes with the theme
+    pub fn colors(self, theme: Theme) -> &'static BoardColors {
+        match self {
+            Palette::Standard => theme.board_colors(),
+            Palette::BlueOrange => &BLUE_ORANGE_COLORS,
+            Palette::TealMagenta => &TEAL_MAGENTA_COLORS,
+        }
+    }
+
+    pub fn step(self, delta: i32) -> Self {
+        step(&Self::ALL, self, delta)
+    }
+}
+
+// Where the on-screen D-pad for touchscreens goes, if anywhere
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DpadCorner {
+    Hidden,
+    BottomLeft,
+    BottomRight,
+}
+
+impl DpadCorner {
+    const ALL: [DpadCorner; 3] = [
+        DpadCorner::Hidden,
+        DpadCorner::BottomLeft,
+        DpadCorner::BottomRight,
+    ];
+
+    pub fn key(self) -> &'static str {
+        match self {
+            DpadCorner::Hidden => "hidden",
+            DpadCorner::BottomLeft => "bottom_left",
+            DpadCorner::BottomRight => "bottom_right",
+        }
+    }
+
+    pub fn step(self, delta: i32) -> Self {
+        step(&Self::ALL, self, delta)
+    }
+}
+
+// How direction input is read. Relative only uses left and right, turning the snake a
+// quarter turn from wherever it's heading
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ControlScheme {
+    Absolute,
+    Relative,
+    // For players with a single button. The way to turn cycles round on its own and any
+    // press takes whichever way it's showing
+    SingleSwitch,
+}
+
+impl ControlScheme {
+    const ALL: [ControlScheme; 3] = [
+        ControlScheme::Absolute,
+        ControlScheme::Relative,
+        ControlScheme::SingleSwitch,
+    ];
+
+    pub fn key(self) -> &'static str {
+        match self {
+            ControlScheme::Absolute => "absolute",
+            ControlScheme::Relative => "relative",
+            ControlScheme::SingleSwitch => "single_switch",
+        }
+    }
+
+    pub fn step(self, delta: i32) -> Self {
+        step(&Self::ALL, self, delta)
+    }
+}
+
+// What the mouse does during a game. Off leaves it for the menus
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MouseMode {
+    Off,
+    // The snake turns toward the cursor by itself
+    Follow,
+    // Clicking a cell sends the snake there by the shortest safe path
+    ClickToPath,
+}
+
+impl MouseMode {
+    const ALL: [MouseMode; 3] = [MouseMode::Off, MouseMode::Follow, MouseMode::ClickToPath];
+
+    pub fn key(self) -> &'static str {
+        match self {
+            MouseMode::Off => "off",
+            MouseMode::Follow => "follow",
+            MouseMode::ClickToPath => "click_to_path",
+        }
+    }
+
+    pub fn step(self, delta: i32) -> Self {
+        step(&Self::ALL, self, delta)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Language {
+    English,
+    Spanish,
+}
+
+impl Language {
+    const ALL: [Language; 2] = [Language::English, Language::Spanish];
+
+    // Always in the language itself, so you can find your way back from one you can't read
+    pub fn name(self) -> &'static str {
+        match self {
+            Language::English => "English",
+            Language::Spanish => "Español",
+        }
+    }
+
+    // Also the name of its file in resources/lang
+    pub fn key(self) -> &'static str {
+        match self {
+            Language::English => "en",
+            Language::Spanish => "es",
+        }
+    }
+
+    pub fn step(self, delta: i32) -> Self {
+        step(&Self::ALL, self, delta)
+    }
+}
+
+// Moves delta places through a list of options, stopping at either end
+fn step<T: Copy + PartialEq>(all: &[T], current: T, delta: i32) -> T {
+    let index = all
+        .iter()
+        .position(|&option| option == current)
+        .unwrap_or(0) as i32;
+    all[(index + delta).clamp(0, all.len() as i32 - 1) as usize]
+}
+
+// Everything the player can change that should stick around between runs
+#[derive(Debug, Clone)]
+pub struct Settings {
+    pub scoring: ScoringPreset,
+    // Both volumes are percentages
+    pub music_volume: u8,
+    pub sfx_volume: u8,
+    // Silences everything without losing either volume. M toggles it from anywhere
+    pub muted: bool,
+    // Just the music, leaving the sound effects as they are. N toggles it from anywhere
+    pub music_muted: bool,
+    // A quiet click every time the snake moves, to keep time by
+    pub tick_sound: bool,
+    pub speed: Speed,
+    // How many milliseconds ahead of the music rhythm mode moves the snake, for speakers
+    // or headphones that lag. [ and ] nudge it during a rhythm game
+    pub rhythm_offset: i16,
+    // Hitting the edge of the board kills you instead of wrapping around
+    pub walls: bool,
+    pub theme: Theme,
+    // Fade to black between screens instead of cutting straight over
+    pub fades: bool,
+    pub language: Language,
+    pub palette: Palette,
+    // Brighter board colors with outlines, and bigger, starker menus
+    pub high_contrast: bool,
+    // Turns off anything that pulses, flashes, slides or fades to black. Everything that
+    // moves for show checks this one flag rather than having a setting of its own
+    pub reduce_motion: bool,
+    // The snake slides from cell to cell between ticks instead of jumping
+    pub smooth_movement: bool,
+    // Thin lines between the cells of the board
+    pub grid_lines: bool,
+    // The cells the tail leaves behind fade out over a few ticks. Reduced motion hides it
+    pub fading_trail: bool,
+    // The board's background slowly goes from day to night and back over a long session
+    pub day_night: bool,
+    pub dpad: DpadCorner,
+    // Turns happen on the next frame instead of waiting for the next tick
+    pub responsive_turns: bool,
+    pub control_scheme: ControlScheme,
+    // For gamepads that drift, are wired oddly or just feel backwards
+    pub stick_deadzone: u8,
+    pub stick_swap_axes: bool,
+    pub stick_invert_x: bool,
+    pub stick_invert_y: bool,
+    pub mouse_mode: MouseMode,
+    // H, J, K and L steer like in vim. Off by default, since L is a long way from the
+    // other three and easy to hit by accident
+    pub vim_keys: bool,
+    // Pressing the same way twice quickly moves the snake two cells on the next tick
+    pub dash: bool,
+    // How far a finger has to travel, in pixels, before a swipe turns the snake. There's
+    // no room left for it on the settings screen, so it only lives in the settings file
+    pub swipe_distance: u8,
+    pub bindings: Bindings,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            scoring: ScoringPreset::Classic,
+            music_volume: 100,
+            sfx_volume: 100,
+            muted: false,
+            music_muted: false,
+            tick_sound: false,
+            speed: Speed::Normal,
+            rhythm_offset: 0,
+            walls: false,
+            theme: Theme::Classic,
+            fades: true,
+            language: Language::English,
+            palette: Palette::Standard,
+            high_contrast: false,
+            reduce_motion: false,
+            smooth_movement: true,
+            grid_lines: false,
+            fading_trail: false,
+            day_night: false,
+            dpad: DpadCorner::Hidden,
+            responsive_turns: false,
+            control_scheme: ControlScheme::Absolute,
+            stick_deadzone: DEFAULT_STICK_DEADZONE,
+            stick_swap_axes: false,
+            stick_invert_x: false,
+            stick_invert_y: false,
+            mouse_mode: MouseMode::Off,
+            vim_keys: false,
+            dash: false,
+            swipe_distance: DEFAULT_SWIPE_DISTANCE,
+            bindings: Bindings::default(),
+        }
+    }
+}
+
+impl Settings {
+    // Anything missing or unreadable just keeps its default
+    pub fn load(ctx: &Context) -> Self {
+        let entries = storage::load(ctx, SETTINGS_FILE);
+        let mut settings = Settings::default();
+
+        if let Some(scoring) = entries
+            .get("scoring")
+            .and_then(|key| ScoringPreset::from_key(key))
+        {
+            settings.scoring = scoring;
+        }
+        if let Some(volume) = entries.get("music_volume").and_then(|v| v.parse().ok()) {
+            settings.music_volume = u8::min(volume, 100);
+        }
+        if let Some(volume) = entries.get("sfx_volume").and_then(|v| v.parse().ok()) {
+            settings.sfx_volume = u8::min(volume, 100);
+        }
+        if let Some(muted) = entries.get("muted").and_then(|v| v.parse().ok()) {
+            settings.muted = muted;
+        }
+        if let Some(muted) = entries.get("music_muted").and_then(|v| v.parse().ok()) {
+            settings.music_muted = muted;
+        }
+        if let Some(tick_sound) = entries.get("tick_sound").and_then(|v| v.parse().ok()) {
+            settings.tick_sound = tick_sound;
+        }
+        if let Some(speed) = entries
+            .get("speed")
+            .and_then(|key| Speed::ALL.into_iter().find(|speed| speed.key() == key))
+        {
+            settings.speed = speed;
+        }
+        if let Some(offset) = entries.get("rhythm_offset").and_then(|v| v.parse().ok()) {
+            settings.rhythm_offset = i16::clamp(offset, -MAX_RHYTHM_OFFSET, MAX_RHYTHM_OFFSET);
+        }
+        if let Some(walls) = entries.get("walls").and_then(|v| v.parse().ok()) {
+            settings.walls = walls;
+        }
+        if let Some(theme) = entries
+            .get("theme")
+            .and_then(|key| Theme::ALL.into_iter().find(|theme| theme.key() == key))
+        {
+            settings.theme = theme;
+        }
+        if let Some(fades) = entries.get("fades").and_then(|v| v.parse().ok()) {
+            settings.fades = fades;
+        }
+        if let Some(language) = entries.get("language").and_then(|key| {
+            Language::ALL
+                .into_iter()
+                .find(|language| language.key() == key)
+        }) {
+            settings.language = language;
+        }
+        if let Some(palette) = entries.get("palette").and_then(|key| {
+            Palette::ALL
+                .into_iter()
+                .find(|palette| palette.key() == key)
+        }) {
+            settings.palette = palette;
+        }
+        if let Some(high_contrast) = entries.get("high_contrast").and_then(|v| v.parse().ok()) {
+            settings.high_contrast = high_contrast;
+        }
+        if let Some(reduce_motion) = entries.get("reduce_motion").and_then(|v| v.parse().ok()) {
+            settings.reduce_motion = reduce_motion;
+        }
+        if let Some(smooth) = entries.get("smooth_movement").and_then(|v| v.parse().ok()) {
+            settings.smooth_movement = smooth;
+        }
+        if let Some(grid_lines) = entries.get("grid_lines").and_then(|v| v.parse().ok()) {
+            settings.grid_lines = grid_lines;
+        }
+        if let Some(fading_trail) = entries.get("fading_trail").and_then(|v| v.parse().ok()) {
+            settings.fading_trail = fading_trail;
+        }
+        if let Some(day_night) = entries.get("day_night").and_then(|v| v.parse().ok()) {
+            settings.day_night = day_night;
+        }
+        if let Some(dpad) = entries
+            .get("dpad")
+            .and_then(|key| DpadCorner::ALL.into_iter().find(|dpad| dpad.key() == key))
+        {
+            settings.dpad = dpad;
+        }
+        if let Some(responsive_turns) = entries.get("responsive_turns").and_then(|v| v.parse().ok())
+        {
+            settings.responsive_turns = responsive_turns;
+        }
+        if let Some(scheme) = entries.get("control_scheme").and_then(|key| {
+            ControlScheme::ALL
+                .into_iter()
+                .find(|scheme| scheme.key() == key)
+        }) {
+            settings.control_scheme = scheme;
+        }
+        if let Some(deadzone) = entries.get("stick_deadzone").and_then(|v| v.parse().ok()) {
+            settings.stick_deadzone = u8::clamp(deadzone, MIN_STICK_DEADZONE, MAX_STICK_DEADZONE);
+        }
+        if let Some(swap) = entries.get("stick_swap_axes").and_then(|v| v.parse().ok()) {
+            settings.stick_swap_axes = swap;
+        }
+        if let Some(invert) = entries.get("stick_invert_x").and_then(|v| v.parse().ok()) {
+            settings.stick_invert_x = invert;
+        }
+        if let Some(invert) = entries.get("stick_invert_y").and_then(|v| v.parse().ok()) {
+            settings.stick_invert_y = invert;
+        }
+        if let Some(mouse_mode) = entries
+            .get("mouse_mode")
+            .and_then(|key| MouseMode::ALL.into_iter().find(|mode| mode.key() == key))
+        {
+            settings.mouse_mode = mouse_mode;
+        }
+        if let Some(vim_keys) = entries.get("vim_keys").and_then(|v| v.parse().ok()) {
+            settings.vim_keys = vim_keys;
+        }
+        if let Some(dash) = entries.get("dash").and_then(|v| v.parse().ok()) {
+            settings.dash = dash;
+        }
+        if let Some(distance) = entries.get("swipe_distance").and_then(|v| v.parse().ok()) {
+            settings.swipe_distance = u8::max(distance, MIN_SWIPE_DISTANCE);
+        }
+        settings.bindings = Bindings::from_names(|action| {
+            entries
+                .get(&format!("bind_{}", action.key()))
+                .map(String::as_str)
+        });
+
+        settings
+    }
+
+    pub fn board_colors(&self) -> &'static BoardColors {
+        if self.high_contrast {
+            &HIGH_CONTRAST_COLORS
+        } else {
+            self.palette.colors(self.theme)
+        }
+    }
+
+    pub fn menu_colors(&self) -> &'static MenuColors {
+        if self.high_contrast {
+            &HIGH_CONTRAST_MENU_COLORS
+        } else {
+            self.theme.menu_colors()
+        }
+    }
+
+    // Colorblind palettes and high contrast bring their own background, since not every
+    // theme has enough contrast with their colors
+    pub fn background(&self) -> Color {
+        self.board_colors()
+            .background
+            .unwrap_or(self.theme.background())
+    }
+
+    // Palettes with a background of their own keep it flat, since they picked it for contrast
+    pub fn checker(&self) -> Option<Color> {
+        match self.board_colors().background {
+            Some(_) => None,
+            None => Some(self.theme.checker()),
+        }
+    }
+
+    // Where the stick really is once it's been swapped and flipped the way it's set up
+    pub fn stick(&self, (x, y): (f32, f32)) -> (f32, f32) {
+        let (x, y) = if self.stick_swap_axes { (y, x) } else { (x, y) };
+        (
+            if self.stick_invert_x { -x } else { x },
+            if self.stick_invert_y { -y } else { y },
+        )
+    }
+
+    pub fn step_stick_deadzone(&mut self, delta: i32) {
+        self.stick_deadzone = (self.stick_deadzone as i32 + delta * STICK_DEADZONE_STEP as i32)
+            .clamp(MIN_STICK_DEADZONE as i32, MAX_STICK_DEADZONE as i32)
+            as u8;
+    }
+
+    pub fn step_rhythm_offset(&mut self, delta: i32) {
+        self.rhythm_offset = (self.rhythm_offset as i32 + delta * RHYTHM_OFFSET_STEP as i32)
+            .clamp(-MAX_RHYTHM_OFFSET as i32, MAX_RHYTHM_OFFSET as i32)
+            as i16;
+    }
+
+    pub fn save(&self, ctx: &Context) {
+        let mut entries = BTreeMap::new();
+        entries.insert("scoring".to_string(), self.scoring.key().to_string());
+        entries.insert("music_volume".to_string(), self.music_volume.to_string());
+        entries.insert("sfx_volume".to_string(), self.sfx_volume.to_string());
+        entries.insert("muted".to_string(), self.muted.to_string());
+        entries.insert("music_muted".to_string(), self.music_muted.to_string());
+        entries.insert("tick_sound".to_string(), self.tick_sound.to_string());
+        entries.insert("speed".to_string(), self.speed.key().to_string());
+        entries.insert("rhythm_offset".to_string(), self.rhythm_offset.to_string());
+        entries.insert("walls".to_string(), self.walls.to_string());
+        entries.insert("theme".to_string(), self.theme.key().to_string());
+        entries.insert("fades".to_string(), self.fades.to_string());
+        entries.insert("language".to_string(), self.language.key().to_string());
+        entries.insert("palette".to_string(), self.palette.key().to_string());
+        entries.insert("high_contrast".to_string(), self.high_contrast.to_string());
+        entries.insert("reduce_motion".to_string(), self.reduce_motion.to_string());
+        entries.insert(
+            "smooth_movement".to_string(),
+            self.smooth_movement.to_string(),
+        );
+        entries.insert("grid_lines".to_string(), self.grid_lines.to_string());
+        entries.insert("fading_trail".to_string(), self.fading_trail.to_string());
+        entries.insert("day_night".to_string(), self.day_night.to_string());
+        entries.insert("dpad".to_string(), self.dpad.key().to_string());
+        entries.insert(
+            "responsive_turns".to_string(),
+            self.responsive_turns.to_string(),
+        );
+        entries.insert(
+            "control_scheme".to_string(),
+            self.control_scheme.key().to_string(),
+        );
+        entries.insert(
+            "stick_deadzone".to_string(),
+            self.stick_deadzone.to_string(),
+        );
+        entries.insert(
+            "stick_swap_axes".to_string(),
+            self.stick_swap_axes.to_string(),
+        );
+        entries.insert(
+            "stick_invert_x".to_string(),
+            self.stick_invert_x.to_string(),
+        );
+        entries.insert(
+            "stick_invert_y".to_string(),
+            self.stick_invert_y.to_string(),
+        );
+        entries.insert("mouse_mode".to_string(), self.mouse_mode.key().to_string());
+        entries.insert("vim_keys".to_string(), self.vim_keys.to_string());
+        entries.insert("dash".to_string(), self.dash.to_string());
+        entries.insert(
+            "swipe_distance".to_string(),
+            self.swipe_distance.to_string(),
+        );
+        for (action, name) in self.bindings.names() {
+            entries.insert(format!("bind_{}", action.key()), name.to_string());
+        }
+        storage::save(ctx, SETTINGS_FILE, &entries);
+    }
+}
+
+// Steps a percentage up or down by VOLUME_STEP, staying within 0-100
+pub fn step_volume(volume: u8, delta: i32) -> u8 {
+    (volume as i32 + delta * VOLUME_STEP as i32).clamp(0, 100) as u8
+}
@@ -0,0 +1,94 @@
+use std::time::Duration;
+
+// A split is taken every time the snake's length reaches another multiple of this
+pub const SPLIT_INTERVAL: u32 = 50;
+
+// The run time at every split the current run has reached so far
+#[derive(Debug, Default)]
+pub struct SplitTracker {
+    times: Vec<Duration>,
+}
+
+impl SplitTracker {
+    // Call whenever the snake grows. Returns the index of the split if this growth just reached one
+    pub fn check(&mut self, num_segments: u32, run_time: Duration) -> Option<usize> {
+        let reached = (num_segments / SPLIT_INTERVAL) as usize;
+        if reached > self.times.len() {
+            self.times.push(run_time);
+            Some(self.times.len() - 1)
+        } else {
+            None
+        }
+    }
+
+    pub fn times(&self) -> &[Duration] {
+        &self.times
+    }
+}
+
+// How a split compared to the stored best at the same point
+#[derive(Debug, Clone, Copy)]
+pub struct SplitResult {
+    pub index: usize,
+    pub time: Duration,
+    // In milliseconds, negative when it was faster. None when there was nothing to compare
+    // against yet
+    pub delta: Option<i64>,
+}
+
+impl SplitResult {
+    pub fn new(index: usize, time: Duration, best: &[Duration]) -> Self {
+        SplitResult {
+            index,
+            time,
+            delta: best
+                .get(index)
+                .map(|&best| time.as_millis() as i64 - best.as_millis() as i64),
+        }
+    }
+
+    // A gold is a split faster than anything stored (or the first time reaching it at all)
+    pub fn gold(&self) -> bool {
+        self.delta.is_none_or(|delta| delta < 0)
+    }
+
+    // The length the snake was at when the split was taken
+    pub fn length(&self) -> u32 {
+        (self.index as u32 + 1) * SPLIT_INTERVAL
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn splits_compare_by_time_whatever_the_speed() {
+        // 150 ticks at 10 a second against 105 at 7 a second is 15s against 15s
+        let best = [Duration::from_secs(15)];
+        let even = SplitResult::new(0, Duration::from_millis(105_000) / 7, &best);
+        assert_eq!(even.delta, Some(0));
+        let slower = SplitResult::new(0, Duration::from_millis(15_400), &best);
+        assert_eq!(slower.delta, Some(400));
+        assert!(!slower.gold());
+        assert!(SplitResult::new(1, Duration::from_secs(40), &best).gold());
+    }
+
+    #[test]
+    fn a_split_is_taken_once_per_interval() {
+        let mut splits = SplitTracker::default();
+        assert_eq!(
+            splits.check(SPLIT_INTERVAL - 1, Duration::from_secs(1)),
+            None
+        );
+        assert_eq!(
+            splits.check(SPLIT_INTERVAL, Duration::from_secs(2)),
+            Some(0)
+        );
+        assert_eq!(
+            splits.check(SPLIT_INTERVAL + 1, Duration::from_secs(3)),
+            None
+        );
+        assert_eq!(splits.times(), [Duration::from_secs(2)]);
+    }
+}
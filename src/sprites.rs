@@ -0,0 +1,119 @@
+use crate::{Direction, GRID_CELL_SIZE, SEGMENT_CAP_RADIUS, SEGMENT_GAP};
+use ggez::graphics::{self, Color, DrawMode, Image, Mesh, Rect};
+use ggez::{Context, GameResult};
+
+// What the snake and food are drawn with, all made once at startup. The pictures are
+// optional, and any that aren't in resources are drawn as the shapes instead. Everything
+// is white or grey and tinted with the palette's colors, so it all changes with the
+// palette and theme. The shapes are built for the cell size, so they'd need building
+// again if that could ever change
+pub struct Sprites {
+    pub head: Option<Image>,
+    pub body: Option<Image>,
+    pub food: Option<Image>,
+    // The shapes are centered on the origin, so they go wherever and grow from the middle
+    pub cap: Mesh,
+    pub food_circle: Mesh,
+    pub food_diamond: Mesh,
+}
+
+impl Sprites {
+    pub fn load(ctx: &Context) -> GameResult<Self> {
+        let (w, h) = (GRID_CELL_SIZE.0 as f32, GRID_CELL_SIZE.1 as f32);
+        let cap = Rect::new(
+            -w / 2.0 + SEGMENT_GAP,
+            -h / 2.0 + SEGMENT_GAP,
+            w - 2.0 * SEGMENT_GAP,
+            h - 2.0 * SEGMENT_GAP,
+        );
+        Ok(Sprites {
+            head: load(ctx, "/snake_head.png"),
+            body: load(ctx, "/snake_body.png"),
+            food: load(ctx, "/food.png"),
+            cap: Mesh::new_rounded_rectangle(
+                ctx,
+                DrawMode::fill(),
+                cap,
+                SEGMENT_CAP_RADIUS,
+                Color::WHITE,
+            )?,
+            // Just touching the sides of the cell, so it's still plainly in the one it's in
+            food_circle: Mesh::new_circle(
+                ctx,
+                DrawMode::fill(),
+                [0.0, 0.0],
+                w / 2.0,
+                0.1,
+                Color::WHITE,
+            )?,
+            food_diamond: Mesh::new_polygon(
+                ctx,
+                DrawMode::fill(),
+                &[
+                    [0.0, -h / 2.0],
+                    [w / 2.0, 0.0],
+                    [0.0, h / 2.0],
+                    [-w / 2.0, 0.0],
+                ],
+                Color::WHITE,
+            )?,
+        })
+    }
+}
+
+// Not having one is normal, so only a file that's there but won't load gets a warning
+fn load(ctx: &Context, path: &str) -> Option<Image> {
+    if !ctx.fs.exists(path) {
+        return None;
+    }
+    match Image::from_path(ctx, path) {
+        Ok(image) => Some(image),
+        Err(e) => {
+            eprintln!("Could not load {}, so it'll be drawn plain: {}", path, e);
+            None
+        }
+    }
+}
+
+// One of the shapes, in the middle of the cell and sized to fit it
+pub fn draw_shape(canvas: &mut graphics::Canvas, shape: &Mesh, cell: Rect, color: Color) {
+    canvas.draw(
+        shape,
+        graphics::DrawParam::new()
+            .dest([cell.x + cell.w / 2.0, cell.y + cell.h / 2.0])
+            .scale([
+                cell.w / GRID_CELL_SIZE.0 as f32,
+                cell.h / GRID_CELL_SIZE.1 as f32,
+            ])
+            .color(color),
+    );
+}
+
+// Stretched over the cell and turned to face dir. Sprites are drawn facing up, so the
+// body and food just pass Up
+pub fn draw(
+    canvas: &mut graphics::Canvas,
+    image: &Image,
+    cell: Rect,
+    dir: Direction,
+    color: Color,
+) {
+    let rotation = match dir {
+        Direction::Up => 0.0,
+        Direction::Right => std::f32::consts::FRAC_PI_2,
+        Direction::Down => std::f32::consts::PI,
+        Direction::Left => -std::f32::consts::FRAC_PI_2,
+    };
+    canvas.draw(
+        image,
+        graphics::DrawParam::new()
+            .dest([cell.x + cell.w / 2.0, cell.y + cell.h / 2.0])
+            .offset([0.5, 0.5])
+            .rotation(rotation)
+            .scale([
+                cell.w / image.width() as f32,
+                cell.h / image.height() as f32,
+            ])
+            .color(color),
+    );
+}
@@ -0,0 +1,109 @@
+use crate::settings::MenuColors;
+use crate::strings::Strings;
+use crate::{draw_centered_text, SCREEN_SIZE};
+use ggez::graphics::{self, Text};
+use ggez::mint::Point2;
+use ggez::{Context, GameResult};
+
+#[derive(Debug, Clone, Copy)]
+pub enum RunEnd {
+    Won,
+    HitSelf,
+    HitWall,
+}
+
+// Only counts runs that actually finished, and only since the game was opened
+#[derive(Debug, Default)]
+pub struct SessionStats {
+    games_played: u32,
+    food_eaten: u32,
+    best_length: u32,
+    total_length: u32,
+    wins: u32,
+    self_deaths: u32,
+    wall_deaths: u32,
+}
+
+impl SessionStats {
+    pub fn record(&mut self, length: u32, food_eaten: u32, end: RunEnd) {
+        self.games_played += 1;
+        self.food_eaten += food_eaten;
+        self.best_length = self.best_length.max(length);
+        self.total_length += length;
+        match end {
+            RunEnd::Won => self.wins += 1,
+            RunEnd::HitSelf => self.self_deaths += 1,
+            RunEnd::HitWall => self.wall_deaths += 1,
+        }
+    }
+
+    fn average_length(&self) -> f32 {
+        if self.games_played == 0 {
+            0.0
+        } else {
+            self.total_length as f32 / self.games_played as f32
+        }
+    }
+
+    pub fn draw(
+        &self,
+        canvas: &mut graphics::Canvas,
+        ctx: &mut Context,
+        strings: &Strings,
+        colors: &MenuColors,
+    ) -> GameResult {
+        let mut title = Text::new(strings.get("stats.heading"));
+        title.set_scale(40.0);
+        draw_centered_text(canvas, ctx, &title, 60.0, colors.text)?;
+
+        let rows = [
+            ("stats.games_played", self.games_played.to_string()),
+            ("stats.food_eaten", self.food_eaten.to_string()),
+            ("stats.best_length", self.best_length.to_string()),
+            (
+                "stats.average_length",
+                format!("{:.1}", self.average_length()),
+            ),
+            ("stats.wins", self.wins.to_string()),
+            ("stats.self_deaths", self.self_deaths.to_string()),
+            ("stats.wall_deaths", self.wall_deaths.to_string()),
+        ];
+
+        // Labels right aligned against the middle, values left aligned after it
+        let middle = SCREEN_SIZE.0 / 2.0;
+        for (i, (label, value)) in rows.iter().enumerate() {
+            let y = 150.0 + i as f32 * 44.0;
+
+            let mut label = Text::new(strings.get(label));
+            label.set_scale(26.0);
+            let width = label.measure(ctx)?.x;
+            canvas.draw(
+                &label,
+                graphics::DrawParam::new()
+                    .dest(Point2 {
+                        x: middle - width - 16.0,
+                        y,
+                    })
+                    .color(colors.text),
+            );
+
+            let mut value = Text::new(value.as_str());
+            value.set_scale(26.0);
+            canvas.draw(
+                &value,
+                graphics::DrawParam::new()
+                    .dest(Point2 {
+                        x: middle + 16.0,
+                        y,
+                    })
+                    .color(colors.text),
+            );
+        }
+
+        let mut hint = Text::new(strings.get("back_hint"));
+        hint.set_scale(16.0);
+        draw_centered_text(canvas, ctx, &hint, SCREEN_SIZE.1 - 24.0, colors.text)?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,47 @@
+use ggez::Context;
+use std::collections::BTreeMap;
+use std::io::{Read, Write};
+
+// Everything we save is a flat list of key=value lines in the user directory.
+// ggez's filesystem reads from the resources folder and the user dirs, but only
+// ever writes to the user config dir so we don't have to think about where it goes.
+pub fn load(ctx: &Context, path: &str) -> BTreeMap<String, String> {
+    let mut contents = String::new();
+    let read = ctx
+        .fs
+        .open(path)
+        .and_then(|mut file| file.read_to_string(&mut contents).map_err(Into::into));
+    if read.is_err() {
+        // Missing (or unreadable) files just mean nothing has been saved yet
+        return BTreeMap::new();
+    }
+
+    parse(&contents)
+}
+
+// Lines without an = (blank lines, comments) are skipped
+pub fn parse(contents: &str) -> BTreeMap<String, String> {
+    let mut entries = BTreeMap::new();
+    for line in contents.lines() {
+        if let Some((key, value)) = line.split_once('=') {
+            entries.insert(key.trim().to_string(), value.trim().to_string());
+        }
+    }
+    entries
+}
+
+pub fn save(ctx: &Context, path: &str, entries: &BTreeMap<String, String>) {
+    let mut contents = String::new();
+    for (key, value) in entries {
+        contents.push_str(&format!("{}={}\n", key, value));
+    }
+
+    // Losing a save shouldn't take the game down with it, so just complain
+    let written = ctx
+        .fs
+        .create(path)
+        .and_then(|mut file| file.write_all(contents.as_bytes()).map_err(Into::into));
+    if let Err(e) = written {
+        eprintln!("Could not save {}: {}", path, e);
+    }
+}
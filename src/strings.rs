@@ -0,0 +1,43 @@
+use crate::settings::Language;
+use crate::storage;
+use ggez::Context;
+use std::collections::BTreeMap;
+use std::fmt::Display;
+
+// Built into the game so there's always something to fall back on, even without the
+// resources folder
+const ENGLISH: &str = include_str!("../resources/lang/en.txt");
+
+// All the text the player reads, looked up by key. Each language is a key=value file in
+// resources/lang, with {0}, {1}... wherever a value gets filled in
+pub struct Strings {
+    table: BTreeMap<String, String>,
+    english: BTreeMap<String, String>,
+}
+
+impl Strings {
+    pub fn load(ctx: &Context, language: Language) -> Self {
+        Strings {
+            table: storage::load(ctx, &format!("/lang/{}.txt", language.key())),
+            english: storage::parse(ENGLISH),
+        }
+    }
+
+    // Anything the language is missing comes out in English, and anything English is
+    // missing just shows its key so it's easy to spot
+    pub fn get(&self, key: &str) -> String {
+        self.table
+            .get(key)
+            .or_else(|| self.english.get(key))
+            .cloned()
+            .unwrap_or_else(|| key.to_string())
+    }
+
+    pub fn format(&self, key: &str, args: &[&dyn Display]) -> String {
+        let mut text = self.get(key);
+        for (i, arg) in args.iter().enumerate() {
+            text = text.replace(&format!("{{{}}}", i), &arg.to_string());
+        }
+        text
+    }
+}
@@ -0,0 +1,61 @@
+use crate::settings::BoardColors;
+use crate::{GridPosition, GRID_SIZE};
+use ggez::graphics::{self, Color};
+
+// Cells per second
+const SPEED: f32 = 12.0;
+const LENGTH: usize = 14;
+// How much of the snake's color shows through, so the buttons stay easy to read over it
+const DIM: f32 = 0.3;
+
+// The cells around the edge of the board, clockwise from the top left corner
+fn border_path() -> Vec<GridPosition> {
+    let (width, height) = GRID_SIZE;
+    let top = (0..width).map(|x| GridPosition::new(x, 0));
+    let right = (1..height).map(|y| GridPosition::new(width - 1, y));
+    let bottom = (0..width - 1)
+        .rev()
+        .map(|x| GridPosition::new(x, height - 1));
+    let left = (1..height - 1).rev().map(|y| GridPosition::new(0, y));
+    top.chain(right).chain(bottom).chain(left).collect()
+}
+
+// Purely for show on the title screen. It follows a fixed path and never touches the RNG
+// or anything else the real game uses
+pub struct TitleSnake {
+    path: Vec<GridPosition>,
+    // How many cells the head has moved along the path, fractions included
+    travelled: f32,
+}
+
+impl TitleSnake {
+    pub fn new() -> Self {
+        TitleSnake {
+            path: border_path(),
+            travelled: LENGTH as f32,
+        }
+    }
+
+    // Runs off frame time since menus don't tick
+    pub fn update(&mut self, seconds: f32) {
+        self.travelled = (self.travelled + SPEED * seconds) % self.path.len() as f32;
+    }
+
+    pub fn draw(&self, canvas: &mut graphics::Canvas, colors: &BoardColors) {
+        let head = self.travelled as usize;
+        for i in 0..LENGTH {
+            let pos = self.path[(head + self.path.len() - i) % self.path.len()];
+            let color = if i == 0 {
+                colors.head
+            } else {
+                colors.body_at((i - 1) as f32 / (LENGTH - 2) as f32)
+            };
+            canvas.draw(
+                &graphics::Quad,
+                graphics::DrawParam::new()
+                    .dest_rect(pos.into())
+                    .color(Color::new(color.r, color.g, color.b, DIM)),
+            );
+        }
+    }
+}
@@ -0,0 +1,86 @@
+use crate::{GridPosition, GRID_CELL_SIZE, SCREEN_SIZE};
+use ggez::graphics::{self, Color};
+use ggez::mint::Point2;
+use ggez::{Context, GameResult};
+use std::collections::VecDeque;
+
+const TRAIL_LENGTH: usize = 30;
+const LINE_WIDTH: f32 = 6.0;
+
+fn center(pos: GridPosition) -> Point2<f32> {
+    Point2 {
+        x: (pos.x as f32 + 0.5) * GRID_CELL_SIZE.0 as f32,
+        y: (pos.y as f32 + 0.5) * GRID_CELL_SIZE.1 as f32,
+    }
+}
+
+// Where a move that wrapped off one side of the board leaves it, and where it comes back on.
+// Only gets called for moves that did wrap
+fn wrap_points(from: GridPosition, to: GridPosition) -> (Point2<f32>, Point2<f32>) {
+    let (from, to) = (center(from), center(to));
+    if from.x != to.x {
+        // Went off the side the head was nearest
+        let (exit, enter) = if from.x > to.x {
+            (SCREEN_SIZE.0, 0.0)
+        } else {
+            (0.0, SCREEN_SIZE.0)
+        };
+        (Point2 { x: exit, y: from.y }, Point2 { x: enter, y: to.y })
+    } else {
+        let (exit, enter) = if from.y > to.y {
+            (SCREEN_SIZE.1, 0.0)
+        } else {
+            (0.0, SCREEN_SIZE.1)
+        };
+        (Point2 { x: from.x, y: exit }, Point2 { x: to.x, y: enter })
+    }
+}
+
+// The last TRAIL_LENGTH cells the head moved into, oldest first, so the loss screen can
+// show the moves that led up to it. Each one notes whether getting there wrapped round
+// the edge of the board
+#[derive(Debug, Default)]
+pub struct HeadTrail {
+    cells: VecDeque<(GridPosition, bool)>,
+}
+
+impl HeadTrail {
+    pub fn record(&mut self, pos: GridPosition, wrapped: bool) {
+        if self.cells.len() == TRAIL_LENGTH {
+            self.cells.pop_front();
+        }
+        self.cells.push_back((pos, wrapped));
+    }
+
+    pub fn clear(&mut self) {
+        self.cells.clear();
+    }
+
+    // Each step is its own line, getting more solid the more recent it is
+    pub fn draw(&self, canvas: &mut graphics::Canvas, ctx: &Context, color: Color) -> GameResult {
+        let steps = self.cells.len().saturating_sub(1);
+        for (i, (&(from, _), &(to, wrapped))) in
+            self.cells.iter().zip(self.cells.iter().skip(1)).enumerate()
+        {
+            let color = Color {
+                a: (i + 1) as f32 / steps as f32,
+                ..color
+            };
+
+            // A wrap gets two short strokes, off one edge and back on at the other,
+            // rather than a line right across the board
+            let strokes = if wrapped {
+                let (exit, enter) = wrap_points(from, to);
+                vec![[center(from), exit], [enter, center(to)]]
+            } else {
+                vec![[center(from), center(to)]]
+            };
+            for stroke in strokes {
+                let line = graphics::Mesh::new_line(ctx, &stroke, LINE_WIDTH, color)?;
+                canvas.draw(&line, graphics::DrawParam::new());
+            }
+        }
+
+        Ok(())
+    }
+}